@@ -0,0 +1,323 @@
+use crate::docker::DockerManager;
+use crate::ports::PortAllocator;
+use crate::project::ProjectManager;
+use crate::schema::{parse_project, PROJECT_SCHEMA_VERSION};
+use crate::state::{Project, ProjectStatus};
+use crate::tls::{CertificateManager, PROJECT_CERTS_DIR};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+const ARCHIVE_EXTENSION: &str = ".godmode.tar.gz";
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database.sql";
+// Everything from the project directory lives under this prefix
+const PROJECT_PREFIX: &str = "project";
+
+// Directories that are never archived, wherever they appear; they are
+// reinstalled or regenerated on import
+const EXCLUDED_DIRS: &[&str] = &["vendor", "node_modules", ".git"];
+
+// Paths relative to the project root that only make sense on this machine
+const EXCLUDED_PATHS: &[&str] = &["backups", "src/backups", PROJECT_CERTS_DIR];
+
+// Ignored by Laravel's .gitignore, but the project does not run without them
+const ALWAYS_INCLUDED: &[&str] = &[".env", "src/.env"];
+
+/// Name an imported database dump gets in the project's `backups` directory.
+const IMPORTED_DUMP_NAME: &str = "imported_from_archive.sql";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub project_name: String,
+    pub template: String,
+    pub exported_at: DateTime<Utc>,
+    pub includes_database: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub file_count: usize,
+    pub size_bytes: u64,
+    pub includes_database: bool,
+}
+
+/// Packs a project into a single `.godmode.tar.gz` that can be restored on
+/// another machine, and unpacks such archives into new projects.
+pub struct ProjectArchive;
+
+impl ProjectArchive {
+    /// Writes the archive to `destination`, a file path or a directory to
+    /// create `<name>-<date>.godmode.tar.gz` in.
+    pub fn export(project: &Project, destination: &str, include_database: bool) -> Result<ExportSummary, String> {
+        let mut archive_path = PathBuf::from(destination);
+        if archive_path.is_dir() {
+            archive_path = archive_path.join(format!(
+                "{}-{}{}",
+                project.name,
+                chrono::Local::now().format("%Y%m%d_%H%M%S"),
+                ARCHIVE_EXTENSION
+            ));
+        }
+
+        // Dump first so a database failure leaves no half-written archive behind
+        let dump = if include_database { Some(Self::database_dump(project)?) } else { None };
+
+        let result = Self::write_archive(project, &archive_path, dump.as_ref().map(|(path, _)| path.as_path()));
+        if let Some((path, true)) = &dump {
+            let _ = fs::remove_file(path);
+        }
+        if result.is_err() {
+            let _ = fs::remove_file(&archive_path);
+        }
+        let file_count = result?;
+
+        let size_bytes = fs::metadata(&archive_path).map(|m| m.len()).unwrap_or(0);
+        println!("[Archive] Exported {} ({} files) to {:?}", project.name, file_count, archive_path);
+
+        Ok(ExportSummary {
+            path: archive_path.to_string_lossy().to_string(),
+            file_count,
+            size_bytes,
+            includes_database: include_database,
+        })
+    }
+
+    /// A fresh dump when the project is running, else its newest backup.
+    /// The flag tells whether the file is a temporary one to clean up.
+    fn database_dump(project: &Project) -> Result<(PathBuf, bool), String> {
+        if !project.config.services.mysql {
+            return Err("Only MySQL databases can be included in an archive".to_string());
+        }
+
+        if project.status == ProjectStatus::Running {
+            let temp = std::env::temp_dir().join(format!("godmode-dump-{}.sql", Uuid::new_v4().simple()));
+            if let Err(e) = DockerManager::dump_database_to(&project.path, &project.name, &temp) {
+                let _ = fs::remove_file(&temp);
+                return Err(e);
+            }
+            return Ok((temp, true));
+        }
+
+        let project_path = Path::new(&project.path);
+        let newest = ["backups", "src/backups"]
+            .iter()
+            .filter_map(|dir| fs::read_dir(project_path.join(dir)).ok())
+            .flat_map(|entries| entries.flatten())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|ext| ext == "sql").unwrap_or(false))
+            .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+
+        newest
+            .map(|path| (path, false))
+            .ok_or_else(|| "Start the project or create a backup to include the database".to_string())
+    }
+
+    fn write_archive(project: &Project, archive_path: &Path, dump: Option<&Path>) -> Result<usize, String> {
+        let file = File::create(archive_path).map_err(|e| format!("Failed to create archive: {}", e))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        builder.follow_symlinks(false);
+
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            project_name: project.name.clone(),
+            template: project.template.clone(),
+            exported_at: Utc::now(),
+            includes_database: dump.is_some(),
+        };
+        let manifest_json =
+            serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.exported_at.timestamp() as u64);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, MANIFEST_ENTRY, manifest_json.as_slice())
+            .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+        let root = Path::new(&project.path);
+        let files = Self::collect_files(root);
+        for relative in &files {
+            builder
+                .append_path_with_name(root.join(relative), Path::new(PROJECT_PREFIX).join(relative))
+                .map_err(|e| format!("Failed to add {} to archive: {}", relative.display(), e))?;
+        }
+
+        if let Some(dump) = dump {
+            builder
+                .append_path_with_name(dump, DATABASE_ENTRY)
+                .map_err(|e| format!("Failed to add database dump to archive: {}", e))?;
+        }
+
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+        Ok(files.len())
+    }
+
+    /// Project files to archive, relative to `root`, honouring `.gitignore`
+    /// and `.godmodeignore` files.
+    fn collect_files(root: &Path) -> Vec<PathBuf> {
+        let walk_root = root.to_path_buf();
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .git_ignore(true)
+            .git_global(false)
+            .git_exclude(false)
+            .require_git(false)
+            .parents(false)
+            .add_custom_ignore_filename(".godmodeignore")
+            .filter_entry(move |entry| {
+                let name = entry.file_name().to_string_lossy();
+                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) && EXCLUDED_DIRS.contains(&name.as_ref()) {
+                    return false;
+                }
+                let relative = entry.path().strip_prefix(&walk_root).unwrap_or(entry.path());
+                !EXCLUDED_PATHS.iter().any(|excluded| relative == Path::new(excluded))
+            })
+            .build();
+
+        let mut files: Vec<PathBuf> = walker
+            .flatten()
+            .filter(|entry| entry.file_type().map(|t| !t.is_dir()).unwrap_or(false))
+            .filter_map(|entry| entry.path().strip_prefix(root).ok().map(|p| p.to_path_buf()))
+            .collect();
+
+        for path in ALWAYS_INCLUDED.iter().map(PathBuf::from) {
+            if root.join(&path).is_file() && !files.contains(&path) {
+                files.push(path);
+            }
+        }
+
+        files.sort();
+        files
+    }
+
+    /// Reads the manifest without unpacking anything else.
+    pub fn read_manifest(archive_path: &str) -> Result<ArchiveManifest, String> {
+        let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+        for entry in archive.entries().map_err(|e| format!("Failed to read archive: {}", e))? {
+            let entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+            let is_manifest = entry.path().map(|p| p == Path::new(MANIFEST_ENTRY)).unwrap_or(false);
+            if is_manifest {
+                return serde_json::from_reader(entry).map_err(|e| format!("Invalid archive manifest: {}", e));
+            }
+        }
+
+        Err("Not a GodMode project archive (no manifest found)".to_string())
+    }
+
+    /// Unpacks an archive into a new project with a fresh id and freshly
+    /// allocated ports. Returns the project and the path of the database
+    /// dump, if the archive carried one, for the caller to load once the
+    /// containers are up.
+    pub fn import(archive_path: &str, name: Option<&str>) -> Result<(Project, Option<PathBuf>), String> {
+        let manifest = Self::read_manifest(archive_path)?;
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(format!(
+                "This archive was made by a newer version of GodMode (format {}); please update to import it",
+                manifest.format_version
+            ));
+        }
+
+        let name = name.map(|n| n.trim()).filter(|n| !n.is_empty()).unwrap_or(&manifest.project_name).to_string();
+        let projects_dir = ProjectManager::ensure_projects_dir()?;
+        let project_path = Path::new(&projects_dir).join(&name);
+        if project_path.exists() {
+            return Err(format!("Project '{}' already exists", name));
+        }
+
+        let result = Self::unpack(archive_path, &project_path)
+            .and_then(|dump| Self::adopt(&project_path, &manifest, &name).map(|project| (project, dump)));
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&project_path);
+        }
+        result
+    }
+
+    fn unpack(archive_path: &str, project_path: &Path) -> Result<Option<PathBuf>, String> {
+        let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        fs::create_dir_all(project_path).map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        let mut dump = None;
+        for entry in archive.entries().map_err(|e| format!("Failed to read archive: {}", e))? {
+            let mut entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+            let entry_path = entry.path().map_err(|e| format!("Invalid path in archive: {}", e))?.into_owned();
+
+            let target = if entry_path == Path::new(DATABASE_ENTRY) {
+                let target = project_path.join("backups").join(IMPORTED_DUMP_NAME);
+                dump = Some(target.clone());
+                target
+            } else if let Ok(relative) = entry_path.strip_prefix(PROJECT_PREFIX) {
+                // Never let an entry escape the project directory
+                if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                    return Err(format!("Refusing to extract unsafe path {}", entry_path.display()));
+                }
+                project_path.join(relative)
+            } else {
+                continue;
+            };
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            entry
+                .unpack(&target)
+                .map_err(|e| format!("Failed to extract {}: {}", entry_path.display(), e))?;
+        }
+
+        Ok(dump)
+    }
+
+    /// Turns the unpacked files into a project of this machine: new id,
+    /// name, ports and certificate.
+    fn adopt(project_path: &Path, manifest: &ArchiveManifest, name: &str) -> Result<Project, String> {
+        let config_path = project_path.join(".godmode.json");
+        let content = fs::read_to_string(&config_path).map_err(|e| format!("Archive has no project config: {}", e))?;
+        let original = parse_project(&content).map_err(|e| format!("Invalid project config in archive: {}", e))?;
+
+        if name != manifest.project_name {
+            ProjectManager::rename_references(project_path, &manifest.project_name, name, &["docker-compose.yml", ".env", "Makefile", "src/.env"])?;
+        }
+
+        let mut config = original.config.clone();
+        config.ports = PortAllocator::allocate(&original.config.ports, &original.config.services, None)?;
+
+        let now = Utc::now();
+        let project = Project {
+            schema_version: PROJECT_SCHEMA_VERSION,
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            template: original.template,
+            path: project_path.to_string_lossy().to_string(),
+            created_at: now,
+            updated_at: now,
+            config,
+            status: ProjectStatus::Stopped,
+            status_message: None,
+        };
+
+        ProjectManager::rewrite_ports(&project, &original.config.ports)?;
+        CertificateManager::ensure_project_certificate(&project)?;
+        ProjectManager::save_project(&project)?;
+
+        println!("[Archive] Imported {} as {}", manifest.project_name, project.name);
+        Ok(project)
+    }
+}
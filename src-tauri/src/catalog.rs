@@ -0,0 +1,363 @@
+use crate::project::ProjectManager;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+/// File at the top of a template pack describing it.
+const PACK_MANIFEST: &str = "pack.json";
+/// Folder in a search root with partials every template can use.
+pub const SHARED_PARTIALS_DIR: &str = "_partials";
+
+/// Where a template was found. Earlier variants take precedence: a user
+/// template overrides a team one of the same type, which overrides packs,
+/// which override the templates shipped with the app.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateSource {
+    User,
+    Team,
+    Pack,
+    Bundled,
+}
+
+/// A directory searched for templates, one folder per template type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateRoot {
+    pub path: String,
+    pub source: TemplateSource,
+    /// The pack's name, for pack roots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack: Option<String>,
+}
+
+/// A template as resolved across the search roots.
+#[derive(Debug, Clone)]
+pub struct CatalogTemplate {
+    pub template_type: String,
+    pub config: serde_json::Value,
+    pub root: TemplateRoot,
+    /// Lower-precedence roots with a template of the same type.
+    pub overrides: Vec<TemplateSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePack {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub path: String,
+    /// Template types the pack provides.
+    pub templates: Vec<String>,
+    pub installed_at: Option<DateTime<Utc>>,
+}
+
+/// Finds templates across the user, team, pack and bundled directories and
+/// installs template packs.
+pub struct TemplateCatalog;
+
+impl TemplateCatalog {
+    /// Templates the user added or overrides locally.
+    pub fn user_dir() -> PathBuf {
+        dirs::home_dir().unwrap_or_default().join(".laravel-godmode").join("templates")
+    }
+
+    /// Where installed packs live, one folder per pack.
+    pub fn packs_dir() -> PathBuf {
+        dirs::home_dir().unwrap_or_default().join(".laravel-godmode").join("template-packs")
+    }
+
+    /// The existing search roots, highest precedence first. `bundled` is the
+    /// app's own templates directory; packs are ordered by name.
+    pub fn roots(bundled: &str) -> Vec<TemplateRoot> {
+        let root = |path: PathBuf, source, pack| TemplateRoot {
+            path: path.to_string_lossy().to_string(),
+            source,
+            pack,
+        };
+
+        let mut roots = vec![root(Self::user_dir(), TemplateSource::User, None)];
+        if let Some(team) = ProjectManager::load_settings().team_templates_path.filter(|p| !p.trim().is_empty()) {
+            roots.push(root(PathBuf::from(team), TemplateSource::Team, None));
+        }
+        let mut packs: Vec<PathBuf> = fs::read_dir(Self::packs_dir())
+            .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| Self::is_installed_pack(p)).collect())
+            .unwrap_or_default();
+        packs.sort();
+        for pack in packs {
+            let name = pack.file_name().map(|n| n.to_string_lossy().to_string());
+            roots.push(root(pack, TemplateSource::Pack, name));
+        }
+        roots.push(root(PathBuf::from(bundled), TemplateSource::Bundled, None));
+
+        // The bundled path falls back to the user directory when the app has none of its own
+        let mut seen = HashSet::new();
+        roots.retain(|r| {
+            let path = Path::new(&r.path);
+            path.is_dir() && seen.insert(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+        });
+        roots
+    }
+
+    /// The directory of `template_type` in the highest-precedence root that
+    /// has it. Falls back to the bundled location, so a missing template
+    /// reports the usual "failed to read" errors.
+    pub fn template_dir(bundled: &str, template_type: &str) -> PathBuf {
+        Self::find_template(&Self::roots(bundled), bundled, template_type)
+    }
+
+    /// `template_dir` among `roots` already listed by `roots`.
+    pub fn find_template(roots: &[TemplateRoot], bundled: &str, template_type: &str) -> PathBuf {
+        roots
+            .iter()
+            .map(|root| Path::new(&root.path).join(template_type))
+            .find(|dir| dir.join("config.json").is_file())
+            .unwrap_or_else(|| Path::new(bundled).join(template_type))
+    }
+
+    /// Folders to load partials from, in the order to register them so that
+    /// later ones win: shared partials of `roots` from the lowest-precedence
+    /// one up, then the template's own in `template_dir`.
+    pub fn partial_dirs(roots: &[TemplateRoot], template_dir: &Path) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = roots
+            .iter()
+            .rev()
+            .map(|root| Path::new(&root.path).join(SHARED_PARTIALS_DIR))
+            .collect();
+        dirs.push(template_dir.join("partials"));
+        dirs
+    }
+
+    /// Every template type, as resolved by precedence, sorted by type.
+    pub fn list(bundled: &str) -> Vec<CatalogTemplate> {
+        let mut templates: Vec<CatalogTemplate> = Vec::new();
+        for root in Self::roots(bundled) {
+            let Ok(entries) = fs::read_dir(&root.path) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let template_type = entry.file_name().to_string_lossy().to_string();
+                let Some(config) = fs::read_to_string(entry.path().join("config.json"))
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                else {
+                    continue;
+                };
+
+                match templates.iter_mut().find(|t| t.template_type == template_type) {
+                    Some(existing) => existing.overrides.push(root.source),
+                    None => templates.push(CatalogTemplate {
+                        template_type,
+                        config,
+                        root: root.clone(),
+                        overrides: Vec::new(),
+                    }),
+                }
+            }
+        }
+        templates.sort_by(|a, b| a.template_type.cmp(&b.template_type));
+        templates
+    }
+
+    pub fn list_packs() -> Vec<TemplatePack> {
+        let Ok(entries) = fs::read_dir(Self::packs_dir()) else {
+            return Vec::new();
+        };
+        let mut packs: Vec<TemplatePack> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| Self::is_installed_pack(path))
+            .filter_map(|path| Self::read_pack(&path).ok())
+            .collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        packs
+    }
+
+    /// Installs the pack in `source`, a directory or a `.tar.gz` archive
+    /// with a `pack.json` at its top (or inside its single top folder). An
+    /// installed pack of the same name is replaced.
+    pub fn install_pack(source: &str) -> Result<TemplatePack, String> {
+        let source = Path::new(source);
+        let packs_dir = Self::packs_dir();
+        fs::create_dir_all(&packs_dir).map_err(|e| format!("Failed to create packs directory: {}", e))?;
+
+        // Staged next to the packs so a broken pack never replaces a working one
+        let staging = packs_dir.join(format!(".install-{}", Uuid::new_v4()));
+        let result = Self::stage(source, &staging).and_then(|pack_root| Self::adopt(&pack_root, &packs_dir));
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+
+    /// Whether `path` in the packs directory is a pack, rather than one
+    /// still being staged by `install_pack` in a dot-folder.
+    fn is_installed_pack(path: &Path) -> bool {
+        let staging = path.file_name().map(|n| n.to_string_lossy().starts_with('.')).unwrap_or(true);
+        !staging && path.join(PACK_MANIFEST).is_file()
+    }
+
+    pub fn remove_pack(name: &str) -> Result<(), String> {
+        let path = Self::packs_dir().join(name);
+        if !Self::is_valid_pack_name(name) || !path.join(PACK_MANIFEST).is_file() {
+            return Err(format!("Template pack '{}' is not installed", name));
+        }
+        fs::remove_dir_all(&path).map_err(|e| format!("Failed to remove template pack: {}", e))?;
+        println!("[Catalog] Removed template pack {}", name);
+        Ok(())
+    }
+
+    /// Copies or unpacks `source` into `staging`. Returns the folder holding `pack.json`.
+    fn stage(source: &Path, staging: &Path) -> Result<PathBuf, String> {
+        if source.is_dir() {
+            Self::copy_dir(source, staging)?;
+        } else if source.is_file() {
+            Self::unpack(source, staging)?;
+        } else {
+            return Err(format!("Template pack not found at {}", source.display()));
+        }
+
+        if staging.join(PACK_MANIFEST).is_file() {
+            return Ok(staging.to_path_buf());
+        }
+        let entries: Vec<PathBuf> = fs::read_dir(staging)
+            .map_err(|e| format!("Failed to read template pack: {}", e))?
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        match entries.as_slice() {
+            [only] if only.join(PACK_MANIFEST).is_file() => Ok(only.clone()),
+            _ => Err(format!("Not a template pack (no {} found)", PACK_MANIFEST)),
+        }
+    }
+
+    /// Checks the staged pack and moves it into place.
+    fn adopt(pack_root: &Path, packs_dir: &Path) -> Result<TemplatePack, String> {
+        let manifest = Self::read_manifest(pack_root)?;
+        if !Self::is_valid_pack_name(&manifest.name) {
+            return Err(format!(
+                "Invalid template pack name '{}' (use letters, digits, '-' and '_')",
+                manifest.name
+            ));
+        }
+
+        let templates = Self::pack_templates(pack_root);
+        if templates.is_empty() {
+            return Err(format!("Template pack '{}' has no templates (folders with a config.json)", manifest.name));
+        }
+        for template in &templates {
+            let config = fs::read_to_string(pack_root.join(template).join("config.json"))
+                .map_err(|e| format!("Failed to read {}/config.json: {}", template, e))?;
+            serde_json::from_str::<serde_json::Value>(&config)
+                .map_err(|e| format!("Invalid {}/config.json in template pack: {}", template, e))?;
+        }
+
+        fs::write(pack_root.join(".installed"), Utc::now().to_rfc3339())
+            .map_err(|e| format!("Failed to record installation: {}", e))?;
+        let target = packs_dir.join(&manifest.name);
+        if let Ok(previous) = Self::read_manifest(&target) {
+            println!("[Catalog] Replacing template pack {} {} with {}", manifest.name, previous.version, manifest.version);
+            fs::remove_dir_all(&target).map_err(|e| format!("Failed to remove the installed pack: {}", e))?;
+        }
+        fs::rename(pack_root, &target).map_err(|e| format!("Failed to install template pack: {}", e))?;
+
+        println!("[Catalog] Installed template pack {} {} ({})", manifest.name, manifest.version, templates.join(", "));
+        Self::read_pack(&target)
+    }
+
+    fn read_pack(path: &Path) -> Result<TemplatePack, String> {
+        let manifest = Self::read_manifest(path)?;
+        let installed_at = fs::read_to_string(path.join(".installed"))
+            .ok()
+            .and_then(|at| DateTime::parse_from_rfc3339(at.trim()).ok())
+            .map(|at| at.with_timezone(&Utc));
+
+        Ok(TemplatePack {
+            name: manifest.name,
+            version: manifest.version,
+            description: manifest.description,
+            path: path.to_string_lossy().to_string(),
+            templates: Self::pack_templates(path),
+            installed_at,
+        })
+    }
+
+    fn read_manifest(path: &Path) -> Result<PackManifest, String> {
+        let content = fs::read_to_string(path.join(PACK_MANIFEST)).map_err(|e| format!("Failed to read {}: {}", PACK_MANIFEST, e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", PACK_MANIFEST, e))
+    }
+
+    fn pack_templates(path: &Path) -> Vec<String> {
+        let mut templates: Vec<String> = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.path().join("config.json").is_file())
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        templates.sort();
+        templates
+    }
+
+    fn is_valid_pack_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    fn copy_dir(source: &Path, target: &Path) -> Result<(), String> {
+        for entry in WalkDir::new(source) {
+            let entry = entry.map_err(|e| format!("Failed to read template pack: {}", e))?;
+            let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+            let destination = target.join(relative);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&destination).map_err(|e| format!("Failed to create directory: {}", e))?;
+            } else if entry.file_type().is_file() {
+                fs::copy(entry.path(), &destination)
+                    .map_err(|e| format!("Failed to copy {}: {}", relative.display(), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn unpack(archive_path: &Path, target: &Path) -> Result<(), String> {
+        let file = File::open(archive_path).map_err(|e| format!("Failed to open template pack: {}", e))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        fs::create_dir_all(target).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+        for entry in archive.entries().map_err(|e| format!("Failed to read template pack: {}", e))? {
+            let mut entry = entry.map_err(|e| format!("Failed to read template pack: {}", e))?;
+            let entry_path = entry.path().map_err(|e| format!("Invalid path in template pack: {}", e))?.into_owned();
+
+            // Never let an entry escape the staging directory
+            let relative: PathBuf = entry_path.components().filter(|c| !matches!(c, Component::CurDir)).collect();
+            if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(format!("Refusing to extract unsafe path {}", entry_path.display()));
+            }
+            // Links could point anywhere, and templates have no use for them
+            let kind = entry.header().entry_type();
+            if relative.as_os_str().is_empty() || !(kind.is_file() || kind.is_dir()) {
+                continue;
+            }
+
+            let destination = target.join(&relative);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            entry
+                .unpack(&destination)
+                .map_err(|e| format!("Failed to extract {}: {}", entry_path.display(), e))?;
+        }
+        Ok(())
+    }
+}
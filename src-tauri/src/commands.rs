@@ -1,12 +1,30 @@
-use crate::docker::DockerManager;
+use crate::archive::{ArchiveManifest, ExportSummary, ProjectArchive};
+use crate::catalog::{TemplateCatalog, TemplatePack, TemplateRoot, TemplateSource};
+use crate::detect::DetectionReport;
+use crate::docker::{DockerManager, DEFAULT_HEALTH_TIMEOUT_SECS};
+use crate::engine::EngineClient;
+use crate::hosts::{HostsManager, HostsPreview};
+use crate::git::GitImporter;
+use crate::jobs::{JobContext, JobInfo, JobKind, JobRegistry};
+use crate::logs::{LogLine, LogStreamInfo, LogStreamOptions};
+use crate::metrics::{MetricsSamplerInfo, ProjectMetrics};
+use crate::ports::{PortAllocator, PortConflict};
+use crate::preview::{TemplatePreview, TemplatePreviewer};
 use crate::project::ProjectManager;
-use crate::state::{AppState, Project, ProjectConfig, ProjectStatus, Settings, ServiceStatus, SupervisorStatus};
-use crate::template::{create_project_from_template, save_project};
+use crate::regenerate::{ConfigUpdate, ProjectRegenerator, RegenerateOutcome};
+use crate::proxy::{ProxyManager, ProxyStatus};
+use crate::relocate::ProjectRelocator;
+use crate::schema::{self, LoadDiagnostic};
+use crate::state::{AppState, ContainerRuntimeInfo, ContainerStats, PortConfig, Project, ProjectConfig, ProjectStatus, ServiceConfig, Settings, ServiceStatus, SupervisorStatus};
+use crate::template::{create_project_from_template, load_template_config, render_template_files, save_project, RenderedFile, TemplateEngine};
+use crate::terminal::{TerminalOptions, TerminalSessionInfo};
+use crate::tinker::{TinkerResult, TinkerSessionInfo, DEFAULT_EVAL_TIMEOUT_SECS};
+use crate::tls::{CertificateAuthorityInfo, CertificateInfo, CertificateManager};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::State;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +34,13 @@ pub struct CreateProjectRequest {
     pub config: ProjectConfig,
 }
 
+/// An imported project and what was detected in its code.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub project: Project,
+    pub detection: DetectionReport,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TemplateInfo {
     pub name: String,
@@ -23,6 +48,14 @@ pub struct TemplateInfo {
     pub icon: String,
     pub template_type: String,
     pub category: String,
+    pub version: Option<String>,
+    /// Where the template used is found.
+    pub source: TemplateSource,
+    /// The pack providing it, for pack templates.
+    pub pack: Option<String>,
+    pub path: String,
+    /// Other sources with a template of this type, which this one overrides.
+    pub overrides: Vec<TemplateSource>,
 }
 
 // ============ Project Commands ============
@@ -34,10 +67,12 @@ pub fn get_projects() -> Result<Vec<Project>, String> {
 }
 
 #[tauri::command]
-pub fn create_project(request: CreateProjectRequest) -> Result<Project, String> {
+pub fn create_project(mut request: CreateProjectRequest) -> Result<Project, String> {
     println!("[GodMode] Creating project: {}", request.name);
     println!("[GodMode] Template: {}", request.template);
 
+    request.config.ports = PortAllocator::allocate(&request.config.ports, &request.config.services, None)?;
+
     let projects_dir = ProjectManager::ensure_projects_dir()?;
     println!("[GodMode] Projects dir: {}", projects_dir);
 
@@ -56,13 +91,59 @@ pub fn create_project(request: CreateProjectRequest) -> Result<Project, String>
 
     save_project(&project)?;
     println!("[GodMode] Project saved!");
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
 
     Ok(project)
 }
 
+/// Re-reads every registered project and picks up new ones in the projects directory.
+#[tauri::command]
+pub fn rescan_projects(state: State<'_, Mutex<AppState>>) -> Result<Vec<Project>, String> {
+    let registry = state.lock().unwrap().registry.clone();
+    let projects = registry.rescan()?;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(projects)
+}
+
+/// Tracks an existing GodMode project that lives outside the projects directory.
+#[tauri::command]
+pub fn register_project(state: State<'_, Mutex<AppState>>, path: String) -> Result<Project, String> {
+    let registry = state.lock().unwrap().registry.clone();
+    let project = registry.register(&path)?;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(project)
+}
+
+/// Stops tracking a project, leaving its files and containers alone.
+#[tauri::command]
+pub fn unregister_project(state: State<'_, Mutex<AppState>>, project_id: String) -> Result<(), String> {
+    let registry = state.lock().unwrap().registry.clone();
+    registry.remove(&project_id);
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(())
+}
+
+/// Project and settings files that failed to load or were migrated to the
+/// current schema since startup.
+#[tauri::command]
+pub fn get_load_diagnostics(state: State<'_, Mutex<AppState>>) -> Result<Vec<LoadDiagnostic>, String> {
+    let registry = state.lock().unwrap().registry.clone();
+    // Loading first makes sure every file has been looked at
+    ProjectManager::load_settings();
+    registry.all()?;
+    Ok(schema::diagnostics())
+}
+
 #[tauri::command]
 pub fn delete_project(project_id: String, delete_files: bool) -> Result<(), String> {
-    ProjectManager::delete_project(&project_id, delete_files)
+    ProjectManager::delete_project(&project_id, delete_files)?;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(())
 }
 
 #[tauri::command]
@@ -138,6 +219,8 @@ pub fn open_project_in_editor(project_id: String, editor: String) -> Result<(),
 #[tauri::command]
 pub fn start_project(project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
+    PortAllocator::ensure_available(&project)?;
+    CertificateManager::ensure_project_certificate(&project)?;
     ProjectManager::update_project_status(&project_id, ProjectStatus::Starting)?;
 
     let result = DockerManager::start_project(&project.path);
@@ -168,12 +251,16 @@ pub fn stop_project(project_id: String) -> Result<String, String> {
 #[tauri::command]
 pub fn restart_project(project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
+    PortAllocator::ensure_available(&project)?;
+    CertificateManager::ensure_project_certificate(&project)?;
     DockerManager::restart_project(&project.path)
 }
 
 #[tauri::command]
 pub fn rebuild_project(project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
+    PortAllocator::ensure_available(&project)?;
+    CertificateManager::ensure_project_certificate(&project)?;
     ProjectManager::update_project_status(&project_id, ProjectStatus::Building)?;
 
     let result = DockerManager::rebuild_project(&project.path);
@@ -187,119 +274,131 @@ pub fn rebuild_project(project_id: String) -> Result<String, String> {
     result
 }
 
-// Streaming versions for real-time output - run in background thread
+// Streaming versions for real-time output - run as jobs in a background thread
+fn job_registry(state: &State<'_, Mutex<AppState>>) -> Arc<JobRegistry> {
+    state.lock().unwrap().jobs.clone()
+}
+
 #[tauri::command]
-pub async fn start_project_streaming(app: tauri::AppHandle, project_id: String) -> Result<String, String> {
+pub async fn start_project_streaming(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String, wait_healthy: Option<bool>, timeout_secs: Option<u64>) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
-    ProjectManager::update_project_status(&project_id, ProjectStatus::Starting)?;
-
-    let app_clone = app.clone();
-    let project_id_clone = project_id.clone();
-    let project_path = project.path.clone();
-    let project_name = project.name.clone();
-    let install_laravel = project.config.install_laravel;
-
-    // Run docker command in a background thread
-    let result = tokio::task::spawn_blocking(move || {
-        DockerManager::start_project_streaming(&app_clone, &project_id_clone, &project_path)
-    })
-    .await
-    .map_err(|e| format!("Task failed: {}", e))?;
+    PortAllocator::ensure_available(&project)?;
+    CertificateManager::ensure_project_certificate(&project)?;
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project_id, JobKind::Start);
+
+    // Run docker command (and the optional Laravel install) as a single job
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            ProjectManager::update_project_status(&project_id, ProjectStatus::Starting)?;
+            let result = DockerManager::start_project_streaming(&app, &project_id, &project.path, &job);
+
+            if let Err(e) = &result {
+                ProjectManager::mark_project_error(&project_id, e)?;
+                return result;
+            }
 
-    if result.is_ok() {
-        ProjectManager::update_project_status(&project_id, ProjectStatus::Running)?;
+            // If install_laravel is enabled, check if src folder is empty and install Laravel
+            let needs_install = project.config.install_laravel && {
+                let src_path = std::path::Path::new(&project.path).join("src");
+                src_path.read_dir().map(|mut d| d.next().is_none()).unwrap_or(true)
+            };
+
+            // The installer talks to the database, so never let it race the healthchecks
+            if wait_healthy.unwrap_or(false) || needs_install {
+                let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_HEALTH_TIMEOUT_SECS));
+                if let Err(e) = DockerManager::wait_for_healthy(&app, &project_id, &project.path, &project.name, timeout, &job) {
+                    ProjectManager::mark_project_error(&project_id, &e)?;
+                    return Err(e);
+                }
+            }
+            ProjectManager::update_project_status(&project_id, ProjectStatus::Running)?;
 
-        // If install_laravel is enabled, check if src folder is empty and install Laravel
-        if install_laravel {
-            let src_path = std::path::Path::new(&project.path).join("src");
-            let is_empty = src_path.read_dir().map(|mut d| d.next().is_none()).unwrap_or(true);
-
-            if is_empty {
-                let app_clone2 = app.clone();
-                let project_id_clone2 = project_id.clone();
-                let project_path2 = project.path.clone();
-                let project_name2 = project_name.clone();
-
-                // Run Laravel installation in background
-                tokio::task::spawn_blocking(move || {
-                    DockerManager::install_laravel_streaming(&app_clone2, &project_id_clone2, &project_path2, &project_name2)
-                })
-                .await
-                .map_err(|e| format!("Laravel install failed: {}", e))??;
+            if needs_install {
+                DockerManager::install_laravel_streaming(&app, &project_id, &project.path, &project.name, &job)?;
             }
-        }
-    } else {
-        ProjectManager::update_project_status(&project_id, ProjectStatus::Error)?;
-    }
 
-    result
+            result
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub async fn stop_project_streaming(app: tauri::AppHandle, project_id: String) -> Result<String, String> {
+pub async fn stop_project_streaming(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
-    ProjectManager::update_project_status(&project_id, ProjectStatus::Stopping)?;
-
-    let app_clone = app.clone();
-    let project_id_clone = project_id.clone();
-    let project_path = project.path.clone();
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project_id, JobKind::Stop);
 
     // Run docker command in a background thread
-    let result = tokio::task::spawn_blocking(move || {
-        DockerManager::stop_project_streaming(&app_clone, &project_id_clone, &project_path)
-    })
-    .await
-    .map_err(|e| format!("Task failed: {}", e))?;
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            ProjectManager::update_project_status(&project_id, ProjectStatus::Stopping)?;
+            let result = DockerManager::stop_project_streaming(&app, &project_id, &project.path, &job);
 
-    if result.is_ok() {
-        ProjectManager::update_project_status(&project_id, ProjectStatus::Stopped)?;
-    }
+            if result.is_ok() {
+                ProjectManager::update_project_status(&project_id, ProjectStatus::Stopped)?;
+            }
 
-    result
+            result
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub async fn rebuild_project_streaming(app: tauri::AppHandle, project_id: String) -> Result<String, String> {
+pub async fn rebuild_project_streaming(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
-    ProjectManager::update_project_status(&project_id, ProjectStatus::Building)?;
-
-    let app_clone = app.clone();
-    let project_id_clone = project_id.clone();
-    let project_path = project.path.clone();
+    PortAllocator::ensure_available(&project)?;
+    CertificateManager::ensure_project_certificate(&project)?;
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project_id, JobKind::Rebuild);
 
     // Run docker command in a background thread
-    let result = tokio::task::spawn_blocking(move || {
-        DockerManager::rebuild_project_streaming(&app_clone, &project_id_clone, &project_path)
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            ProjectManager::update_project_status(&project_id, ProjectStatus::Building)?;
+            let result = DockerManager::rebuild_project_streaming(&app, &project_id, &project.path, &job);
+
+            if result.is_ok() {
+                ProjectManager::update_project_status(&project_id, ProjectStatus::Running)?;
+            } else {
+                ProjectManager::update_project_status(&project_id, ProjectStatus::Error)?;
+            }
+
+            result
+        })
     })
     .await
-    .map_err(|e| format!("Task failed: {}", e))?;
-
-    if result.is_ok() {
-        ProjectManager::update_project_status(&project_id, ProjectStatus::Running)?;
-    } else {
-        ProjectManager::update_project_status(&project_id, ProjectStatus::Error)?;
-    }
-
-    result
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub async fn install_laravel_streaming(app: tauri::AppHandle, project_id: String) -> Result<String, String> {
+pub async fn install_laravel_streaming(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
-
-    let app_clone = app.clone();
-    let project_id_clone = project_id.clone();
-    let project_path = project.path.clone();
-    let project_name = project.name.clone();
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project_id, JobKind::InstallLaravel);
 
     // Run composer install in a background thread
-    let result = tokio::task::spawn_blocking(move || {
-        DockerManager::install_laravel_streaming(&app_clone, &project_id_clone, &project_path, &project_name)
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            DockerManager::install_laravel_streaming(&app, &project_id, &project.path, &project.name, &job)
+        })
     })
     .await
-    .map_err(|e| format!("Task failed: {}", e))?;
+    .map_err(|e| format!("Task failed: {}", e))?
+}
 
-    result
+#[tauri::command]
+pub fn list_jobs(state: State<'_, Mutex<AppState>>, project_id: Option<String>) -> Vec<JobInfo> {
+    job_registry(&state).list(project_id.as_deref())
+}
+
+#[tauri::command]
+pub fn cancel_job(state: State<'_, Mutex<AppState>>, job_id: String) -> Result<JobInfo, String> {
+    job_registry(&state).cancel(&job_id)
 }
 
 #[tauri::command]
@@ -314,54 +413,268 @@ pub fn get_container_logs(project_id: String, service: String, lines: u32) -> Re
     DockerManager::get_container_logs(&project.path, &service, lines)
 }
 
+#[tauri::command]
+pub fn stream_container_logs(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String, options: Option<LogStreamOptions>) -> Result<String, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let log_streams = state.lock().unwrap().log_streams.clone();
+    log_streams.start(&app, &project_id, &project.path, &project.name, options.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn stop_container_logs(state: State<'_, Mutex<AppState>>, stream_id: String) -> Result<(), String> {
+    let log_streams = state.lock().unwrap().log_streams.clone();
+    log_streams.stop(&stream_id)
+}
+
+#[tauri::command]
+pub fn list_log_streams(state: State<'_, Mutex<AppState>>, project_id: Option<String>) -> Vec<LogStreamInfo> {
+    let log_streams = state.lock().unwrap().log_streams.clone();
+    log_streams.list(project_id.as_deref())
+}
+
+#[tauri::command]
+pub fn get_log_stream_buffer(state: State<'_, Mutex<AppState>>, stream_id: String, limit: Option<usize>) -> Result<Vec<LogLine>, String> {
+    let log_streams = state.lock().unwrap().log_streams.clone();
+    log_streams.buffer(&stream_id, limit)
+}
+
 #[tauri::command]
 pub fn get_services_status(project_id: String) -> Result<Vec<ServiceStatus>, String> {
     let project = ProjectManager::get_project(&project_id)?;
     DockerManager::get_project_status(&project.path, &project.name)
 }
 
-// ============ Template Commands ============
+#[tauri::command]
+pub fn get_project_stats(project_id: String) -> Result<Vec<ContainerStats>, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    DockerManager::get_project_stats(&project.path, &project.name)
+}
 
 #[tauri::command]
-pub fn get_templates() -> Result<Vec<TemplateInfo>, String> {
-    let templates_path = get_templates_path();
-    let mut templates = Vec::new();
-
-    let entries = fs::read_dir(&templates_path)
-        .map_err(|e| format!("Failed to read templates directory: {}", e))?;
-
-    for entry in entries.flatten() {
-        if entry.path().is_dir() {
-            let config_path = entry.path().join("config.json");
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
-                        templates.push(TemplateInfo {
-                            name: config["name"].as_str().unwrap_or("").to_string(),
-                            description: config["description"].as_str().unwrap_or("").to_string(),
-                            icon: config["icon"].as_str().unwrap_or("📦").to_string(),
-                            template_type: config["type"].as_str().unwrap_or("").to_string(),
-                            category: config["category"].as_str().unwrap_or("").to_string(),
-                        });
-                    }
+pub fn start_metrics_sampling(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_ids: Option<Vec<String>>, interval_secs: Option<u64>) -> MetricsSamplerInfo {
+    let metrics = state.lock().unwrap().metrics.clone();
+    metrics.start(&app, project_ids.unwrap_or_default(), interval_secs)
+}
+
+#[tauri::command]
+pub fn stop_metrics_sampling(state: State<'_, Mutex<AppState>>) {
+    let metrics = state.lock().unwrap().metrics.clone();
+    metrics.stop();
+}
+
+#[tauri::command]
+pub fn get_metrics_sampler(state: State<'_, Mutex<AppState>>) -> MetricsSamplerInfo {
+    let metrics = state.lock().unwrap().metrics.clone();
+    metrics.info()
+}
+
+#[tauri::command]
+pub fn get_latest_metrics(state: State<'_, Mutex<AppState>>) -> Vec<ProjectMetrics> {
+    let metrics = state.lock().unwrap().metrics.clone();
+    metrics.latest()
+}
+
+#[tauri::command]
+pub fn get_metrics_history(state: State<'_, Mutex<AppState>>, project_id: String) -> HashMap<String, Vec<ContainerStats>> {
+    let metrics = state.lock().unwrap().metrics.clone();
+    metrics.history(&project_id)
+}
+
+#[tauri::command]
+pub fn suggest_ports(ports: Option<PortConfig>, services: Option<ServiceConfig>, project_id: Option<String>) -> Result<PortConfig, String> {
+    PortAllocator::allocate(&ports.unwrap_or_default(), &services.unwrap_or_default(), project_id.as_deref())
+}
+
+#[tauri::command]
+pub fn check_port_conflicts(project_id: String) -> Result<Vec<PortConflict>, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    Ok(PortAllocator::find_conflicts(&project))
+}
+
+// ============ Proxy Commands ============
+
+#[tauri::command]
+pub fn get_proxy_status() -> Result<ProxyStatus, String> {
+    ProxyManager::status()
+}
+
+#[tauri::command]
+pub fn enable_proxy(http_port: Option<u16>, https_port: Option<u16>) -> Result<ProxyStatus, String> {
+    ProxyManager::enable(http_port, https_port)
+}
+
+#[tauri::command]
+pub fn disable_proxy() -> Result<ProxyStatus, String> {
+    ProxyManager::disable()
+}
+
+#[tauri::command]
+pub fn refresh_proxy() -> Result<ProxyStatus, String> {
+    ProxyManager::refresh()
+}
+
+// ============ Hosts File Commands ============
+
+#[tauri::command]
+pub fn preview_hosts_file(path: Option<String>) -> Result<HostsPreview, String> {
+    HostsManager::preview(path.as_deref())
+}
+
+#[tauri::command]
+pub fn apply_hosts_file(path: Option<String>) -> Result<HostsPreview, String> {
+    HostsManager::apply(path.as_deref())
+}
+
+#[tauri::command]
+pub fn remove_hosts_entries(path: Option<String>) -> Result<HostsPreview, String> {
+    HostsManager::remove(path.as_deref())
+}
+
+/// Turns automatic hosts file updates on or off. Turning them on brings
+/// the file up to date right away, turning them off removes the block.
+#[tauri::command]
+pub fn set_hosts_management(managed: bool, path: Option<String>) -> Result<HostsPreview, String> {
+    let mut settings = ProjectManager::load_settings();
+    let managed_path = settings.hosts.path.clone();
+    settings.hosts.managed = managed;
+    settings.hosts.path = path.filter(|p| !p.trim().is_empty());
+    ProjectManager::save_settings(&settings)?;
+
+    if managed {
+        HostsManager::apply(None)
+    } else {
+        // The block is in the file that was managed until now
+        HostsManager::remove(managed_path.as_deref())
+    }
+}
+
+// ============ Certificate Commands ============
+
+#[tauri::command]
+pub fn get_certificate_authority() -> Result<Option<CertificateAuthorityInfo>, String> {
+    CertificateManager::ca_info()
+}
+
+#[tauri::command]
+pub fn create_certificate_authority() -> Result<CertificateAuthorityInfo, String> {
+    CertificateManager::ensure_ca()
+}
+
+#[tauri::command]
+pub fn export_certificate_authority(destination: String) -> Result<String, String> {
+    CertificateManager::export_ca(&destination)
+}
+
+#[tauri::command]
+pub fn issue_project_certificate(project_id: String) -> Result<CertificateInfo, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let info = CertificateManager::issue_for_project(&project.path, &project.name)?;
+
+    // A running web server only picks the new certificate up on reload
+    if project.status == ProjectStatus::Running {
+        if let Ok(client) = EngineClient::from_env() {
+            for service in ["nginx", "caddy"] {
+                if let Ok(container) = DockerManager::find_service_container(&client, &project.path, &project.name, service) {
+                    let reload: &[&str] = if service == "nginx" {
+                        &["nginx", "-s", "reload"]
+                    } else {
+                        &["caddy", "reload", "--config", "/etc/caddy/Caddyfile"]
+                    };
+                    let _ = client.exec(&container.id, reload, &Default::default());
                 }
             }
         }
     }
 
+    Ok(info)
+}
+
+// ============ Template Commands ============
+
+#[tauri::command]
+pub fn get_templates() -> Result<Vec<TemplateInfo>, String> {
+    let templates = TemplateCatalog::list(&get_templates_path())
+        .into_iter()
+        .map(|template| {
+            let config = &template.config;
+            TemplateInfo {
+                name: config["name"].as_str().unwrap_or("").to_string(),
+                description: config["description"].as_str().unwrap_or("").to_string(),
+                icon: config["icon"].as_str().unwrap_or("📦").to_string(),
+                template_type: config["type"].as_str().unwrap_or("").to_string(),
+                category: config["category"].as_str().unwrap_or("").to_string(),
+                version: config["version"].as_str().map(String::from),
+                source: template.root.source,
+                pack: template.root.pack.clone(),
+                path: Path::new(&template.root.path).join(&template.template_type).to_string_lossy().to_string(),
+                overrides: template.overrides,
+            }
+        })
+        .collect();
+
     Ok(templates)
 }
 
 #[tauri::command]
 pub fn get_template(template_type: String) -> Result<serde_json::Value, String> {
-    let templates_path = get_templates_path();
-    let config_path = Path::new(&templates_path).join(&template_type).join("config.json");
+    load_template_config(&get_templates_path(), &template_type)
+}
+
+/// The directories searched for templates, highest precedence first.
+#[tauri::command]
+pub fn get_template_roots() -> Vec<TemplateRoot> {
+    TemplateCatalog::roots(&get_templates_path())
+}
+
+#[tauri::command]
+pub fn list_template_packs() -> Vec<TemplatePack> {
+    TemplateCatalog::list_packs()
+}
+
+/// Installs a template pack from a directory or `.tar.gz` archive,
+/// replacing an installed pack of the same name.
+#[tauri::command]
+pub async fn install_template_pack(source: String) -> Result<TemplatePack, String> {
+    tokio::task::spawn_blocking(move || TemplateCatalog::install_pack(&source))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn remove_template_pack(name: String) -> Result<(), String> {
+    TemplateCatalog::remove_pack(&name)
+}
+
+/// Renders the files `create_project` would write, without writing any.
+/// With `compare_project_id`, each file is compared to that project's copy.
+#[tauri::command]
+pub fn preview_project_from_template(mut request: CreateProjectRequest, compare_project_id: Option<String>) -> Result<TemplatePreview, String> {
+    let compare_with = compare_project_id.map(|id| ProjectManager::get_project(&id)).transpose()?;
+    if compare_with.is_none() {
+        request.config.ports = PortAllocator::allocate(&request.config.ports, &request.config.services, None)?;
+    }
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read template config: {}", e))?;
+    let files = render_template_files(&request.name, &request.template, &get_templates_path(), &request.config)?;
+    Ok(preview_files(&request.name, files, compare_with.as_ref()))
+}
+
+/// What regenerating a project's files from its template would change.
+#[tauri::command]
+pub fn preview_project_regeneration(project_id: String) -> Result<TemplatePreview, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    if project.template.starts_with("custom:") {
+        return Err(format!("{} was created from a custom template, preview it with that template instead", project.name));
+    }
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse template config: {}", e))
+    let files = TemplateEngine::for_project(&get_templates_path(), &project)?.render_project(&project.name, &project.config)?;
+    Ok(preview_files(&project.name, files, Some(&project)))
+}
+
+fn preview_files(name: &str, files: Vec<RenderedFile>, compare_with: Option<&Project>) -> TemplatePreview {
+    match compare_with {
+        Some(project) => TemplatePreviewer::preview(Path::new(&project.path), files, Some(Path::new(&project.path))),
+        None => TemplatePreviewer::preview(&Path::new(&ProjectManager::get_projects_dir()).join(name), files, None),
+    }
 }
 
 // ============ Artisan Commands ============
@@ -453,31 +766,12 @@ pub fn restart_supervisor(project_id: String) -> Result<String, String> {
 
 #[tauri::command]
 pub fn get_settings() -> Result<Settings, String> {
-    let settings_path = get_settings_path();
-
-    if Path::new(&settings_path).exists() {
-        let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))
-    } else {
-        Ok(Settings::default())
-    }
+    Ok(ProjectManager::load_settings())
 }
 
 #[tauri::command]
 pub fn save_settings(settings: Settings) -> Result<(), String> {
-    let settings_path = get_settings_path();
-
-    if let Some(parent) = Path::new(&settings_path).parent() {
-        fs::create_dir_all(parent).ok();
-    }
-
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    fs::write(&settings_path, content)
-        .map_err(|e| format!("Failed to save settings: {}", e))
+    ProjectManager::replace_settings(&settings)
 }
 
 // ============ System Commands ============
@@ -492,6 +786,17 @@ pub fn get_docker_version() -> Result<String, String> {
     DockerManager::get_docker_version()
 }
 
+#[tauri::command]
+pub fn get_container_runtime() -> ContainerRuntimeInfo {
+    DockerManager::get_runtime_info()
+}
+
+/// Detects the compose backend again, e.g. after installing one.
+#[tauri::command]
+pub fn refresh_container_runtime() -> ContainerRuntimeInfo {
+    DockerManager::refresh_runtime_info()
+}
+
 // ============ Queue Management Commands ============
 
 #[tauri::command]
@@ -583,13 +888,191 @@ pub fn stop_scheduler(project_id: String) -> Result<String, String> {
 // ============ Project Cloning Commands ============
 
 #[tauri::command]
-pub fn clone_project(project_id: String, new_name: String) -> Result<Project, String> {
-    ProjectManager::clone_project(&project_id, &new_name)
+pub async fn clone_project(project_id: String, new_name: String, copy_volumes: Option<bool>) -> Result<Project, String> {
+    let templates_path = get_templates_path();
+    let project = tokio::task::spawn_blocking(move || {
+        ProjectManager::clone_project(&project_id, &new_name, &templates_path, copy_volumes.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(project)
 }
 
 #[tauri::command]
-pub fn import_project(source_path: String, name: String) -> Result<Project, String> {
-    ProjectManager::import_project(&source_path, &name)
+pub fn import_project(source_path: String, name: String) -> Result<ImportResult, String> {
+    let (project, detection) = ProjectManager::import_project(&source_path, &name, &get_templates_path())?;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(ImportResult { project, detection })
+}
+
+// ============ Project Rename & Move Commands ============
+
+#[tauri::command]
+pub async fn rename_project(state: State<'_, Mutex<AppState>>, project_id: String, new_name: String) -> Result<Project, String> {
+    ensure_no_active_jobs(&state, &project_id)?;
+    let templates_path = get_templates_path();
+
+    let project = tokio::task::spawn_blocking(move || ProjectRelocator::rename(&project_id, &new_name, &templates_path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn move_project(state: State<'_, Mutex<AppState>>, project_id: String, destination: String) -> Result<Project, String> {
+    ensure_no_active_jobs(&state, &project_id)?;
+    let templates_path = get_templates_path();
+
+    let project = tokio::task::spawn_blocking(move || ProjectRelocator::move_to(&project_id, &destination, &templates_path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    ProxyManager::refresh_in_background();
+    Ok(project)
+}
+
+// A queued start or stop would run against the old name and path
+fn ensure_no_active_jobs(state: &State<'_, Mutex<AppState>>, project_id: &str) -> Result<(), String> {
+    if job_registry(state).has_active(project_id) {
+        return Err("Wait for the project's running jobs to finish first".to_string());
+    }
+    Ok(())
+}
+
+// ============ Project Config Commands ============
+
+/// Saves a project's configuration and merges the files its template
+/// renders for it into the project. With `rebuild`, the containers are
+/// rebuilt afterwards, unless a file was left with conflicts.
+#[tauri::command]
+pub async fn update_project_config(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    project_id: String,
+    config: ProjectConfig,
+    rebuild: Option<bool>,
+) -> Result<ConfigUpdate, String> {
+    ensure_no_active_jobs(&state, &project_id)?;
+    let templates_path = get_templates_path();
+
+    let id = project_id.clone();
+    let mut update = tokio::task::spawn_blocking(move || ProjectRegenerator::update_config(&id, config, &templates_path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    ProxyManager::refresh_in_background();
+
+    let changed = update.files.iter().any(|file| file.outcome != RegenerateOutcome::Unchanged);
+    if rebuild.unwrap_or(false) && changed && update.conflicts.is_empty() {
+        rebuild_project_streaming(app, state, project_id).await?;
+        update.rebuilt = true;
+    }
+    Ok(update)
+}
+
+// ============ Project Archive Commands ============
+
+#[tauri::command]
+pub async fn export_project(project_id: String, destination: String, include_database: Option<bool>) -> Result<ExportSummary, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+
+    tokio::task::spawn_blocking(move || ProjectArchive::export(&project, &destination, include_database.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn read_project_archive(archive_path: String) -> Result<ArchiveManifest, String> {
+    ProjectArchive::read_manifest(&archive_path)
+}
+
+/// Restores an archive as a new project. Unless `start` is false, the
+/// project is then started in the background as an `import` job that
+/// installs missing dependencies and reloads the archived database.
+#[tauri::command]
+pub async fn import_project_archive(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, archive_path: String, name: Option<String>, start: Option<bool>) -> Result<Project, String> {
+    let (project, dump) = tokio::task::spawn_blocking(move || ProjectArchive::import(&archive_path, name.as_deref()))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    HostsManager::sync_in_background();
+
+    if !start.unwrap_or(true) {
+        return Ok(project);
+    }
+
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project.id, JobKind::Import);
+    let imported = project.clone();
+
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            let project = imported;
+            start_imported_project(&app, &project, &job)?;
+            if let Some(dump) = &dump {
+                DockerManager::load_database_dump(&project.path, &project.name, dump)?;
+            }
+
+            Ok(format!("Imported {}", project.name))
+        })
+    });
+
+    Ok(project)
+}
+
+/// Clones a git repository into a new project scaffolded from the template
+/// matching its framework. Unless `start` is false, the project is then
+/// started in the background as an `import` job that installs dependencies
+/// and runs the template's setup commands.
+#[tauri::command]
+pub async fn import_project_from_git(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, url: String, name: Option<String>, branch: Option<String>, start: Option<bool>) -> Result<ImportResult, String> {
+    let templates_path = get_templates_path();
+    let (project, detection, setup) = tokio::task::spawn_blocking(move || GitImporter::import(&url, name.as_deref(), branch.as_deref(), &templates_path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
+
+    if !start.unwrap_or(true) {
+        return Ok(ImportResult { project, detection });
+    }
+
+    let jobs = job_registry(&state);
+    let job = jobs.enqueue(&app, &project.id, JobKind::Import);
+    let imported = project.clone();
+
+    tokio::task::spawn_blocking(move || {
+        jobs.run(&app, &job, || {
+            let project = imported;
+            start_imported_project(&app, &project, &job)?;
+            DockerManager::run_setup_commands_streaming(&app, &project.id, &project.path, &project.name, &setup, &job)?;
+
+            Ok(format!("Imported {}", project.name))
+        })
+    });
+
+    Ok(ImportResult { project, detection })
+}
+
+/// First start of an imported project: checks its ports, starts it, waits
+/// until it is healthy and installs dependencies the import left out.
+fn start_imported_project(app: &tauri::AppHandle, project: &Project, job: &JobContext) -> Result<(), String> {
+    PortAllocator::ensure_available(project)?;
+    ProjectManager::update_project_status(&project.id, ProjectStatus::Starting)?;
+
+    let timeout = std::time::Duration::from_secs(DEFAULT_HEALTH_TIMEOUT_SECS);
+    let started = DockerManager::start_project_streaming(app, &project.id, &project.path, job)
+        .and_then(|_| DockerManager::wait_for_healthy(app, &project.id, &project.path, &project.name, timeout, job));
+    if let Err(e) = started {
+        ProjectManager::mark_project_error(&project.id, &e)?;
+        return Err(e);
+    }
+    ProjectManager::update_project_status(&project.id, ProjectStatus::Running)?;
+
+    DockerManager::install_dependencies_streaming(app, &project.id, &project.path, &project.name, job)?;
+    Ok(())
 }
 
 // ============ Database Backup & Restore Commands ============
@@ -674,6 +1157,43 @@ pub fn run_tinker_command(project_id: String, code: String) -> Result<String, St
     DockerManager::exec_in_container(&project.path, "app", &command)
 }
 
+#[tauri::command]
+pub fn start_tinker_session(state: State<'_, Mutex<AppState>>, project_id: String) -> Result<TinkerSessionInfo, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let tinker = state.lock().unwrap().tinker.clone();
+    tinker.start(&project_id, &project.path, &project.name)
+}
+
+#[tauri::command]
+pub async fn eval_tinker(state: State<'_, Mutex<AppState>>, project_id: String, code: String, timeout_secs: Option<u64>) -> Result<TinkerResult, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let tinker = state.lock().unwrap().tinker.clone();
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_EVAL_TIMEOUT_SECS));
+
+    tokio::task::spawn_blocking(move || tinker.eval(&project_id, &project.path, &project.name, &code, timeout))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn reset_tinker_session(state: State<'_, Mutex<AppState>>, project_id: String) -> Result<TinkerSessionInfo, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let tinker = state.lock().unwrap().tinker.clone();
+    tinker.reset(&project_id, &project.path, &project.name)
+}
+
+#[tauri::command]
+pub fn close_tinker_session(state: State<'_, Mutex<AppState>>, project_id: String) {
+    let tinker = state.lock().unwrap().tinker.clone();
+    tinker.close(&project_id);
+}
+
+#[tauri::command]
+pub fn get_tinker_session(state: State<'_, Mutex<AppState>>, project_id: String) -> Option<TinkerSessionInfo> {
+    let tinker = state.lock().unwrap().tinker.clone();
+    tinker.info(&project_id)
+}
+
 #[tauri::command]
 pub fn run_composer_command(project_id: String, command: String) -> Result<String, String> {
     let project = ProjectManager::get_project(&project_id)?;
@@ -686,6 +1206,37 @@ pub fn run_npm_command(project_id: String, command: String) -> Result<String, St
     DockerManager::exec_in_container(&project.path, "app", &format!("npm {}", command))
 }
 
+#[tauri::command]
+pub fn open_terminal_session(app: tauri::AppHandle, state: State<'_, Mutex<AppState>>, project_id: String, options: Option<TerminalOptions>) -> Result<TerminalSessionInfo, String> {
+    let project = ProjectManager::get_project(&project_id)?;
+    let terminals = state.lock().unwrap().terminals.clone();
+    terminals.open(&app, &project_id, &project.path, &project.name, options.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn write_terminal(state: State<'_, Mutex<AppState>>, session_id: String, data: String) -> Result<(), String> {
+    let terminals = state.lock().unwrap().terminals.clone();
+    terminals.write(&session_id, &data)
+}
+
+#[tauri::command]
+pub fn resize_terminal(state: State<'_, Mutex<AppState>>, session_id: String, rows: u16, cols: u16) -> Result<(), String> {
+    let terminals = state.lock().unwrap().terminals.clone();
+    terminals.resize(&session_id, rows, cols)
+}
+
+#[tauri::command]
+pub fn close_terminal_session(state: State<'_, Mutex<AppState>>, session_id: String) -> Result<(), String> {
+    let terminals = state.lock().unwrap().terminals.clone();
+    terminals.close(&session_id)
+}
+
+#[tauri::command]
+pub fn list_terminal_sessions(state: State<'_, Mutex<AppState>>, project_id: Option<String>) -> Vec<TerminalSessionInfo> {
+    let terminals = state.lock().unwrap().terminals.clone();
+    terminals.list(project_id.as_deref())
+}
+
 // ============ Helper Functions ============
 
 fn get_templates_path() -> String {
@@ -716,14 +1267,6 @@ fn get_templates_path() -> String {
     fallback.to_string_lossy().to_string()
 }
 
-fn get_settings_path() -> String {
-    let home = dirs::home_dir().unwrap_or_default();
-    home.join(".laravel-godmode")
-        .join("settings.json")
-        .to_string_lossy()
-        .to_string()
-}
-
 // ============ Custom Template Commands ============
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -732,6 +1275,15 @@ pub struct CreateFromCustomTemplateRequest {
     pub template: crate::custom_template::CustomTemplate,
 }
 
+/// Renders the files `create_project_from_custom_template` would write,
+/// optionally compared to an existing project's.
+#[tauri::command]
+pub fn preview_project_from_custom_template(request: CreateFromCustomTemplateRequest, compare_project_id: Option<String>) -> Result<TemplatePreview, String> {
+    let compare_with = compare_project_id.map(|id| ProjectManager::get_project(&id)).transpose()?;
+    let files = crate::custom_template::render_custom_template_files(&request.name, &request.template);
+    Ok(preview_files(&request.name, files, compare_with.as_ref()))
+}
+
 #[tauri::command]
 pub fn create_project_from_custom_template(request: CreateFromCustomTemplateRequest) -> Result<Project, String> {
     use crate::custom_template::create_project_from_custom_template as create_custom;
@@ -750,6 +1302,8 @@ pub fn create_project_from_custom_template(request: CreateFromCustomTemplateRequ
 
     // Save to project manager
     ProjectManager::save_project(&project)?;
+    ProxyManager::refresh_in_background();
+    HostsManager::sync_in_background();
 
     Ok(project)
 }
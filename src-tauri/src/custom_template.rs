@@ -3,8 +3,10 @@ use std::collections::HashMap;
 use std::fs;
 use uuid::Uuid;
 use chrono::Utc;
+use crate::schema::PROJECT_SCHEMA_VERSION;
 use crate::state::{Project, ProjectConfig, ProjectStatus, PortConfig, ServiceConfig};
-use crate::template::save_project;
+use crate::template::{save_project, write_rendered_file, RenderedFile};
+use crate::tls::CertificateManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockInstance {
@@ -68,21 +70,24 @@ pub fn generate_docker_compose_from_blocks(project_name: &str, blocks: &[BlockIn
 
             "nginx" => {
                 let port = get_config_int(&block.config, "port", 80);
+                let https_port = get_config_int(&block.config, "https_port", 8443);
                 services.push_str(&format!(r#"
   nginx:
     image: nginx:alpine
     container_name: {}_nginx
     ports:
       - "{}:80"
+      - "{}:443"
     volumes:
       - ./src:/var/www/html:ro
       - ./docker/nginx.conf:/etc/nginx/conf.d/default.conf:ro
+      - ./docker/certs:/etc/nginx/certs:ro
     depends_on:
       - app
     networks:
       - {}_network
     restart: unless-stopped
-"#, project_name, port, project_name));
+"#, project_name, port, https_port, project_name));
             }
 
             "caddy" => {
@@ -98,6 +103,7 @@ pub fn generate_docker_compose_from_blocks(project_name: &str, blocks: &[BlockIn
     volumes:
       - ./src:/srv
       - ./docker/Caddyfile:/etc/caddy/Caddyfile:ro
+      - ./docker/certs:/etc/caddy/certs:ro
       - caddy_data:/data
       - caddy_config:/config
     depends_on:
@@ -575,10 +581,47 @@ pub fn create_project_from_custom_template(
     fs::create_dir_all(project_path.join("docker"))
         .map_err(|e| format!("Failed to create docker directory: {}", e))?;
 
+    for file in render_custom_template_files(project_name, template) {
+        write_rendered_file(&project_path, &file)?;
+    }
+
+    // Create project config
+    let config = create_project_config_from_blocks(&template.blocks);
+    let now = Utc::now();
+
+    let project = Project {
+        schema_version: PROJECT_SCHEMA_VERSION,
+        id: project_id,
+        name: project_name.to_string(),
+        template: format!("custom:{}", template.name),
+        path: project_path.to_string_lossy().to_string(),
+        created_at: now,
+        updated_at: now,
+        config,
+        status: ProjectStatus::Stopped,
+        status_message: None,
+    };
+
+    // The web server mounts the project's certificate, so it must exist before the first start
+    CertificateManager::ensure_project_certificate(&project)?;
+
+    // Save project metadata
+    save_project(&project)?;
+
+    Ok(project)
+}
+
+/// Renders, in memory, every file `template` produces for a project called `project_name`.
+pub fn render_custom_template_files(project_name: &str, template: &CustomTemplate) -> Vec<RenderedFile> {
+    let file = |path: &str, content: String| RenderedFile {
+        path: path.to_string(),
+        content,
+        mode: None,
+    };
+    let mut files = Vec::new();
+
     // Generate docker-compose.yml
-    let docker_compose = generate_docker_compose_from_blocks(project_name, &template.blocks);
-    fs::write(project_path.join("docker-compose.yml"), &docker_compose)
-        .map_err(|e| format!("Failed to write docker-compose.yml: {}", e))?;
+    files.push(file("docker-compose.yml", generate_docker_compose_from_blocks(project_name, &template.blocks)));
 
     // Generate basic Dockerfile for PHP if php-fpm is enabled
     let has_php = template.blocks.iter().any(|b| b.block_id == "php-fpm" && b.enabled);
@@ -588,58 +631,37 @@ pub fn create_project_from_custom_template(
             .and_then(|b| b.version.clone())
             .unwrap_or_else(|| "8.4".to_string());
 
-        let dockerfile = generate_php_dockerfile(&php_version);
-        fs::write(project_path.join("Dockerfile"), &dockerfile)
-            .map_err(|e| format!("Failed to write Dockerfile: {}", e))?;
+        files.push(file("Dockerfile", generate_php_dockerfile(&php_version)));
 
         // Generate php.ini
-        fs::write(project_path.join("docker/php.ini"), generate_php_ini())
-            .map_err(|e| format!("Failed to write php.ini: {}", e))?;
+        files.push(file("docker/php.ini", generate_php_ini().to_string()));
 
         // Generate nginx.conf if nginx is enabled
         let has_nginx = template.blocks.iter().any(|b| b.block_id == "nginx" && b.enabled);
         if has_nginx {
-            fs::write(project_path.join("docker/nginx.conf"), generate_nginx_conf())
-                .map_err(|e| format!("Failed to write nginx.conf: {}", e))?;
+            files.push(file("docker/nginx.conf", generate_nginx_conf().to_string()));
         }
     }
 
+    // Generate Caddyfile if caddy is enabled
+    if let Some(caddy) = template.blocks.iter().find(|b| b.block_id == "caddy" && b.enabled) {
+        let auto_https = caddy.config.get("auto_https").and_then(|v| v.as_bool()).unwrap_or(false);
+        files.push(file("docker/Caddyfile", generate_caddyfile(auto_https)));
+    }
+
     // Generate basic .env file
-    let env_content = generate_env_file(project_name, &template.blocks);
-    fs::write(project_path.join(".env"), &env_content)
-        .map_err(|e| format!("Failed to write .env: {}", e))?;
+    files.push(file(".env", generate_env_file(project_name, &template.blocks)));
 
     // Generate Makefile
-    let makefile = generate_makefile(project_name);
-    fs::write(project_path.join("Makefile"), &makefile)
-        .map_err(|e| format!("Failed to write Makefile: {}", e))?;
+    files.push(file("Makefile", generate_makefile(project_name)));
 
     // Generate mysql.cnf if MySQL/MariaDB is enabled
     let has_mysql = template.blocks.iter().any(|b| (b.block_id == "mysql" || b.block_id == "mariadb") && b.enabled);
     if has_mysql {
-        fs::write(project_path.join("docker/mysql.cnf"), generate_mysql_cnf())
-            .map_err(|e| format!("Failed to write mysql.cnf: {}", e))?;
+        files.push(file("docker/mysql.cnf", generate_mysql_cnf().to_string()));
     }
 
-    // Create project config
-    let config = create_project_config_from_blocks(&template.blocks);
-    let now = Utc::now();
-
-    let project = Project {
-        id: project_id,
-        name: project_name.to_string(),
-        template: format!("custom:{}", template.name),
-        path: project_path.to_string_lossy().to_string(),
-        created_at: now,
-        updated_at: now,
-        config,
-        status: ProjectStatus::Stopped,
-    };
-
-    // Save project metadata
-    save_project(&project)?;
-
-    Ok(project)
+    files
 }
 
 fn generate_php_dockerfile(php_version: &str) -> String {
@@ -708,11 +730,16 @@ opcache.revalidate_freq=2
 fn generate_nginx_conf() -> &'static str {
     r#"server {
     listen 80;
+    listen 443 ssl;
     index index.php index.html;
     error_log  /var/log/nginx/error.log;
     access_log /var/log/nginx/access.log;
     root /var/www/html/public;
 
+    # Signed by the Laravel GodMode local CA
+    ssl_certificate /etc/nginx/certs/cert.pem;
+    ssl_certificate_key /etc/nginx/certs/key.pem;
+
     location / {
         try_files $uri $uri/ /index.php?$query_string;
     }
@@ -736,6 +763,34 @@ fn generate_nginx_conf() -> &'static str {
 "#
 }
 
+fn generate_caddyfile(auto_https: bool) -> String {
+    // Caddy's own internal CA, or the certificate from the local CA
+    let tls = if auto_https {
+        "tls internal"
+    } else {
+        "tls /etc/caddy/certs/cert.pem /etc/caddy/certs/key.pem"
+    };
+
+    format!(r#"(app) {{
+    root * /srv/public
+    encode gzip
+    php_fastcgi app:9000 {{
+        root /var/www/html/public
+    }}
+    file_server
+}}
+
+:80 {{
+    import app
+}}
+
+:443 {{
+    {}
+    import app
+}}
+"#, tls)
+}
+
 fn generate_mysql_cnf() -> &'static str {
     r#"[mysqld]
 general_log = 1
@@ -891,54 +946,60 @@ AWS_USE_PATH_STYLE_ENDPOINT=true
 }
 
 fn generate_makefile(project_name: &str) -> String {
+    let compose = crate::docker::DockerManager::compose_backend()
+        .map(|backend| backend.command_line())
+        .unwrap_or_else(|_| "docker compose".to_string());
+
     format!(r#".PHONY: up down build rebuild shell logs
 
+DOCKER_COMPOSE = {}
+
 up:
-	docker compose up -d
+	$(DOCKER_COMPOSE) up -d
 
 down:
-	docker compose down
+	$(DOCKER_COMPOSE) down
 
 build:
-	docker compose build
+	$(DOCKER_COMPOSE) build
 
 rebuild:
-	docker compose down
-	docker compose build --no-cache
-	docker compose up -d
+	$(DOCKER_COMPOSE) down
+	$(DOCKER_COMPOSE) build --no-cache
+	$(DOCKER_COMPOSE) up -d
 
 shell:
-	docker compose exec app bash
+	$(DOCKER_COMPOSE) exec app bash
 
 logs:
-	docker compose logs -f
+	$(DOCKER_COMPOSE) logs -f
 
 # Laravel specific
 artisan:
-	docker compose exec app php artisan $(filter-out $@,$(MAKECMDGOALS))
+	$(DOCKER_COMPOSE) exec app php artisan $(filter-out $@,$(MAKECMDGOALS))
 
 composer:
-	docker compose exec app composer $(filter-out $@,$(MAKECMDGOALS))
+	$(DOCKER_COMPOSE) exec app composer $(filter-out $@,$(MAKECMDGOALS))
 
 npm:
-	docker compose exec app npm $(filter-out $@,$(MAKECMDGOALS))
+	$(DOCKER_COMPOSE) exec app npm $(filter-out $@,$(MAKECMDGOALS))
 
 migrate:
-	docker compose exec app php artisan migrate
+	$(DOCKER_COMPOSE) exec app php artisan migrate
 
 fresh:
-	docker compose exec app php artisan migrate:fresh --seed
+	$(DOCKER_COMPOSE) exec app php artisan migrate:fresh --seed
 
 tinker:
-	docker compose exec app php artisan tinker
+	$(DOCKER_COMPOSE) exec app php artisan tinker
 
 test:
-	docker compose exec app php artisan test
+	$(DOCKER_COMPOSE) exec app php artisan test
 
 # Catch all for passing arguments
 %:
 	@:
-"#)
+"#, compose)
 }
 
 fn create_project_config_from_blocks(blocks: &[BlockInstance]) -> ProjectConfig {
@@ -972,6 +1033,14 @@ fn create_project_config_from_blocks(blocks: &[BlockInstance]) -> ProjectConfig
         .map(|b| get_config_int(&b.config, "port", 8080) as u16)
         .unwrap_or(8080);
 
+    let https_port = blocks.iter()
+        .find(|b| b.block_id == "nginx" && b.enabled)
+        .map(|b| get_config_int(&b.config, "https_port", 8443) as u16)
+        .or_else(|| blocks.iter()
+            .find(|b| b.block_id == "caddy" && b.enabled)
+            .map(|b| get_config_int(&b.config, "https_port", 443) as u16))
+        .unwrap_or(8443);
+
     let mailhog_port = blocks.iter()
         .find(|b| (b.block_id == "mailhog" || b.block_id == "mailpit") && b.enabled)
         .map(|b| get_config_int(&b.config, "ui_port", 8025) as u16)
@@ -991,6 +1060,7 @@ fn create_project_config_from_blocks(blocks: &[BlockInstance]) -> ProjectConfig
             redis: redis_port,
             phpmyadmin: phpmyadmin_port,
             mailhog: mailhog_port,
+            https: https_port,
         },
         services: ServiceConfig {
             mysql: blocks.iter().any(|b| b.block_id == "mysql" && b.enabled),
@@ -999,5 +1069,6 @@ fn create_project_config_from_blocks(blocks: &[BlockInstance]) -> ProjectConfig
             phpmyadmin: blocks.iter().any(|b| b.block_id == "phpmyadmin" && b.enabled),
             nginx: blocks.iter().any(|b| b.block_id == "nginx" && b.enabled),
         },
+        database_name: None,
     }
 }
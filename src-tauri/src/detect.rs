@@ -0,0 +1,257 @@
+use crate::state::{ProjectConfig, ServiceConfig};
+use crate::template::{default_config, load_template_config};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// What was found in an imported codebase and the configuration chosen from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionReport {
+    pub template: String,
+    /// Version constraint of the framework package, e.g. "^11.0" for laravel/framework.
+    pub framework_version: Option<String>,
+    pub php_version: Option<String>,
+    pub node_version: String,
+    pub services: ServiceConfig,
+    /// Packages that shape the setup, e.g. "horizon", "reverb", "scout (meilisearch)".
+    pub features: Vec<String>,
+    /// One line per finding, saying which file it came from.
+    pub evidence: Vec<String>,
+}
+
+/// A version requirement read from the project. Exact ones come from
+/// `.php-version`/`.nvmrc`, the rest are composer or npm constraints.
+struct Requirement {
+    value: String,
+    exact: bool,
+}
+
+/// Works out which template fits an existing codebase from the files in it.
+pub struct FrameworkDetector;
+
+impl FrameworkDetector {
+    /// The template for the code in `dir`: Laravel when it has `artisan` or
+    /// requires `laravel/framework`, otherwise a Node template chosen from
+    /// the dependencies in `package.json`. `None` when neither applies.
+    pub fn detect_template(dir: &Path) -> Option<&'static str> {
+        if dir.join("artisan").exists() || Self::composer_requires(dir, "laravel/framework") {
+            return Some("laravel");
+        }
+
+        let package = Self::read_json(&dir.join("package.json"))?;
+        let has_dependency = |name: &str| Self::package_dependency(&package, name).is_some();
+
+        Some(if has_dependency("next") {
+            "nextjs"
+        } else if has_dependency("nuxt") {
+            "nuxt"
+        } else if has_dependency("astro") {
+            "astro"
+        } else {
+            "nodejs"
+        })
+    }
+
+    /// Detects the template for the code in `dir` and builds its project
+    /// configuration: the template's defaults, with runtime versions and
+    /// services adjusted to what the code asks for.
+    pub fn detect(dir: &Path, templates_path: &str) -> Result<(ProjectConfig, DetectionReport), String> {
+        let template = Self::detect_template(dir).ok_or_else(|| {
+            format!("No supported framework found in {} (expected artisan, composer.json or package.json)", dir.display())
+        })?;
+        let template_config = load_template_config(templates_path, template)?;
+        let mut config = default_config(templates_path, template)?;
+
+        let composer = Self::read_json(&dir.join("composer.json")).unwrap_or(Value::Null);
+        let package = Self::read_json(&dir.join("package.json")).unwrap_or(Value::Null);
+        let env = Self::read_env(dir);
+        let mut evidence = vec![format!("Detected {} from the project files", template)];
+
+        let framework_version = match template {
+            "laravel" => composer["require"]["laravel/framework"].as_str().map(String::from),
+            "nextjs" => Self::package_dependency(&package, "next"),
+            "nodejs" => None,
+            other => Self::package_dependency(&package, other),
+        };
+        if let Some(version) = &framework_version {
+            evidence.push(format!("Framework version constraint {}", version));
+        }
+
+        // PHP only matters for templates that offer a choice of versions
+        if config.php_version.is_some() {
+            let requirement = Self::read_version_file(&dir.join(".php-version"))
+                .map(|value| (value, true, ".php-version"))
+                .or_else(|| composer["require"]["php"].as_str().map(|v| (v.to_string(), false, "composer.json")));
+            if let Some((value, exact, source)) = requirement {
+                let chosen = Self::pick_version(&template_config["versions"]["php"], &Requirement { value: value.clone(), exact });
+                evidence.push(format!("PHP {} from {}, using {}", value, source, chosen));
+                config.php_version = Some(chosen);
+            }
+        }
+
+        let node_requirement = Self::read_version_file(&dir.join(".nvmrc"))
+            .map(|value| (value, true, ".nvmrc"))
+            .or_else(|| package["engines"]["node"].as_str().map(|v| (v.to_string(), false, "package.json engines")));
+        if let Some((value, exact, source)) = node_requirement {
+            let chosen = Self::pick_version(&template_config["versions"]["node"], &Requirement { value: value.clone(), exact });
+            evidence.push(format!("Node {} from {}, using {}", value, source, chosen));
+            config.node_version = chosen;
+        }
+
+        let mut features = Vec::new();
+        for feature in ["horizon", "reverb", "scout"] {
+            if composer["require"].get(format!("laravel/{}", feature)).is_some() {
+                features.push(match (feature, env.get("SCOUT_DRIVER")) {
+                    ("scout", Some(driver)) => format!("scout ({})", driver),
+                    _ => feature.to_string(),
+                });
+            }
+        }
+
+        Self::detect_services(&template_config, &env, &features, &mut config.services, &mut evidence);
+
+        let report = DetectionReport {
+            template: template.to_string(),
+            framework_version,
+            php_version: config.php_version.clone(),
+            node_version: config.node_version.clone(),
+            services: config.services.clone(),
+            features,
+            evidence,
+        };
+        Ok((config, report))
+    }
+
+    /// Turns services on or off from the `.env` settings. Services the
+    /// template requires stay on, and anything `.env` does not mention keeps
+    /// the template default.
+    fn detect_services(template_config: &Value, env: &HashMap<String, String>, features: &[String], services: &mut ServiceConfig, evidence: &mut Vec<String>) {
+        let required = |service: &str| template_config["services"][service]["required"].as_bool().unwrap_or(false);
+
+        if let Some(connection) = env.get("DB_CONNECTION") {
+            let uses_mysql = matches!(connection.as_str(), "mysql" | "mariadb");
+            services.mysql = uses_mysql || required("mysql");
+            evidence.push(format!("DB_CONNECTION={} in .env", connection));
+        }
+
+        let redis_host = env.get("REDIS_HOST").filter(|host| !host.is_empty() && host.as_str() != "null");
+        if let Some(host) = redis_host {
+            services.redis = true;
+            evidence.push(format!("REDIS_HOST={} in .env", host));
+        } else if features.iter().any(|f| f == "horizon") {
+            services.redis = true;
+            evidence.push("Horizon needs Redis".to_string());
+        } else if !env.is_empty() {
+            services.redis = required("redis");
+        }
+
+        if let Some(host) = env.get("MAIL_HOST") {
+            let mailer = env.get("MAIL_MAILER").map(String::as_str).unwrap_or("smtp");
+            let local_smtp = ["mailhog", "mailpit", "localhost", "127.0.0.1"].contains(&host.as_str()) && mailer == "smtp";
+            services.mailhog = local_smtp || required("mailhog");
+            evidence.push(format!("MAIL_HOST={} (MAIL_MAILER={}) in .env", host, mailer));
+        }
+
+        // phpMyAdmin has nothing to manage without MySQL
+        if !services.mysql {
+            services.phpmyadmin = false;
+        }
+    }
+
+    /// Chooses one of the template's version `options` for `requirement`:
+    /// the matching option for an exact version, otherwise the template
+    /// default when it satisfies the minimum, else the lowest option that
+    /// does. Falls back to the newest option when none is new enough.
+    fn pick_version(versions: &Value, requirement: &Requirement) -> String {
+        let default = versions["default"].as_str().unwrap_or_default().to_string();
+        let options: Vec<&str> = versions["options"]
+            .as_array()
+            .map(|options| options.iter().filter_map(|o| o.as_str()).collect())
+            .unwrap_or_default();
+
+        let minimum = match Self::parse_version(&requirement.value) {
+            Some(minimum) => minimum,
+            None => return default,
+        };
+
+        if requirement.exact {
+            let exact = options.iter().find(|option| {
+                Self::parse_version(option)
+                    .map(|(major, minor)| major == minimum.0 && (minor.is_none() || minimum.1.is_none() || minor == minimum.1))
+                    .unwrap_or(false)
+            });
+            if let Some(option) = exact {
+                return option.to_string();
+            }
+        }
+
+        let key = |(major, minor): (u32, Option<u32>)| (major, minor.unwrap_or(0));
+        let satisfying: Vec<&str> = options
+            .iter()
+            .copied()
+            .filter(|option| Self::parse_version(option).map(|v| key(v) >= key(minimum)).unwrap_or(false))
+            .collect();
+
+        if satisfying.contains(&default.as_str()) {
+            default
+        } else if let Some(lowest) = satisfying.iter().min_by_key(|o| Self::parse_version(o).map(key)) {
+            lowest.to_string()
+        } else {
+            options
+                .iter()
+                .max_by_key(|o| Self::parse_version(o).map(key))
+                .map(|o| o.to_string())
+                .unwrap_or(default)
+        }
+    }
+
+    /// The first `major[.minor]` in a version or constraint, e.g. (8, Some(2)) for "^8.2|^9.0".
+    fn parse_version(value: &str) -> Option<(u32, Option<u32>)> {
+        let pattern = Regex::new(r"(\d+)(?:\.(\d+))?").unwrap();
+        let captures = pattern.captures(value)?;
+        let major = captures[1].parse().ok()?;
+        Some((major, captures.get(2).and_then(|m| m.as_str().parse().ok())))
+    }
+
+    fn read_version_file(path: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        let version = content.lines().next()?.trim();
+        (!version.is_empty()).then(|| version.to_string())
+    }
+
+    /// `.env`, or `.env.example` for a checkout that has none yet.
+    fn read_env(dir: &Path) -> HashMap<String, String> {
+        let content = fs::read_to_string(dir.join(".env"))
+            .or_else(|_| fs::read_to_string(dir.join(".env.example")))
+            .unwrap_or_default();
+
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches(['"', '\'']).to_string()))
+            .collect()
+    }
+
+    fn package_dependency(package: &Value, name: &str) -> Option<String> {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .find_map(|section| package[section].get(name))
+            .and_then(|version| version.as_str().map(String::from))
+    }
+
+    fn composer_requires(dir: &Path, package: &str) -> bool {
+        Self::read_json(&dir.join("composer.json"))
+            .map(|composer| composer["require"].get(package).is_some())
+            .unwrap_or(false)
+    }
+
+    fn read_json(path: &Path) -> Option<Value> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
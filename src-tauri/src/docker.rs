@@ -1,9 +1,16 @@
-use crate::state::{ServiceStatus, SupervisorStatus, SupervisorProgram};
+use crate::engine::{ContainerSummary, EngineClient, EngineError, ExecOptions, StreamKind, PID_MARKER, PID_WRAPPER};
+use crate::jobs::JobContext;
+use crate::project::ProjectManager;
+use crate::state::{ComposeBackend, ContainerRuntimeInfo, ContainerStats, ServiceStatus, SupervisorStatus, SupervisorProgram};
 use std::process::{Command, Stdio};
 use std::path::Path;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use serde::Serialize;
+use serde_json::json;
 
 #[derive(Clone, Serialize)]
 pub struct DockerOutputEvent {
@@ -12,36 +19,172 @@ pub struct DockerOutputEvent {
     pub stream_type: String, // "stdout", "stderr", "status"
 }
 
+#[derive(Clone, Serialize)]
+pub struct ServiceReadinessEvent {
+    pub project_id: String,
+    pub service: String,
+    pub container: String,
+    pub state: String,
+    pub health: Option<String>,
+    pub ready: bool,
+}
+
+pub const DEFAULT_HEALTH_TIMEOUT_SECS: u64 = 180;
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Run inside the db container, using the credentials from its own environment
+const DUMP_DATABASE_COMMAND: &str = "mysqldump -uroot -p\"$MYSQL_ROOT_PASSWORD\" --single-transaction --routines --triggers \"$MYSQL_DATABASE\"";
+const LOAD_DATABASE_COMMAND: &str = "mysql -uroot -p\"$MYSQL_ROOT_PASSWORD\" \"$MYSQL_DATABASE\"";
+
+// Throwaway container that copies one volume's contents into another
+const VOLUME_COPY_IMAGE: &str = "alpine:3";
+
+// Compose backend in use for this process, resolved once from settings or by probing
+static COMPOSE_BACKEND: Mutex<Option<ComposeBackend>> = Mutex::new(None);
+
 pub struct DockerManager;
 
 impl DockerManager {
-    pub fn is_docker_installed() -> bool {
-        Command::new("docker")
-            .arg("--version")
+    /// Returns the version reported by a compose backend, or `None` if it is
+    /// not available on this machine.
+    fn probe_compose(backend: ComposeBackend) -> Option<String> {
+        let output = Command::new(backend.program())
+            .args(backend.base_args())
+            .arg("version")
             .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&output.stderr);
+        let version = regex::Regex::new(r"v?(\d+\.\d+(\.\d+)?)")
+            .ok()
+            .and_then(|re| re.captures(&text).map(|c| c[1].to_string()))
+            .unwrap_or_else(|| text.lines().next().unwrap_or("").trim().to_string());
+        Some(version)
     }
 
-    pub fn get_docker_version() -> Result<String, String> {
-        let output = Command::new("docker")
-            .arg("--version")
-            .output()
-            .map_err(|e| format!("Failed to get Docker version: {}", e))?;
+    /// Probes `docker compose`, `docker-compose` and `podman compose` in that
+    /// order and returns the first one that works.
+    pub fn detect_compose_backend() -> Option<(ComposeBackend, String)> {
+        ComposeBackend::ALL
+            .iter()
+            .find_map(|backend| Self::probe_compose(*backend).map(|version| (*backend, version)))
+    }
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-        } else {
-            Err("Docker not installed or not running".to_string())
+    /// Re-detects the compose backend and records it in the settings file.
+    pub fn refresh_compose_backend() -> Option<(ComposeBackend, String)> {
+        let detected = Self::detect_compose_backend();
+        *COMPOSE_BACKEND.lock().unwrap() = detected.as_ref().map(|(backend, _)| *backend);
+
+        let mut settings = ProjectManager::load_settings();
+        let backend = detected.as_ref().map(|(backend, _)| *backend);
+        if settings.compose_backend != backend {
+            settings.compose_backend = backend;
+            if let Err(e) = ProjectManager::save_settings(&settings) {
+                println!("[DockerManager] Failed to record compose backend: {}", e);
+            }
         }
+
+        detected
     }
 
-    pub fn start_project(project_path: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
+    /// The compose backend already known to this process or recorded in
+    /// the settings, without probing anything.
+    pub fn cached_compose_backend() -> Option<ComposeBackend> {
+        if let Some(backend) = *COMPOSE_BACKEND.lock().unwrap() {
+            return Some(backend);
+        }
+
+        let backend = ProjectManager::load_settings().compose_backend?;
+        *COMPOSE_BACKEND.lock().unwrap() = Some(backend);
+        Some(backend)
+    }
 
-        let output = Command::new("docker-compose")
+    pub fn compose_backend() -> Result<ComposeBackend, String> {
+        if let Some(backend) = Self::cached_compose_backend() {
+            return Ok(backend);
+        }
+
+        Self::refresh_compose_backend()
+            .map(|(backend, _)| backend)
+            .ok_or_else(|| "No compose backend found. Install the Docker Compose plugin, docker-compose or podman-compose.".to_string())
+    }
+
+    /// Builds a compose command for the detected backend, run from the project directory.
+    pub fn compose_command(project_path: &str) -> Result<Command, String> {
+        let backend = Self::compose_backend()?;
+        let mut command = Command::new(backend.program());
+        command.args(backend.base_args()).current_dir(project_path);
+        Ok(command)
+    }
+
+    /// The container runtime in use. Only the cached compose backend is
+    /// asked for its version; all of them are probed again when it no
+    /// longer works or none is known yet.
+    pub fn get_runtime_info() -> ContainerRuntimeInfo {
+        let cached = Self::cached_compose_backend().and_then(|backend| Self::probe_compose(backend).map(|version| (backend, version)));
+        Self::runtime_info(cached.or_else(Self::refresh_compose_backend))
+    }
+
+    /// Probes every compose backend again, e.g. after installing a new one.
+    pub fn refresh_runtime_info() -> ContainerRuntimeInfo {
+        Self::runtime_info(Self::refresh_compose_backend())
+    }
+
+    fn runtime_info(detected: Option<(ComposeBackend, String)>) -> ContainerRuntimeInfo {
+        let engine_program = detected
+            .as_ref()
+            .map(|(backend, _)| backend.engine_program())
+            .unwrap_or("docker");
+
+        let engine_version = ["docker", "podman"]
+            .iter()
+            .filter(|program| detected.is_none() || **program == engine_program)
+            .find_map(|program| {
+                let output = Command::new(program).arg("--version").output().ok()?;
+                output
+                    .status
+                    .success()
+                    .then(|| (program.to_string(), String::from_utf8_lossy(&output.stdout).trim().to_string()))
+            });
+
+        ContainerRuntimeInfo {
+            engine: engine_version.as_ref().map(|(engine, _)| engine.clone()),
+            engine_version: engine_version.map(|(_, version)| version),
+            compose_backend: detected.as_ref().map(|(backend, _)| *backend),
+            compose_command: detected.as_ref().map(|(backend, _)| backend.command_line()),
+            compose_version: detected.map(|(_, version)| version),
+        }
+    }
+
+    pub fn is_docker_installed() -> bool {
+        let engines: &[&str] = match Self::cached_compose_backend() {
+            Some(backend) => &[backend.engine_program()],
+            None => &["docker", "podman"],
+        };
+        engines
+            .iter()
+            .any(|program| Command::new(program).arg("--version").output().map(|o| o.status.success()).unwrap_or(false))
+    }
+
+    pub fn get_docker_version() -> Result<String, String> {
+        let info = Self::get_runtime_info();
+        let engine_version = info
+            .engine_version
+            .ok_or_else(|| "Docker not installed or not running".to_string())?;
+
+        match (info.compose_command, info.compose_version) {
+            (Some(command), Some(version)) => Ok(format!("{} · Compose {} ({})", engine_version, version, command)),
+            _ => Ok(format!("{} · no compose backend found", engine_version)),
+        }
+    }
+
+    pub fn start_project(project_path: &str) -> Result<String, String> {
+        let output = Self::compose_command(project_path)?
             .args(["up", "-d"])
-            .current_dir(path)
             .output()
             .map_err(|e| format!("Failed to start project: {}", e))?;
 
@@ -53,11 +196,8 @@ impl DockerManager {
     }
 
     pub fn stop_project(project_path: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
-
-        let output = Command::new("docker-compose")
+        let output = Self::compose_command(project_path)?
             .args(["down"])
-            .current_dir(path)
             .output()
             .map_err(|e| format!("Failed to stop project: {}", e))?;
 
@@ -69,11 +209,8 @@ impl DockerManager {
     }
 
     pub fn restart_project(project_path: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
-
-        let output = Command::new("docker-compose")
+        let output = Self::compose_command(project_path)?
             .args(["restart"])
-            .current_dir(path)
             .output()
             .map_err(|e| format!("Failed to restart project: {}", e))?;
 
@@ -85,18 +222,14 @@ impl DockerManager {
     }
 
     pub fn rebuild_project(project_path: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
-
         // First stop
-        let _ = Command::new("docker-compose")
+        let _ = Self::compose_command(project_path)?
             .args(["down"])
-            .current_dir(path)
             .output();
 
         // Rebuild
-        let output = Command::new("docker-compose")
+        let output = Self::compose_command(project_path)?
             .args(["build", "--no-cache"])
-            .current_dir(path)
             .output()
             .map_err(|e| format!("Failed to rebuild project: {}", e))?;
 
@@ -105,9 +238,8 @@ impl DockerManager {
         }
 
         // Start again
-        let output = Command::new("docker-compose")
+        let output = Self::compose_command(project_path)?
             .args(["up", "-d"])
-            .current_dir(path)
             .output()
             .map_err(|e| format!("Failed to start rebuilt project: {}", e))?;
 
@@ -119,117 +251,230 @@ impl DockerManager {
     }
 
     // Streaming versions for real-time output
-    pub fn start_project_streaming(app: &AppHandle, project_id: &str, project_path: &str) -> Result<String, String> {
-        Self::run_docker_compose_streaming(app, project_id, project_path, &["up", "-d", "--build"])
+    pub fn start_project_streaming(app: &AppHandle, project_id: &str, project_path: &str, job: &JobContext) -> Result<String, String> {
+        Self::run_docker_compose_streaming(app, project_id, project_path, &["up", "-d", "--build"], job)
     }
 
-    pub fn stop_project_streaming(app: &AppHandle, project_id: &str, project_path: &str) -> Result<String, String> {
-        Self::run_docker_compose_streaming(app, project_id, project_path, &["down"])
+    pub fn stop_project_streaming(app: &AppHandle, project_id: &str, project_path: &str, job: &JobContext) -> Result<String, String> {
+        Self::run_docker_compose_streaming(app, project_id, project_path, &["down"], job)
     }
 
-    pub fn rebuild_project_streaming(app: &AppHandle, project_id: &str, project_path: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
-
+    pub fn rebuild_project_streaming(app: &AppHandle, project_id: &str, project_path: &str, job: &JobContext) -> Result<String, String> {
         // First stop
         Self::emit_output(app, project_id, "Stopping existing containers...", "status");
-        let _ = Command::new("docker-compose")
-            .args(["down"])
-            .current_dir(path)
-            .output();
+        let _ = Self::run_docker_compose_streaming(app, project_id, project_path, &["down"], job);
+        job.check_cancelled()?;
 
         // Rebuild with streaming
         Self::emit_output(app, project_id, "Building containers (this may take a while)...", "status");
-        Self::run_docker_compose_streaming(app, project_id, project_path, &["up", "-d", "--build", "--force-recreate"])
+        Self::run_docker_compose_streaming(app, project_id, project_path, &["up", "-d", "--build", "--force-recreate"], job)
     }
 
-    pub fn install_laravel_streaming(app: &AppHandle, project_id: &str, project_path: &str, project_name: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
+    /// Blocks until every service is running and, where it defines a
+    /// healthcheck, healthy. Emits a `service-readiness` event whenever a
+    /// service's state changes.
+    pub fn wait_for_healthy(app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, timeout: Duration, job: &JobContext) -> Result<(), String> {
+        Self::emit_output(app, project_id, "Waiting for services to become healthy...", "status");
 
-        Self::emit_output(app, project_id, "🚀 Installing fresh Laravel application...", "status");
-        Self::emit_output(app, project_id, "This may take a few minutes...", "status");
+        let client = EngineClient::from_env()?;
+        let deadline = Instant::now() + timeout;
+        let mut reported: HashMap<String, (String, Option<String>)> = HashMap::new();
 
-        // Run composer create-project inside the app container
-        let container_name = format!("{}_app", project_name);
+        loop {
+            job.check_cancelled()?;
 
-        Self::emit_output(app, project_id, &format!("Running: composer create-project laravel/laravel . in container {}", container_name), "status");
+            let containers = Self::find_project_containers(&client, project_path, project_name)
+                .map_err(|e| format!("Failed to get project containers: {}", e))?;
+            if containers.is_empty() {
+                return Err("No containers found for project".to_string());
+            }
 
-        let mut child = Command::new("docker")
-            .args([
-                "exec", "-w", "/var/www/html", &container_name,
-                "composer", "create-project", "laravel/laravel", ".", "--prefer-dist", "--no-interaction"
-            ])
-            .current_dir(path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to run composer: {}", e))?;
+            let mut pending = Vec::new();
+            for container in &containers {
+                let service = container
+                    .service()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| container.name().trim_start_matches(&format!("{}_", project_name)).to_string());
+                let health = container.health();
+                let ready = container.state == "running" && health.as_deref().map(|h| h == "healthy").unwrap_or(true);
+
+                let snapshot = (container.state.clone(), health.clone());
+                if reported.get(&service) != Some(&snapshot) {
+                    if ready {
+                        Self::emit_output(app, project_id, &format!("✓ {} is ready", service), "status");
+                    }
+                    let _ = app.emit("service-readiness", ServiceReadinessEvent {
+                        project_id: project_id.to_string(),
+                        service: service.clone(),
+                        container: container.name(),
+                        state: container.state.clone(),
+                        health: health.clone(),
+                        ready,
+                    });
+                    reported.insert(service.clone(), snapshot);
+                }
 
-        let stdout = child.stdout.take();
-        let stderr = child.stderr.take();
+                if ready {
+                    continue;
+                }
 
-        // Read stdout in a thread
-        let app_clone = app.clone();
-        let project_id_clone = project_id.to_string();
-        let stdout_handle = std::thread::spawn(move || {
-            if let Some(stdout) = stdout {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().map_while(Result::ok) {
-                    Self::emit_output(&app_clone, &project_id_clone, &line, "stdout");
+                match container.state.as_str() {
+                    // One-off services (migrations, asset builds) are done once they exit cleanly
+                    "exited" if container.status.starts_with("Exited (0)") => {}
+                    "exited" | "dead" => return Err(format!("Service '{}' stopped: {}", service, container.status)),
+                    _ => pending.push(format!("{} ({})", service, health.unwrap_or_else(|| container.state.clone()))),
                 }
             }
-        });
 
-        // Read stderr
-        let mut all_stderr = String::new();
-        if let Some(stderr) = stderr {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().map_while(Result::ok) {
-                Self::emit_output(app, project_id, &line, "stderr");
-                all_stderr.push_str(&line);
-                all_stderr.push('\n');
+            if pending.is_empty() {
+                Self::emit_output(app, project_id, "All services are healthy", "status");
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(format!("Timed out after {}s waiting for {}", timeout.as_secs(), pending.join(", ")));
             }
+
+            std::thread::sleep(HEALTH_POLL_INTERVAL);
         }
+    }
 
-        stdout_handle.join().ok();
+    pub fn install_laravel_streaming(app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, job: &JobContext) -> Result<String, String> {
+        Self::emit_output(app, project_id, "🚀 Installing fresh Laravel application...", "status");
+        Self::emit_output(app, project_id, "This may take a few minutes...", "status");
 
-        let status = child.wait().map_err(|e| format!("Failed to wait for composer: {}", e))?;
+        // Run composer create-project inside the app container
+        let client = EngineClient::from_env()?;
+        let container = Self::find_service_container(&client, project_path, project_name, "app")?;
+        let container_name = container.name();
+        let options = ExecOptions {
+            working_dir: Some("/var/www/html".to_string()),
+            ..Default::default()
+        };
 
-        if status.success() {
+        Self::emit_output(app, project_id, &format!("Running: composer create-project laravel/laravel . in container {}", container_name), "status");
+
+        let output = Self::exec_tracked(
+            &client,
+            &container.id,
+            &["composer", "create-project", "laravel/laravel", ".", "--prefer-dist", "--no-interaction"],
+            &options,
+            job,
+            |kind, line| Self::emit_output(app, project_id, line, kind.as_str()),
+        )
+        .map_err(|e| format!("Failed to run composer: {}", e))?;
+        job.check_cancelled()?;
+
+        if output.exit_code == 0 {
             Self::emit_output(app, project_id, "✓ Laravel installed successfully!", "status");
 
             // Run additional setup commands
             Self::emit_output(app, project_id, "Running php artisan key:generate...", "status");
-            let _ = Command::new("docker")
-                .args(["exec", "-w", "/var/www/html", &container_name, "php", "artisan", "key:generate", "--force"])
-                .current_dir(path)
-                .output();
+            let _ = client.exec(&container.id, &["php", "artisan", "key:generate", "--force"], &options);
+            job.check_cancelled()?;
 
             Self::emit_output(app, project_id, "Setting storage permissions...", "status");
-            let _ = Command::new("docker")
-                .args(["exec", "-w", "/var/www/html", &container_name, "chmod", "-R", "777", "storage", "bootstrap/cache"])
-                .current_dir(path)
-                .output();
+            let _ = client.exec(&container.id, &["chmod", "-R", "777", "storage", "bootstrap/cache"], &options);
 
             Self::emit_output(app, project_id, "✓ Laravel setup complete!", "status");
             Ok("Laravel installed successfully".to_string())
         } else {
-            Self::emit_output(app, project_id, &format!("✗ Laravel installation failed: {}", all_stderr), "status");
-            Err(format!("Composer failed: {}", all_stderr))
+            Self::emit_output(app, project_id, &format!("✗ Laravel installation failed: {}", output.stderr), "status");
+            Err(format!("Composer failed: {}", output.stderr))
         }
     }
 
-    fn run_docker_compose_streaming(app: &AppHandle, project_id: &str, project_path: &str, args: &[&str]) -> Result<String, String> {
-        let path = Path::new(project_path);
+    /// Installs Composer and npm dependencies that are declared in `src` but
+    /// missing, e.g. after importing a project that was archived without them.
+    pub fn install_dependencies_streaming(app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, job: &JobContext) -> Result<String, String> {
+        let src = Path::new(project_path).join("src");
+        let steps: [(&str, &str, &[&str]); 2] = [
+            ("composer.json", "vendor", &["composer", "install", "--no-interaction", "--prefer-dist"]),
+            ("package.json", "node_modules", &["npm", "install"]),
+        ];
+
+        let client = EngineClient::from_env()?;
+        let container = Self::find_service_container(&client, project_path, project_name, "app")?;
+        // The container's working directory is where the template mounts src
+        let options = ExecOptions::default();
+
+        for (manifest, installed_dir, cmd) in steps {
+            if !src.join(manifest).exists() || src.join(installed_dir).exists() {
+                continue;
+            }
 
-        Self::emit_output(app, project_id, &format!("Running: docker-compose {}", args.join(" ")), "status");
+            Self::emit_output(app, project_id, &format!("Running: {}", cmd.join(" ")), "status");
+            let output = Self::exec_tracked(&client, &container.id, cmd, &options, job, |kind, line| {
+                Self::emit_output(app, project_id, line, kind.as_str())
+            })
+            .map_err(|e| format!("Failed to run {}: {}", cmd[0], e))?;
+            job.check_cancelled()?;
 
-        let mut child = Command::new("docker-compose")
-            .args(args)
-            .current_dir(path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            if output.exit_code != 0 {
+                return Err(format!("{} failed: {}", cmd.join(" "), output.stderr.trim()));
+            }
+        }
+
+        Ok("Dependencies installed".to_string())
+    }
+
+    /// Runs a template's setup commands (e.g. `php artisan key:generate`)
+    /// one after another in the app container, stopping at the first failure.
+    pub fn run_setup_commands_streaming(app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, commands: &[String], job: &JobContext) -> Result<String, String> {
+        if commands.is_empty() {
+            return Ok("No setup commands".to_string());
+        }
+
+        let client = EngineClient::from_env()?;
+        let container = Self::find_service_container(&client, project_path, project_name, "app")?;
+
+        for command in commands {
+            Self::emit_output(app, project_id, &format!("Running: {}", command), "status");
+            let output = Self::exec_tracked(&client, &container.id, &["sh", "-c", command], &ExecOptions::default(), job, |kind, line| {
+                Self::emit_output(app, project_id, line, kind.as_str())
+            })
+            .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+            job.check_cancelled()?;
+
+            if output.exit_code != 0 {
+                return Err(format!("{} failed: {}", command, output.stderr.trim()));
+            }
+        }
+
+        Ok(format!("Ran {} setup command(s)", commands.len()))
+    }
+
+    /// Runs a command in a container through the Engine API, recording its
+    /// in-container PID on the job so cancelling can kill the process tree.
+    fn exec_tracked<F>(client: &EngineClient, container_id: &str, cmd: &[&str], options: &ExecOptions, job: &JobContext, mut on_line: F) -> Result<crate::engine::ExecOutput, EngineError>
+    where
+        F: FnMut(crate::engine::StreamKind, &str),
+    {
+        let mut wrapped = vec!["sh", "-c", PID_WRAPPER, "sh"];
+        wrapped.extend_from_slice(cmd);
+
+        let result = client.exec_streaming(container_id, &wrapped, options, |kind, line| match line.strip_prefix(PID_MARKER) {
+            Some(pid) => job.track_exec(container_id, pid.trim()),
+            None => on_line(kind, line),
+        });
+        job.release_children();
+        result
+    }
+
+    fn run_docker_compose_streaming(app: &AppHandle, project_id: &str, project_path: &str, args: &[&str], job: &JobContext) -> Result<String, String> {
+        job.check_cancelled()?;
+        let backend = Self::compose_backend()?;
+
+        Self::emit_output(app, project_id, &format!("Running: {} {}", backend.command_line(), args.join(" ")), "status");
+
+        let mut command = Self::compose_command(project_path)?;
+        command.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+        JobContext::prepare_command(&mut command);
+
+        let mut child = command
             .spawn()
-            .map_err(|e| format!("Failed to spawn docker-compose: {}", e))?;
+            .map_err(|e| format!("Failed to spawn {}: {}", backend.command_line(), e))?;
+        job.track_process(child.id());
 
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
@@ -260,8 +505,12 @@ impl DockerManager {
         stdout_handle.join().ok();
 
         let status = child.wait().map_err(|e| format!("Failed to wait for process: {}", e))?;
+        job.release_children();
 
-        if status.success() {
+        if job.is_cancelled() {
+            Self::emit_output(app, project_id, "✗ Cancelled", "status");
+            Err("Job cancelled".to_string())
+        } else if status.success() {
             Self::emit_output(app, project_id, "✓ Command completed successfully", "status");
             Ok("Command completed successfully".to_string())
         } else {
@@ -279,107 +528,216 @@ impl DockerManager {
         let _ = app.emit("docker-output", event);
     }
 
-    pub fn get_project_status(project_path: &str, project_name: &str) -> Result<Vec<ServiceStatus>, String> {
-        let path = Path::new(project_path);
+    /// Compose derives its project name from the directory name, lowercased
+    /// and stripped of anything other than letters, digits, `-` and `_`.
+    pub fn compose_project_name(project_path: &str) -> String {
+        Path::new(project_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect()
+    }
 
-        let output = Command::new("docker-compose")
-            .args(["ps", "--format", "json"])
-            .current_dir(path)
-            .output()
-            .map_err(|e| format!("Failed to get project status: {}", e))?;
+    /// Finds every container belonging to a project, first by compose project
+    /// label and then by the `{name}_` container name prefix our templates use.
+    pub fn find_project_containers(client: &EngineClient, project_path: &str, project_name: &str) -> Result<Vec<ContainerSummary>, EngineError> {
+        let label = format!("com.docker.compose.project={}", Self::compose_project_name(project_path));
+        let containers = client.list_containers(&json!({ "label": [label] }))?;
+        if !containers.is_empty() {
+            return Ok(containers);
+        }
 
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let mut services = Vec::new();
-
-            // Parse docker-compose ps output
-            for line in stdout.lines() {
-                if let Ok(container) = serde_json::from_str::<serde_json::Value>(line) {
-                    let name = container["Name"].as_str().unwrap_or("").to_string();
-                    let state = container["State"].as_str().unwrap_or("unknown").to_string();
-                    let ports = container["Ports"].as_str().unwrap_or("").to_string();
-
-                    services.push(ServiceStatus {
-                        name,
-                        status: state,
-                        container_id: container["ID"].as_str().map(|s| s.to_string()),
-                        ports: ports.split(',').map(|s| s.trim().to_string()).collect(),
-                        health: container["Health"].as_str().map(|s| s.to_string()),
-                    });
-                }
-            }
+        let prefix = format!("{}_", project_name);
+        Ok(client
+            .list_containers(&json!({ "name": [prefix] }))?
+            .into_iter()
+            .filter(|c| c.name().starts_with(&prefix))
+            .collect())
+    }
 
-            // If JSON parsing didn't work, try regular ps
-            if services.is_empty() {
-                services = Self::get_services_fallback(project_path, project_name)?;
-            }
+    /// Resolves a compose service name (e.g. "app") to its container.
+    pub fn find_service_container(client: &EngineClient, project_path: &str, project_name: &str, service: &str) -> Result<ContainerSummary, EngineError> {
+        let containers = Self::find_project_containers(client, project_path, project_name)?;
+        let container_name = format!("{}_{}", project_name, service);
+
+        containers
+            .iter()
+            .find(|c| c.service() == Some(service))
+            .or_else(|| containers.iter().find(|c| c.name() == container_name))
+            .cloned()
+            .ok_or_else(|| EngineError::NotFound(format!("no container for service '{}' (is the project running?)", service)))
+    }
 
-            Ok(services)
-        } else {
-            // Project might not be running
-            Ok(vec![])
+    /// Copies every named volume of the compose project at `source_path`
+    /// into a same-named volume of the one at `target_path`, so the target
+    /// starts with the same data. The source should be stopped. Returns the
+    /// names of the volumes created.
+    pub fn copy_volumes(source_path: &str, target_path: &str) -> Result<Vec<String>, String> {
+        let client = EngineClient::from_env()?;
+        let source_project = Self::compose_project_name(source_path);
+        let target_project = Self::compose_project_name(target_path);
+
+        let label = format!("com.docker.compose.project={}", source_project);
+        let volumes = client
+            .list_volumes(&json!({ "label": [label] }))
+            .map_err(|e| format!("Failed to list volumes: {}", e))?;
+        if volumes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if !client.image_exists(VOLUME_COPY_IMAGE).unwrap_or(false) {
+            client
+                .pull_image(VOLUME_COPY_IMAGE)
+                .map_err(|e| format!("Failed to pull {}: {}", VOLUME_COPY_IMAGE, e))?;
         }
+
+        let mut copied = Vec::new();
+        for volume in volumes {
+            let (Some(source), Some(key)) = (volume["Name"].as_str(), volume["Labels"]["com.docker.compose.volume"].as_str()) else {
+                continue;
+            };
+            let target = format!("{}_{}", target_project, key);
+
+            // Compose adopts the volume on the next `up` through these labels
+            let labels = json!({
+                "com.docker.compose.project": target_project,
+                "com.docker.compose.volume": key,
+            });
+            client
+                .create_volume(&target, &labels)
+                .map_err(|e| format!("Failed to create volume {}: {}", target, e))?;
+
+            println!("[Docker] Copying volume {} -> {}", source, target);
+            let config = json!({
+                "Image": VOLUME_COPY_IMAGE,
+                "Cmd": ["sh", "-c", "cp -a /from/. /to/"],
+                "HostConfig": {
+                    "Binds": [format!("{}:/from:ro", source), format!("{}:/to", target)],
+                },
+            });
+            let container = client
+                .create_container(&format!("{}_volume_copy", target), &config)
+                .map_err(|e| format!("Failed to create copy container for {}: {}", source, e))?;
+            let exit_code = client
+                .start_container(&container)
+                .and_then(|_| client.wait_container(&container))
+                .map_err(|e| format!("Failed to copy volume {}: {}", source, e));
+            let _ = client.remove_container(&container, true);
+
+            match exit_code? {
+                0 => copied.push(target),
+                code => return Err(format!("Copying volume {} failed with exit code {}", source, code)),
+            }
+        }
+
+        Ok(copied)
     }
 
-    fn get_services_fallback(project_path: &str, project_name: &str) -> Result<Vec<ServiceStatus>, String> {
-        let output = Command::new("docker")
-            .args(["ps", "-a", "--filter", &format!("name={}_", project_name), "--format", "{{.Names}}\t{{.Status}}\t{{.Ports}}\t{{.ID}}"])
-            .output()
-            .map_err(|e| format!("Failed to get services: {}", e))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut services = Vec::new();
-
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 4 {
-                let status_str = parts[1].to_lowercase();
-                let status = if status_str.contains("up") {
-                    "running".to_string()
-                } else if status_str.contains("exited") {
-                    "stopped".to_string()
-                } else {
-                    status_str
-                };
-
-                services.push(ServiceStatus {
-                    name: parts[0].to_string(),
-                    status,
-                    container_id: Some(parts[3].to_string()),
-                    ports: parts[2].split(',').map(|s| s.trim().to_string()).collect(),
-                    health: None,
-                });
+    /// Removes named volumes, e.g. ones left behind after their data was copied elsewhere.
+    pub fn remove_volumes(names: &[String]) -> Result<(), String> {
+        let client = EngineClient::from_env()?;
+        for name in names {
+            match client.remove_volume(name) {
+                Ok(()) | Err(EngineError::NotFound(_)) => {}
+                Err(e) => return Err(format!("Failed to remove volume {}: {}", name, e)),
             }
         }
+        Ok(())
+    }
 
-        Ok(services)
+    /// Names of the named volumes belonging to the compose project at `project_path`.
+    pub fn project_volumes(project_path: &str) -> Result<Vec<String>, String> {
+        let client = EngineClient::from_env()?;
+        let label = format!("com.docker.compose.project={}", Self::compose_project_name(project_path));
+        let volumes = client
+            .list_volumes(&json!({ "label": [label] }))
+            .map_err(|e| format!("Failed to list volumes: {}", e))?;
+        Ok(volumes.iter().filter_map(|v| v["Name"].as_str().map(String::from)).collect())
     }
 
-    pub fn get_container_logs(project_path: &str, service: &str, lines: u32) -> Result<String, String> {
-        let path = Path::new(project_path);
+    fn project_name_from_path(project_path: &str) -> String {
+        Path::new(project_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
 
-        let output = Command::new("docker-compose")
-            .args(["logs", "--tail", &lines.to_string(), service])
-            .current_dir(path)
-            .output()
+    pub fn get_project_status(project_path: &str, project_name: &str) -> Result<Vec<ServiceStatus>, String> {
+        let client = EngineClient::from_env()?;
+        let containers = Self::find_project_containers(&client, project_path, project_name)
+            .map_err(|e| format!("Failed to get project status: {}", e))?;
+
+        Ok(containers
+            .iter()
+            .map(|container| ServiceStatus {
+                name: container.name(),
+                status: container.state.clone(),
+                container_id: Some(container.id.chars().take(12).collect()),
+                ports: container.port_strings(),
+                health: container.health(),
+            })
+            .collect())
+    }
+
+    pub fn get_project_stats(project_path: &str, project_name: &str) -> Result<Vec<ContainerStats>, String> {
+        let client = EngineClient::from_env()?;
+        let containers = Self::find_project_containers(&client, project_path, project_name)
+            .map_err(|e| format!("Failed to get project containers: {}", e))?;
+
+        // The daemon waits for two CPU samples per request, so query containers in parallel
+        let samples: Vec<Result<ContainerStats, String>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = containers
+                .iter()
+                .filter(|c| c.state == "running")
+                .map(|container| {
+                    let client = &client;
+                    scope.spawn(move || {
+                        client
+                            .stats(&container.id)
+                            .map(|sample| ContainerStats::from_engine(container, &sample))
+                            .map_err(|e| format!("Failed to get stats for {}: {}", container.name(), e))
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|_| Err("Stats thread panicked".to_string())))
+                .collect()
+        });
+
+        samples.into_iter().collect()
+    }
+
+    pub fn get_container_logs(project_path: &str, service: &str, lines: u32) -> Result<String, String> {
+        let client = EngineClient::from_env()?;
+        let project_name = Self::project_name_from_path(project_path);
+        let container = Self::find_service_container(&client, project_path, &project_name, service)
             .map_err(|e| format!("Failed to get logs: {}", e))?;
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&output.stderr))
+        client
+            .logs(&container.id, lines)
+            .map_err(|e| format!("Failed to get logs: {}", e))
     }
 
     pub fn exec_in_container(project_path: &str, service: &str, command: &str) -> Result<String, String> {
-        let path = Path::new(project_path);
+        let client = EngineClient::from_env()?;
+        let project_name = Self::project_name_from_path(project_path);
+        let container = Self::find_service_container(&client, project_path, &project_name, service)
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
 
-        let output = Command::new("docker-compose")
-            .args(["exec", "-T", service, "sh", "-c", command])
-            .current_dir(path)
-            .output()
+        let output = client
+            .exec(&container.id, &["sh", "-c", command], &ExecOptions::default())
             .map_err(|e| format!("Failed to execute command: {}", e))?;
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        if output.exit_code == 0 {
+            Ok(output.stdout)
+        } else if output.stderr.trim().is_empty() {
+            Err(output.stdout)
         } else {
-            Err(String::from_utf8_lossy(&output.stderr).to_string())
+            Err(output.stderr)
         }
     }
 
@@ -542,6 +900,75 @@ impl DockerManager {
         Ok(format!("Backup created: {}", backup_name))
     }
 
+    /// Streams a `mysqldump` of the project's database from the db container into `destination`.
+    pub fn dump_database_to(project_path: &str, project_name: &str, destination: &Path) -> Result<(), String> {
+        let client = EngineClient::from_env()?;
+        let container = Self::find_service_container(&client, project_path, project_name, "db")?;
+        let exec_id = client
+            .create_exec(&container.id, &["sh", "-c", DUMP_DATABASE_COMMAND], &ExecOptions::default())
+            .map_err(|e| format!("Failed to dump database: {}", e))?;
+        let mut reader = client
+            .start_exec(&exec_id, false)
+            .map_err(|e| format!("Failed to dump database: {}", e))?;
+
+        let mut file = std::fs::File::create(destination)
+            .map_err(|e| format!("Failed to create database dump: {}", e))?;
+        let mut errors = String::new();
+        while let Some((kind, data)) = reader.next_frame().map_err(|e| format!("Failed to read database dump: {}", e))? {
+            match kind {
+                StreamKind::Stderr => errors.push_str(&String::from_utf8_lossy(&data)),
+                _ => file.write_all(&data).map_err(|e| format!("Failed to write database dump: {}", e))?,
+            }
+        }
+
+        let exit_code = client.exec_exit_code(&exec_id).map_err(|e| format!("Failed to dump database: {}", e))?;
+        if exit_code != 0 {
+            return Err(format!("Failed to dump database: {}", errors.trim()));
+        }
+        Ok(())
+    }
+
+    /// Feeds a SQL dump from the host into the project's database.
+    pub fn load_database_dump(project_path: &str, project_name: &str, dump_path: &Path) -> Result<(), String> {
+        let client = EngineClient::from_env()?;
+        let container = Self::find_service_container(&client, project_path, project_name, "db")?;
+        let options = ExecOptions {
+            attach_stdin: true,
+            ..Default::default()
+        };
+        let exec_id = client
+            .create_exec(&container.id, &["sh", "-c", LOAD_DATABASE_COMMAND], &options)
+            .map_err(|e| format!("Failed to load database dump: {}", e))?;
+        let (mut stdin, mut reader) = client
+            .start_exec_attached(&exec_id, false)
+            .map_err(|e| format!("Failed to load database dump: {}", e))?;
+
+        let mut file = std::fs::File::open(dump_path)
+            .map_err(|e| format!("Failed to open database dump: {}", e))?;
+
+        // Write from another thread so mysql's output never backs up behind the input
+        let writer = std::thread::spawn(move || {
+            let result = std::io::copy(&mut file, &mut stdin);
+            let _ = stdin.close_write();
+            result
+        });
+
+        let mut errors = String::new();
+        while let Ok(Some((kind, data))) = reader.next_frame() {
+            if kind == StreamKind::Stderr {
+                errors.push_str(&String::from_utf8_lossy(&data));
+            }
+        }
+        let written = writer.join().map_err(|_| "Failed to load database dump: writer panicked".to_string())?;
+
+        let exit_code = client.exec_exit_code(&exec_id).map_err(|e| format!("Failed to load database dump: {}", e))?;
+        if exit_code != 0 {
+            return Err(format!("Failed to load database dump: {}", errors.trim()));
+        }
+        written.map_err(|e| format!("Failed to send database dump: {}", e))?;
+        Ok(())
+    }
+
     pub fn restore_database(project_path: &str, project_name: &str, backup_name: &str) -> Result<String, String> {
         let backup_path = format!("/var/www/html/backups/{}", backup_name);
 
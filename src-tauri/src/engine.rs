@@ -0,0 +1,907 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+// Kills a process and all of its descendants inside a container, using /proc
+// so it works on slim images without procps.
+const KILL_TREE_SCRIPT: &str = r#"kill_tree() { for t in /proc/$1/task/*; do for c in $(cat $t/children 2>/dev/null); do kill_tree $c; done; done; kill -TERM $1 2>/dev/null; }; kill_tree $1"#;
+
+/// Prefix of the line printed by [`PID_WRAPPER`] before it execs the real
+/// command, so the in-container PID can be killed later.
+pub const PID_MARKER: &str = "godmode-pid:";
+
+/// `sh -c` script that reports its PID and then execs its arguments.
+pub const PID_WRAPPER: &str = "echo \"godmode-pid:$$\"; exec \"$@\"";
+
+/// Errors returned by the Docker Engine API client.
+#[derive(Debug)]
+pub enum EngineError {
+    /// The daemon socket could not be reached.
+    Connect { endpoint: String, source: io::Error },
+    /// Reading from or writing to an open connection failed.
+    Io(io::Error),
+    /// The requested container, exec instance or resource does not exist.
+    NotFound(String),
+    /// The daemon answered with a non-success status code.
+    Api { status: u16, message: String },
+    /// The daemon answered with something we could not understand.
+    Protocol(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Connect { endpoint, source } => {
+                write!(f, "Cannot connect to the Docker Engine at {}: {}", endpoint, source)
+            }
+            EngineError::Io(e) => write!(f, "Docker Engine connection error: {}", e),
+            EngineError::NotFound(message) => write!(f, "Not found: {}", message),
+            EngineError::Api { status, message } => {
+                write!(f, "Docker Engine returned {}: {}", status, message)
+            }
+            EngineError::Protocol(message) => write!(f, "Unexpected Docker Engine response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<io::Error> for EngineError {
+    fn from(e: io::Error) -> Self {
+        EngineError::Io(e)
+    }
+}
+
+impl From<EngineError> for String {
+    fn from(e: EngineError) -> Self {
+        e.to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Endpoint {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+        }
+    }
+}
+
+/// A raw connection to the daemon, either over the local socket or TCP.
+pub enum Connection {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Connection {
+    pub fn try_clone(&self) -> io::Result<Connection> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.try_clone().map(Connection::Unix),
+            Connection::Tcp(s) => s.try_clone().map(Connection::Tcp),
+        }
+    }
+
+    /// Closes both directions, unblocking any thread reading from a clone.
+    pub fn shutdown(&self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.shutdown(std::net::Shutdown::Both),
+            Connection::Tcp(s) => s.shutdown(std::net::Shutdown::Both),
+        }
+    }
+
+    /// Signals end of input while still reading the remaining output.
+    pub fn close_write(&self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.shutdown(std::net::Shutdown::Write),
+            Connection::Tcp(s) => s.shutdown(std::net::Shutdown::Write),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.read(buf),
+            Connection::Tcp(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.write(buf),
+            Connection::Tcp(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(s) => s.flush(),
+            Connection::Tcp(s) => s.flush(),
+        }
+    }
+}
+
+/// Decoded HTTP response body. Handles both `Content-Length` and chunked
+/// transfer encoding, and falls back to reading until the daemon closes the
+/// connection (used by attach/exec streams).
+pub enum Body {
+    Length(io::Take<BufReader<Connection>>),
+    Chunked { reader: BufReader<Connection>, remaining: usize, done: bool },
+    Eof(BufReader<Connection>),
+}
+
+impl Read for Body {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Body::Length(reader) => reader.read(buf),
+            Body::Eof(reader) => reader.read(buf),
+            Body::Chunked { reader, remaining, done } => {
+                if *done {
+                    return Ok(0);
+                }
+
+                if *remaining == 0 {
+                    let mut size_line = String::new();
+                    if reader.read_line(&mut size_line)? == 0 {
+                        *done = true;
+                        return Ok(0);
+                    }
+                    let size_str = size_line.trim().split(';').next().unwrap_or("");
+                    let size = usize::from_str_radix(size_str, 16)
+                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid chunk size: {:?}", size_line)))?;
+
+                    if size == 0 {
+                        // Consume optional trailers up to the terminating blank line
+                        loop {
+                            let mut trailer = String::new();
+                            if reader.read_line(&mut trailer)? == 0 || trailer.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        *done = true;
+                        return Ok(0);
+                    }
+                    *remaining = size;
+                }
+
+                let max = buf.len().min(*remaining);
+                let n = reader.read(&mut buf[..max])?;
+                if n == 0 {
+                    *done = true;
+                    return Ok(0);
+                }
+                *remaining -= n;
+
+                if *remaining == 0 {
+                    let mut crlf = String::new();
+                    reader.read_line(&mut crlf)?;
+                }
+
+                Ok(n)
+            }
+        }
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    body: Body,
+}
+
+impl Response {
+    pub fn into_body(self) -> Body {
+        self.body
+    }
+
+    pub fn bytes(mut self) -> Result<Vec<u8>, EngineError> {
+        let mut buf = Vec::new();
+        self.body.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub fn json<T: DeserializeOwned>(self) -> Result<T, EngineError> {
+        let bytes = self.bytes()?;
+        serde_json::from_slice(&bytes).map_err(|e| EngineError::Protocol(e.to_string()))
+    }
+}
+
+/// Which output stream a multiplexed frame belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+impl StreamKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamKind::Stdin => "stdin",
+            StreamKind::Stdout => "stdout",
+            StreamKind::Stderr => "stderr",
+        }
+    }
+}
+
+/// Reads container output from a logs/attach/exec body. Non-TTY containers
+/// send 8-byte framed (multiplexed) stdout/stderr; TTY containers send raw
+/// bytes which are reported as stdout.
+pub struct OutputReader<R: Read> {
+    inner: R,
+    multiplexed: bool,
+}
+
+impl<R: Read> OutputReader<R> {
+    pub fn new(inner: R, multiplexed: bool) -> Self {
+        Self { inner, multiplexed }
+    }
+
+    /// Returns the next chunk of output, or `None` at end of stream.
+    pub fn next_frame(&mut self) -> io::Result<Option<(StreamKind, Vec<u8>)>> {
+        if !self.multiplexed {
+            let mut buf = vec![0u8; 8192];
+            let n = self.inner.read(&mut buf)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            buf.truncate(n);
+            return Ok(Some((StreamKind::Stdout, buf)));
+        }
+
+        let mut header = [0u8; 8];
+        if let Err(e) = self.inner.read_exact(&mut header) {
+            return if e.kind() == io::ErrorKind::UnexpectedEof { Ok(None) } else { Err(e) };
+        }
+
+        let kind = match header[0] {
+            0 => StreamKind::Stdin,
+            2 => StreamKind::Stderr,
+            _ => StreamKind::Stdout,
+        };
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let mut payload = vec![0u8; size];
+        self.inner.read_exact(&mut payload)?;
+        Ok(Some((kind, payload)))
+    }
+}
+
+/// Splits a stream of output frames into complete lines per stream.
+#[derive(Default)]
+pub struct LineSplitter {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl LineSplitter {
+    pub fn push(&mut self, kind: StreamKind, data: &[u8]) -> Vec<(StreamKind, String)> {
+        let buffer = match kind {
+            StreamKind::Stderr => &mut self.stderr,
+            _ => &mut self.stdout,
+        };
+        buffer.extend_from_slice(data);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            let text = String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string();
+            lines.push((kind, text));
+        }
+        lines
+    }
+
+    pub fn finish(&mut self) -> Vec<(StreamKind, String)> {
+        let mut lines = Vec::new();
+        for (kind, buffer) in [(StreamKind::Stdout, &mut self.stdout), (StreamKind::Stderr, &mut self.stderr)] {
+            if !buffer.is_empty() {
+                lines.push((kind, String::from_utf8_lossy(buffer).trim_end_matches('\r').to_string()));
+                buffer.clear();
+            }
+        }
+        lines
+    }
+}
+
+/// An event from `GET /events`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EngineEvent {
+    /// e.g. "start", "die" or "health_status: unhealthy".
+    #[serde(rename = "Action", default)]
+    pub action: String,
+    #[serde(rename = "Actor", default)]
+    pub actor: EventActor,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventActor {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+    /// Container labels plus `name`, `image` and, for `die`, `exitCode`.
+    #[serde(rename = "Attributes", default)]
+    pub attributes: HashMap<String, String>,
+}
+
+impl EngineEvent {
+    /// The action without its detail, e.g. "health_status" or "exec_start".
+    pub fn base_action(&self) -> &str {
+        self.action.split(':').next().unwrap_or("").trim()
+    }
+
+    pub fn container_name(&self) -> Option<&str> {
+        self.actor.attributes.get("name").map(|s| s.as_str())
+    }
+
+    pub fn compose_project(&self) -> Option<&str> {
+        self.actor.attributes.get("com.docker.compose.project").map(|s| s.as_str())
+    }
+}
+
+/// Events decoded one JSON object at a time as the daemon sends them.
+pub type EventStream = serde_json::StreamDeserializer<'static, serde_json::de::IoRead<Body>, EngineEvent>;
+
+/// Summary of a container as returned by `GET /containers/json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContainerSummary {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Names", default)]
+    pub names: Vec<String>,
+    #[serde(rename = "State", default)]
+    pub state: String,
+    #[serde(rename = "Status", default)]
+    pub status: String,
+    #[serde(rename = "Ports", default)]
+    pub ports: Vec<PortBinding>,
+    #[serde(rename = "Labels", default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PortBinding {
+    #[serde(rename = "IP")]
+    pub ip: Option<String>,
+    #[serde(rename = "PrivatePort")]
+    pub private_port: u16,
+    #[serde(rename = "PublicPort")]
+    pub public_port: Option<u16>,
+    #[serde(rename = "Type")]
+    pub port_type: String,
+}
+
+impl ContainerSummary {
+    pub fn name(&self) -> String {
+        self.names
+            .first()
+            .map(|n| n.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| self.id.chars().take(12).collect())
+    }
+
+    /// The compose service this container belongs to, if any.
+    pub fn service(&self) -> Option<&str> {
+        self.labels.get("com.docker.compose.service").map(|s| s.as_str())
+    }
+
+    /// Health as reported in the status text, e.g. "Up 2 minutes (healthy)".
+    pub fn health(&self) -> Option<String> {
+        let status = self.status.to_lowercase();
+        if status.contains("(unhealthy)") {
+            Some("unhealthy".to_string())
+        } else if status.contains("(healthy)") {
+            Some("healthy".to_string())
+        } else if status.contains("(health: starting)") {
+            Some("starting".to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn port_strings(&self) -> Vec<String> {
+        self.ports
+            .iter()
+            .map(|p| match p.public_port {
+                Some(public) => format!(
+                    "{}:{}->{}/{}",
+                    p.ip.as_deref().unwrap_or("0.0.0.0"),
+                    public,
+                    p.private_port,
+                    p.port_type
+                ),
+                None => format!("{}/{}", p.private_port, p.port_type),
+            })
+            .collect()
+    }
+}
+
+/// Result of running a command to completion inside a container.
+#[derive(Debug, Clone)]
+pub struct ExecOutput {
+    pub exit_code: i64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Query parameters for `GET /containers/{id}/logs`. `since` and `until`
+/// are UNIX timestamps in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct LogsQuery {
+    pub follow: bool,
+    pub timestamps: bool,
+    pub tail: Option<u32>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+/// Options for creating an exec instance.
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    pub working_dir: Option<String>,
+    pub env: Vec<String>,
+    pub tty: bool,
+    pub attach_stdin: bool,
+    /// User to run as, e.g. "www-data" or "1000:1000".
+    pub user: Option<String>,
+}
+
+/// Minimal synchronous client for the Docker Engine HTTP API.
+///
+/// Talks to the local daemon socket (or `DOCKER_HOST` when set) so
+/// per-container operations no longer spawn `docker`/`docker-compose`
+/// processes.
+#[derive(Debug, Clone)]
+pub struct EngineClient {
+    endpoint: Endpoint,
+}
+
+impl EngineClient {
+    /// Builds a client from `DOCKER_HOST`, falling back to the default
+    /// socket (or Docker Desktop's / rootless Podman's per-user socket when
+    /// that is missing).
+    pub fn from_env() -> Result<Self, EngineError> {
+        if let Ok(host) = std::env::var("DOCKER_HOST") {
+            if !host.trim().is_empty() {
+                return Self::from_host(host.trim());
+            }
+        }
+
+        #[cfg(unix)]
+        {
+            let default = PathBuf::from(DEFAULT_SOCKET);
+            if !default.exists() {
+                let home = dirs::home_dir().unwrap_or_default();
+                let mut candidates = vec![
+                    home.join(".docker").join("run").join("docker.sock"),
+                    home.join(".docker").join("desktop").join("docker.sock"),
+                ];
+                if let Some(runtime_dir) = dirs::runtime_dir() {
+                    candidates.push(runtime_dir.join("podman").join("podman.sock"));
+                }
+                for candidate in candidates {
+                    if candidate.exists() {
+                        return Ok(Self { endpoint: Endpoint::Unix(candidate) });
+                    }
+                }
+            }
+            Ok(Self { endpoint: Endpoint::Unix(default) })
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self::from_host("tcp://localhost:2375")
+        }
+    }
+
+    pub fn from_host(host: &str) -> Result<Self, EngineError> {
+        if let Some(path) = host.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Self { endpoint: Endpoint::Unix(PathBuf::from(path)) });
+            #[cfg(not(unix))]
+            return Err(EngineError::Protocol(format!("Unix sockets are not supported on this platform: {}", path)));
+        }
+
+        if let Some(addr) = host.strip_prefix("tcp://").or_else(|| host.strip_prefix("http://")) {
+            return Ok(Self { endpoint: Endpoint::Tcp(addr.trim_end_matches('/').to_string()) });
+        }
+
+        Err(EngineError::Protocol(format!("Unsupported DOCKER_HOST: {}", host)))
+    }
+
+    fn connect(&self) -> Result<Connection, EngineError> {
+        let result = match &self.endpoint {
+            #[cfg(unix)]
+            Endpoint::Unix(path) => UnixStream::connect(path).map(Connection::Unix),
+            Endpoint::Tcp(addr) => TcpStream::connect(addr).map(Connection::Tcp),
+        };
+
+        result.map_err(|source| EngineError::Connect { endpoint: self.endpoint.to_string(), source })
+    }
+
+    fn send(&self, method: &str, path: &str, body: Option<&Value>, upgrade: bool) -> Result<(Connection, Response), EngineError> {
+        let mut conn = self.connect()?;
+        let payload = body.map(|b| b.to_string()).unwrap_or_default();
+
+        let mut head = format!("{} {} HTTP/1.1\r\nHost: docker\r\nUser-Agent: laravel-godmode\r\n", method, path);
+        if upgrade {
+            head.push_str("Connection: Upgrade\r\nUpgrade: tcp\r\n");
+        } else {
+            head.push_str("Connection: close\r\n");
+        }
+        if body.is_some() {
+            head.push_str(&format!("Content-Type: application/json\r\nContent-Length: {}\r\n", payload.len()));
+        }
+        head.push_str("\r\n");
+
+        conn.write_all(head.as_bytes())?;
+        conn.write_all(payload.as_bytes())?;
+        conn.flush()?;
+
+        let writer = conn.try_clone()?;
+        let mut reader = BufReader::new(conn);
+
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line)?;
+        let status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse::<u16>().ok())
+            .ok_or_else(|| EngineError::Protocol(format!("invalid status line: {:?}", status_line.trim())))?;
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _): &&(String, String)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone())
+        };
+
+        let body = if status == 101 {
+            Body::Eof(reader)
+        } else if status == 204 || status == 304 {
+            Body::Length(reader.take(0))
+        } else if header("Transfer-Encoding").map(|v| v.eq_ignore_ascii_case("chunked")).unwrap_or(false) {
+            Body::Chunked { reader, remaining: 0, done: false }
+        } else if let Some(length) = header("Content-Length").and_then(|v| v.parse::<u64>().ok()) {
+            Body::Length(reader.take(length))
+        } else {
+            Body::Eof(reader)
+        };
+
+        let response = Response { status, body };
+
+        if response.status >= 400 {
+            let status = response.status;
+            let bytes = response.bytes().unwrap_or_default();
+            let message = serde_json::from_slice::<Value>(&bytes)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| String::from_utf8_lossy(&bytes).trim().to_string());
+
+            return Err(if status == 404 {
+                EngineError::NotFound(message)
+            } else {
+                EngineError::Api { status, message }
+            });
+        }
+
+        Ok((writer, response))
+    }
+
+    /// Performs a request and returns the response with its body unread.
+    pub fn request(&self, method: &str, path: &str, body: Option<&Value>) -> Result<Response, EngineError> {
+        self.send(method, path, body, false).map(|(_, response)| response)
+    }
+
+    pub fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, EngineError> {
+        self.request("GET", path, None)?.json()
+    }
+
+    /// Lists containers (including stopped ones) matching the given filters,
+    /// e.g. `{"label": ["com.docker.compose.project=blog"]}`.
+    pub fn list_containers(&self, filters: &Value) -> Result<Vec<ContainerSummary>, EngineError> {
+        let path = format!("/containers/json?all=1&filters={}", encode_query(&filters.to_string()));
+        self.get_json(&path)
+    }
+
+    pub fn inspect_container(&self, id: &str) -> Result<Value, EngineError> {
+        self.get_json(&format!("/containers/{}/json", encode_query(id)))
+    }
+
+    pub fn container_has_tty(&self, id: &str) -> Result<bool, EngineError> {
+        let info = self.inspect_container(id)?;
+        Ok(info["Config"]["Tty"].as_bool().unwrap_or(false))
+    }
+
+    /// Returns the last `tail` lines of a container's stdout and stderr.
+    pub fn logs(&self, id: &str, tail: u32) -> Result<String, EngineError> {
+        let query = LogsQuery { tail: Some(tail), ..Default::default() };
+        let (_, mut reader) = self.logs_stream(id, &query)?;
+
+        let mut output = Vec::new();
+        while let Some((_, data)) = reader.next_frame()? {
+            output.extend_from_slice(&data);
+        }
+        Ok(String::from_utf8_lossy(&output).to_string())
+    }
+
+    /// Opens a logs stream. The returned connection can be shut down from
+    /// another thread to stop a `follow` stream.
+    pub fn logs_stream(&self, id: &str, query: &LogsQuery) -> Result<(Connection, OutputReader<Body>), EngineError> {
+        let tty = self.container_has_tty(id)?;
+
+        let mut path = format!("/containers/{}/logs?stdout=1&stderr=1", encode_query(id));
+        if query.follow {
+            path.push_str("&follow=1");
+        }
+        if query.timestamps {
+            path.push_str("&timestamps=1");
+        }
+        if let Some(tail) = query.tail {
+            path.push_str(&format!("&tail={}", tail));
+        }
+        if let Some(since) = query.since {
+            path.push_str(&format!("&since={}", since));
+        }
+        if let Some(until) = query.until {
+            path.push_str(&format!("&until={}", until));
+        }
+
+        let (connection, response) = self.send("GET", &path, None, false)?;
+        Ok((connection, OutputReader::new(response.into_body(), !tty)))
+    }
+
+    /// Subscribes to daemon events matching the given filters, e.g.
+    /// `{"type": ["container"]}`. The stream stays open until the connection
+    /// is shut down or the daemon goes away.
+    pub fn events(&self, filters: &Value) -> Result<(Connection, EventStream), EngineError> {
+        let path = format!("/events?filters={}", encode_query(&filters.to_string()));
+        let (connection, response) = self.send("GET", &path, None, false)?;
+        Ok((connection, serde_json::Deserializer::from_reader(response.into_body()).into_iter()))
+    }
+
+    /// Pulls an image, waiting until the daemon has finished.
+    pub fn pull_image(&self, image: &str) -> Result<(), EngineError> {
+        let (name, tag) = image.rsplit_once(':').unwrap_or((image, "latest"));
+        let path = format!("/images/create?fromImage={}&tag={}", encode_query(name), encode_query(tag));
+        let body = self.request("POST", &path, None)?.bytes()?;
+
+        // Failures are reported inside the progress stream rather than as a status
+        for line in String::from_utf8_lossy(&body).lines() {
+            if let Ok(progress) = serde_json::from_str::<Value>(line) {
+                if let Some(error) = progress["error"].as_str() {
+                    return Err(EngineError::Api { status: 500, message: error.to_string() });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn image_exists(&self, image: &str) -> Result<bool, EngineError> {
+        match self.request("GET", &format!("/images/{}/json", encode_query(image)), None) {
+            Ok(_) => Ok(true),
+            Err(EngineError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a container from a `POST /containers/create` body and returns its id.
+    pub fn create_container(&self, name: &str, config: &Value) -> Result<String, EngineError> {
+        let created: Value = self
+            .request("POST", &format!("/containers/create?name={}", encode_query(name)), Some(config))?
+            .json()?;
+        created["Id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| EngineError::Protocol("container create returned no Id".to_string()))
+    }
+
+    pub fn start_container(&self, id: &str) -> Result<(), EngineError> {
+        self.request("POST", &format!("/containers/{}/start", encode_query(id)), None)?;
+        Ok(())
+    }
+
+    pub fn remove_container(&self, id: &str, force: bool) -> Result<(), EngineError> {
+        let path = format!("/containers/{}?force={}", encode_query(id), force);
+        self.request("DELETE", &path, None)?;
+        Ok(())
+    }
+
+    /// Creates a bridge network unless one with that name already exists.
+    pub fn ensure_network(&self, name: &str) -> Result<(), EngineError> {
+        match self.request("GET", &format!("/networks/{}", encode_query(name)), None) {
+            Ok(_) => Ok(()),
+            Err(EngineError::NotFound(_)) => {
+                let body = json!({ "Name": name, "Driver": "bridge", "CheckDuplicate": true });
+                self.request("POST", "/networks/create", Some(&body))?;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Attaches a container to a network; does nothing if it already is.
+    pub fn connect_network(&self, network: &str, container: &str) -> Result<(), EngineError> {
+        let body = json!({ "Container": container });
+        match self.request("POST", &format!("/networks/{}/connect", encode_query(network)), Some(&body)) {
+            Ok(_) => Ok(()),
+            Err(EngineError::Api { status: 403, .. }) | Err(EngineError::Api { status: 409, .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Blocks until a container exits and returns its exit code.
+    pub fn wait_container(&self, id: &str) -> Result<i64, EngineError> {
+        let result: Value = self.request("POST", &format!("/containers/{}/wait", encode_query(id)), None)?.json()?;
+        Ok(result["StatusCode"].as_i64().unwrap_or(-1))
+    }
+
+    /// Lists volumes matching `filters`, e.g. `{"label": ["com.docker.compose.project=blog"]}`.
+    pub fn list_volumes(&self, filters: &Value) -> Result<Vec<Value>, EngineError> {
+        let path = format!("/volumes?filters={}", encode_query(&filters.to_string()));
+        let result: Value = self.get_json(&path)?;
+        Ok(result["Volumes"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Creates a local volume; returns the existing one if the name is taken.
+    pub fn create_volume(&self, name: &str, labels: &Value) -> Result<(), EngineError> {
+        let body = json!({ "Name": name, "Driver": "local", "Labels": labels });
+        self.request("POST", "/volumes/create", Some(&body))?;
+        Ok(())
+    }
+
+    pub fn remove_volume(&self, name: &str) -> Result<(), EngineError> {
+        self.request("DELETE", &format!("/volumes/{}", encode_query(name)), None)?;
+        Ok(())
+    }
+
+    /// Takes a single stats sample for a container.
+    pub fn stats(&self, id: &str) -> Result<Value, EngineError> {
+        self.get_json(&format!("/containers/{}/stats?stream=false", encode_query(id)))
+    }
+
+    /// Creates an exec instance and returns its id.
+    pub fn create_exec(&self, id: &str, cmd: &[&str], options: &ExecOptions) -> Result<String, EngineError> {
+        let mut body = json!({
+            "AttachStdin": options.attach_stdin,
+            "AttachStdout": true,
+            "AttachStderr": true,
+            "Tty": options.tty,
+            "Cmd": cmd,
+        });
+        if let Some(dir) = &options.working_dir {
+            body["WorkingDir"] = json!(dir);
+        }
+        if let Some(user) = &options.user {
+            body["User"] = json!(user);
+        }
+        if !options.env.is_empty() {
+            body["Env"] = json!(options.env);
+        }
+
+        let created: Value = self
+            .request("POST", &format!("/containers/{}/exec", encode_query(id)), Some(&body))?
+            .json()?;
+        created["Id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| EngineError::Protocol("exec create returned no Id".to_string()))
+    }
+
+    /// Starts an exec instance and returns a reader over its output.
+    pub fn start_exec(&self, exec_id: &str, tty: bool) -> Result<OutputReader<Body>, EngineError> {
+        let body = json!({ "Detach": false, "Tty": tty });
+        let response = self.request("POST", &format!("/exec/{}/start", exec_id), Some(&body))?;
+        Ok(OutputReader::new(response.into_body(), !tty))
+    }
+
+    /// Starts an exec instance created with `attach_stdin` and hijacks the
+    /// connection, returning the write half for stdin and a reader over the
+    /// output.
+    pub fn start_exec_attached(&self, exec_id: &str, tty: bool) -> Result<(Connection, OutputReader<Body>), EngineError> {
+        let body = json!({ "Detach": false, "Tty": tty });
+        let (connection, response) = self.send("POST", &format!("/exec/{}/start", exec_id), Some(&body), true)?;
+        Ok((connection, OutputReader::new(response.into_body(), !tty)))
+    }
+
+    /// Resizes the TTY of an exec instance.
+    pub fn resize_exec(&self, exec_id: &str, rows: u16, cols: u16) -> Result<(), EngineError> {
+        self.request("POST", &format!("/exec/{}/resize?h={}&w={}", exec_id, rows, cols), None)?;
+        Ok(())
+    }
+
+    pub fn exec_exit_code(&self, exec_id: &str) -> Result<i64, EngineError> {
+        let info: Value = self.get_json(&format!("/exec/{}/json", exec_id))?;
+        Ok(info["ExitCode"].as_i64().unwrap_or(-1))
+    }
+
+    /// Runs a command to completion, streaming each output line to `on_line`.
+    pub fn exec_streaming<F>(&self, id: &str, cmd: &[&str], options: &ExecOptions, mut on_line: F) -> Result<ExecOutput, EngineError>
+    where
+        F: FnMut(StreamKind, &str),
+    {
+        let exec_id = self.create_exec(id, cmd, options)?;
+        let mut reader = self.start_exec(&exec_id, options.tty)?;
+
+        let mut splitter = LineSplitter::default();
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let mut collect = |kind: StreamKind, line: String| {
+            on_line(kind, &line);
+            let target = if kind == StreamKind::Stderr { &mut stderr } else { &mut stdout };
+            target.push_str(&line);
+            target.push('\n');
+        };
+
+        while let Some((kind, data)) = reader.next_frame()? {
+            for (kind, line) in splitter.push(kind, &data) {
+                collect(kind, line);
+            }
+        }
+        for (kind, line) in splitter.finish() {
+            collect(kind, line);
+        }
+
+        let exit_code = self.exec_exit_code(&exec_id)?;
+        Ok(ExecOutput { exit_code, stdout, stderr })
+    }
+
+    /// Sends SIGTERM to a process inside a container and to everything it spawned.
+    pub fn kill_process_tree(&self, id: &str, pid: &str) -> Result<(), EngineError> {
+        self.exec(id, &["sh", "-c", KILL_TREE_SCRIPT, "sh", pid], &ExecOptions::default())?;
+        Ok(())
+    }
+
+    /// Runs a command to completion and returns its buffered output.
+    pub fn exec(&self, id: &str, cmd: &[&str], options: &ExecOptions) -> Result<ExecOutput, EngineError> {
+        self.exec_streaming(id, cmd, options, |_, _| {})
+    }
+}
+
+/// Percent-encodes a value for use in a query string or path segment.
+pub fn encode_query(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,101 @@
+use crate::detect::DetectionReport;
+use crate::project::ProjectManager;
+use crate::state::Project;
+use crate::template::setup_commands;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Clones repositories with the system `git`, so credentials helpers, SSH
+/// keys and local paths work the same as on the command line.
+pub struct GitImporter;
+
+impl GitImporter {
+    /// The project name a repository URL suggests: its last path segment
+    /// without `.git`, e.g. `shop` for `git@github.com:acme/shop.git`.
+    pub fn name_from_url(url: &str) -> Option<String> {
+        let trimmed = url.trim_end_matches(['/', '\\']);
+        let segment = trimmed.rsplit(['/', '\\', ':']).next()?;
+        let name = segment.strip_suffix(".git").unwrap_or(segment);
+        if name.is_empty() {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+
+    /// Clones `url` into `destination`, which must not exist yet, checking
+    /// out `branch` when given. Accepts anything `git clone` does, including
+    /// local (bare) repositories and `file://` URLs. A partial clone is
+    /// removed on failure.
+    pub fn clone(url: &str, branch: Option<&str>, destination: &Path) -> Result<(), String> {
+        let url = url.trim();
+        if url.is_empty() {
+            return Err("Repository URL is required".to_string());
+        }
+        // Keeps a URL from being read as an option by git
+        if url.starts_with('-') || branch.map(|b| b.starts_with('-')).unwrap_or(false) {
+            return Err(format!("Invalid repository URL or branch: {}", url));
+        }
+        if destination.exists() {
+            return Err(format!("Directory already exists: {}", destination.display()));
+        }
+
+        let mut command = Command::new("git");
+        command.arg("clone");
+        if let Some(branch) = branch.filter(|b| !b.is_empty()) {
+            command.args(["--branch", branch]);
+        }
+        command
+            .arg("--")
+            .arg(url)
+            .arg(destination)
+            // Fail instead of waiting on a password prompt nobody can answer
+            .env("GIT_TERMINAL_PROMPT", "0");
+
+        println!("[Git] Cloning {} into {:?}", url, destination);
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to run git (is it installed?): {}", e))?;
+
+        if !output.status.success() {
+            let _ = fs::remove_dir_all(destination);
+            return Err(format!(
+                "Failed to clone {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Clones `url` as the `src` directory of a new project, detects its
+    /// framework and generates the matching template's docker scaffolding
+    /// around it. Returns the saved project, what was detected and the
+    /// template's setup commands, which still have to run once it is up.
+    pub fn import(url: &str, name: Option<&str>, branch: Option<&str>, templates_path: &str) -> Result<(Project, DetectionReport, Vec<String>), String> {
+        let name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(name) => name.to_string(),
+            None => Self::name_from_url(url).ok_or_else(|| format!("Cannot derive a project name from {}", url))?,
+        };
+
+        let projects_dir = ProjectManager::ensure_projects_dir()?;
+        let project_path = Path::new(&projects_dir).join(&name);
+        if project_path.exists() {
+            return Err(format!("A project directory named '{}' already exists", name));
+        }
+        fs::create_dir_all(&project_path).map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        let result = Self::clone(url, branch, &project_path.join("src")).and_then(|_| {
+            let (project, report) = ProjectManager::scaffold_imported(&name, templates_path)?;
+            let setup = setup_commands(templates_path, &project.template)?;
+            Ok((project, report, setup))
+        });
+
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&project_path);
+        }
+        result
+    }
+}
@@ -0,0 +1,322 @@
+use crate::project::ProjectManager;
+use crate::proxy::ProxyManager;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+const BLOCK_START: &str = "# >>> laravel-godmode >>> (managed by Laravel GodMode, do not edit)";
+const BLOCK_END: &str = "# <<< laravel-godmode <<<";
+const LOOPBACK: &str = "127.0.0.1";
+
+// Serialises writes triggered from different threads
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostsPreview {
+    pub path: String,
+    pub changed: bool,
+    /// Entry lines the managed block gains, e.g. "127.0.0.1 shop.test".
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// The managed block before and after, as a unified-style line diff.
+    pub diff: String,
+}
+
+/// Keeps a delimited block of `127.0.0.1 <host>` lines in the hosts file in
+/// step with the projects, leaving everything outside the block alone.
+pub struct HostsManager;
+
+impl HostsManager {
+    pub fn system_hosts_path() -> PathBuf {
+        if cfg!(target_os = "windows") {
+            PathBuf::from(r"C:\Windows\System32\drivers\etc\hosts")
+        } else {
+            PathBuf::from("/etc/hosts")
+        }
+    }
+
+    /// `path` if given, else the configured path, else the system hosts file.
+    fn resolve_path(path: Option<&str>) -> PathBuf {
+        path.map(PathBuf::from)
+            .or_else(|| ProjectManager::load_settings().hosts.path.map(PathBuf::from))
+            .unwrap_or_else(Self::system_hosts_path)
+    }
+
+    /// The entries the managed block should contain, one per host name of every project.
+    pub fn desired_entries() -> Result<Vec<String>, String> {
+        let projects = ProjectManager::load_all_projects()?;
+        let mut entries: Vec<String> = projects
+            .values()
+            .flat_map(|project| ProxyManager::project_hosts(&project.name))
+            .map(|host| format!("{} {}", LOOPBACK, host))
+            .collect();
+        entries.sort();
+        entries.dedup();
+        Ok(entries)
+    }
+
+    /// What `apply` would change, without touching the file.
+    pub fn preview(path: Option<&str>) -> Result<HostsPreview, String> {
+        let path = Self::resolve_path(path);
+        let content = Self::read(&path)?;
+        Ok(Self::diff(&path, &content, &Self::desired_entries()?))
+    }
+
+    /// Rewrites the managed block to match the current projects.
+    pub fn apply(path: Option<&str>) -> Result<HostsPreview, String> {
+        Self::write_entries(&Self::resolve_path(path), &Self::desired_entries()?)
+    }
+
+    /// Removes the managed block entirely.
+    pub fn remove(path: Option<&str>) -> Result<HostsPreview, String> {
+        Self::write_entries(&Self::resolve_path(path), &[])
+    }
+
+    /// Syncs on a background thread when hosts management is enabled, for
+    /// callers that change the set of projects (create/delete/rename).
+    pub fn sync_in_background() {
+        if !ProjectManager::load_settings().hosts.managed {
+            return;
+        }
+
+        std::thread::spawn(|| {
+            if let Err(e) = Self::apply(None) {
+                println!("[Hosts] Failed to update hosts file: {}", e);
+            }
+        });
+    }
+
+    fn write_entries(path: &Path, entries: &[String]) -> Result<HostsPreview, String> {
+        let _guard = WRITE_LOCK.lock().unwrap();
+
+        let content = Self::read(path)?;
+        let preview = Self::diff(path, &content, entries);
+        if !preview.changed {
+            return Ok(preview);
+        }
+
+        let updated = Self::render(&content, entries);
+        match fs::write(path, &updated) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && path == Self::system_hosts_path() => {
+                Self::write_elevated(path, &updated)?;
+            }
+            Err(e) => return Err(format!("Failed to write {}: {}", path.display(), e)),
+        }
+
+        println!("[Hosts] Updated {} (+{} -{})", path.display(), preview.added.len(), preview.removed.len());
+        Ok(preview)
+    }
+
+    fn read(path: &Path) -> Result<String, String> {
+        if !path.exists() {
+            return Ok(String::new());
+        }
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+
+    /// Lines currently inside the managed block.
+    fn current_entries(content: &str) -> Vec<String> {
+        let mut inside = false;
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line == BLOCK_START {
+                inside = true;
+            } else if line == BLOCK_END {
+                inside = false;
+            } else if inside && !line.is_empty() {
+                entries.push(line.to_string());
+            }
+        }
+        entries
+    }
+
+    fn diff(path: &Path, content: &str, entries: &[String]) -> HostsPreview {
+        let current = Self::current_entries(content);
+        let added: Vec<String> = entries.iter().filter(|e| !current.contains(e)).cloned().collect();
+        let removed: Vec<String> = current.iter().filter(|e| !entries.contains(e)).cloned().collect();
+
+        let mut lines: Vec<(&str, &str)> = current
+            .iter()
+            .map(|e| (if removed.contains(e) { "-" } else { " " }, e.as_str()))
+            .chain(added.iter().map(|e| ("+", e.as_str())))
+            .collect();
+        lines.sort_by_key(|(_, entry)| *entry);
+        let diff = lines.iter().map(|(sign, entry)| format!("{} {}", sign, entry)).collect::<Vec<_>>().join("\n");
+
+        HostsPreview {
+            path: path.to_string_lossy().to_string(),
+            changed: !added.is_empty() || !removed.is_empty() || (entries.is_empty() && content.contains(BLOCK_START)),
+            added,
+            removed,
+            diff,
+        }
+    }
+
+    /// `content` with the managed block replaced by `entries`, dropped when
+    /// there are none, or appended when the file has no block yet.
+    fn render(content: &str, entries: &[String]) -> String {
+        let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+
+        let mut outside: Vec<&str> = Vec::new();
+        let mut insert_at = None;
+        let mut inside = false;
+        for line in content.lines() {
+            match line.trim() {
+                BLOCK_START => {
+                    inside = true;
+                    insert_at.get_or_insert(outside.len());
+                }
+                BLOCK_END => inside = false,
+                _ if !inside => outside.push(line),
+                _ => {}
+            }
+        }
+
+        let mut block = Vec::new();
+        if !entries.is_empty() {
+            block.push(BLOCK_START);
+            block.extend(entries.iter().map(|e| e.as_str()));
+            block.push(BLOCK_END);
+        }
+
+        let mut lines = outside;
+        match insert_at {
+            Some(index) => {
+                lines.splice(index..index, block);
+            }
+            None if !block.is_empty() => {
+                if lines.last().map(|l| !l.trim().is_empty()).unwrap_or(false) {
+                    lines.push("");
+                }
+                lines.extend(block);
+            }
+            None => {}
+        }
+
+        let mut rendered = lines.join(newline);
+        rendered.push_str(newline);
+        rendered
+    }
+
+    /// Writes the system hosts file through the platform's privilege prompt.
+    fn write_elevated(path: &Path, content: &str) -> Result<(), String> {
+        let staged = std::env::temp_dir().join("laravel-godmode-hosts");
+        fs::write(&staged, content).map_err(|e| format!("Failed to stage hosts file: {}", e))?;
+        let (staged_str, target) = (staged.to_string_lossy().to_string(), path.to_string_lossy().to_string());
+
+        let output = if cfg!(target_os = "macos") {
+            let script = format!("do shell script \"cp '{}' '{}'\" with administrator privileges", staged_str, target);
+            Command::new("osascript").args(["-e", &script]).output()
+        } else if cfg!(target_os = "windows") {
+            let script = format!(
+                "Start-Process -Verb RunAs -Wait -FilePath cmd -ArgumentList '/c copy /y \"{}\" \"{}\"'",
+                staged_str, target
+            );
+            Command::new("powershell").args(["-NoProfile", "-Command", &script]).output()
+        } else {
+            Command::new("pkexec").args(["cp", &staged_str, &target]).output()
+        };
+        let _ = fs::remove_file(&staged);
+
+        let output = output.map_err(|e| format!("Failed to request permission to write {}: {}", target, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Permission to write {} was denied: {}",
+                target,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(hosts: &[&str]) -> Vec<String> {
+        hosts.iter().map(|host| format!("{} {}", LOOPBACK, host)).collect()
+    }
+
+    fn block(hosts: &[&str]) -> String {
+        let mut lines = vec![BLOCK_START.to_string()];
+        lines.extend(entries(hosts));
+        lines.push(BLOCK_END.to_string());
+        lines.join("\n")
+    }
+
+    #[test]
+    fn render_appends_the_block_to_a_file_without_one() {
+        assert_eq!(HostsManager::render("", &entries(&["shop.test"])), format!("{}\n", block(&["shop.test"])));
+
+        let content = "127.0.0.1 localhost\n";
+        let rendered = HostsManager::render(content, &entries(&["shop.test"]));
+        assert_eq!(rendered, format!("127.0.0.1 localhost\n\n{}\n", block(&["shop.test"])));
+    }
+
+    #[test]
+    fn render_replaces_the_block_in_place() {
+        let content = format!("127.0.0.1 localhost\n{}\n::1 localhost\n", block(&["shop.test"]));
+
+        let rendered = HostsManager::render(&content, &entries(&["blog.test", "shop.test"]));
+
+        assert_eq!(rendered, format!("127.0.0.1 localhost\n{}\n::1 localhost\n", block(&["blog.test", "shop.test"])));
+        assert_eq!(HostsManager::current_entries(&rendered), entries(&["blog.test", "shop.test"]));
+    }
+
+    #[test]
+    fn render_keeps_windows_line_endings() {
+        let content = "127.0.0.1 localhost\r\n";
+
+        let rendered = HostsManager::render(content, &entries(&["shop.test"]));
+
+        assert_eq!(rendered, format!("127.0.0.1 localhost\r\n\r\n{}\r\n", block(&["shop.test"]).replace('\n', "\r\n")));
+        assert_eq!(HostsManager::current_entries(&rendered), entries(&["shop.test"]));
+    }
+
+    #[test]
+    fn render_without_entries_removes_the_block() {
+        let content = format!("127.0.0.1 localhost\n{}\n::1 localhost\n", block(&["shop.test"]));
+
+        assert_eq!(HostsManager::render(&content, &[]), "127.0.0.1 localhost\n::1 localhost\n");
+    }
+
+    #[test]
+    fn diff_lists_added_and_removed_entries() {
+        let content = format!("{}\n", block(&["old.test", "shop.test"]));
+
+        let preview = HostsManager::diff(Path::new("hosts"), &content, &entries(&["new.test", "shop.test"]));
+
+        assert!(preview.changed);
+        assert_eq!(preview.added, entries(&["new.test"]));
+        assert_eq!(preview.removed, entries(&["old.test"]));
+        assert_eq!(preview.diff, "+ 127.0.0.1 new.test\n- 127.0.0.1 old.test\n  127.0.0.1 shop.test");
+    }
+
+    #[test]
+    fn diff_sees_no_change_when_the_block_matches() {
+        let content = format!("{}\n", block(&["shop.test"]));
+
+        assert!(!HostsManager::diff(Path::new("hosts"), &content, &entries(&["shop.test"])).changed);
+        // An empty block still has to go
+        assert!(HostsManager::diff(Path::new("hosts"), &format!("{}\n", block(&[])), &[]).changed);
+        assert!(!HostsManager::diff(Path::new("hosts"), "127.0.0.1 localhost\n", &[]).changed);
+    }
+
+    #[test]
+    fn remove_drops_the_block_from_an_alternate_file() {
+        let path = std::env::temp_dir().join(format!("godmode-hosts-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, format!("127.0.0.1 localhost\n{}\n", block(&["shop.test"]))).unwrap();
+
+        let preview = HostsManager::remove(Some(&path.to_string_lossy())).unwrap();
+
+        assert_eq!(preview.removed, entries(&["shop.test"]));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "127.0.0.1 localhost\n");
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,346 @@
+use crate::engine::EngineClient;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+// How many finished jobs to keep around for `list_jobs`
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Start,
+    Stop,
+    Rebuild,
+    InstallLaravel,
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub project_id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum TrackedChild {
+    /// A host process, spawned as the leader of its own process group.
+    Process(u32),
+    /// A process started through the Engine API inside a container.
+    Exec { container_id: String, pid: String },
+}
+
+/// Handle passed to a running job so it can register the processes it spawns
+/// and check whether it has been cancelled.
+#[derive(Clone)]
+pub struct JobContext {
+    pub id: String,
+    cancelled: Arc<AtomicBool>,
+    children: Arc<Mutex<Vec<TrackedChild>>>,
+}
+
+impl JobContext {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            children: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns an error if the job was cancelled, for use between steps.
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("Job cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Prepares a command so that it and everything it spawns can be killed together.
+    pub fn prepare_command(command: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    pub fn track_process(&self, pid: u32) {
+        self.children.lock().unwrap().push(TrackedChild::Process(pid));
+        if self.is_cancelled() {
+            self.kill_children();
+        }
+    }
+
+    pub fn track_exec(&self, container_id: &str, pid: &str) {
+        self.children.lock().unwrap().push(TrackedChild::Exec {
+            container_id: container_id.to_string(),
+            pid: pid.to_string(),
+        });
+        if self.is_cancelled() {
+            self.kill_children();
+        }
+    }
+
+    /// Forgets tracked processes once they have exited, so a recycled PID is never killed.
+    pub fn release_children(&self) {
+        self.children.lock().unwrap().clear();
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.kill_children();
+    }
+
+    fn kill_children(&self) {
+        let children: Vec<TrackedChild> = self.children.lock().unwrap().drain(..).collect();
+        for child in children {
+            match child {
+                TrackedChild::Process(pid) => {
+                    #[cfg(unix)]
+                    let _ = Command::new("kill").args(["-TERM", &format!("-{}", pid)]).output();
+                    #[cfg(windows)]
+                    let _ = Command::new("taskkill").args(["/PID", &pid.to_string(), "/T", "/F"]).output();
+                }
+                TrackedChild::Exec { container_id, pid } => {
+                    if let Ok(client) = EngineClient::from_env() {
+                        let _ = client.kill_process_tree(&container_id, &pid);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct JobEntry {
+    info: JobInfo,
+    context: JobContext,
+}
+
+#[derive(Default)]
+struct JobsInner {
+    jobs: HashMap<String, JobEntry>,
+    // Job ids waiting or running per project; the front of each queue is the one allowed to run
+    queues: HashMap<String, VecDeque<String>>,
+}
+
+/// Tracks long-running project operations, runs them one at a time per
+/// project and lets the UI list and cancel them.
+#[derive(Default)]
+pub struct JobRegistry {
+    inner: Mutex<JobsInner>,
+    turn: Condvar,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new job for a project and queues it behind any other
+    /// operation already running for that project.
+    pub fn enqueue(&self, app: &AppHandle, project_id: &str, kind: JobKind) -> JobContext {
+        let id = Uuid::new_v4().to_string();
+        let context = JobContext::new(&id);
+        let info = JobInfo {
+            id: id.clone(),
+            project_id: project_id.to_string(),
+            kind,
+            status: JobStatus::Queued,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        };
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.queues.entry(project_id.to_string()).or_default().push_back(id.clone());
+            inner.jobs.insert(id, JobEntry { info: info.clone(), context: context.clone() });
+        }
+
+        Self::emit(app, &info);
+        context
+    }
+
+    /// Waits for the job's turn, runs it and records the outcome. Must be
+    /// called from a blocking thread.
+    pub fn run<F>(&self, app: &AppHandle, context: &JobContext, job: F) -> Result<String, String>
+    where
+        F: FnOnce() -> Result<String, String>,
+    {
+        if let Err(e) = self.wait_turn(context) {
+            self.finish(app, context, Err(e.clone()));
+            return Err(e);
+        }
+
+        if let Some(info) = self.update(&context.id, |info| {
+            info.status = JobStatus::Running;
+            info.started_at = Some(Utc::now());
+        }) {
+            Self::emit(app, &info);
+        }
+
+        let result = job();
+        self.finish(app, context, result.clone());
+        result
+    }
+
+    fn wait_turn(&self, context: &JobContext) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        loop {
+            if context.is_cancelled() {
+                return Err("Job cancelled".to_string());
+            }
+
+            let project_id = match inner.jobs.get(&context.id) {
+                Some(entry) => entry.info.project_id.clone(),
+                None => return Err("Job no longer exists".to_string()),
+            };
+            let is_next = inner
+                .queues
+                .get(&project_id)
+                .and_then(|queue| queue.front())
+                .map(|front| front == &context.id)
+                .unwrap_or(false);
+
+            if is_next {
+                return Ok(());
+            }
+
+            inner = self.turn.wait(inner).unwrap();
+        }
+    }
+
+    fn finish(&self, app: &AppHandle, context: &JobContext, result: Result<String, String>) {
+        let info = {
+            let mut inner = self.inner.lock().unwrap();
+
+            let project_id = inner.jobs.get(&context.id).map(|entry| entry.info.project_id.clone());
+            if let Some(project_id) = project_id {
+                if let Some(queue) = inner.queues.get_mut(&project_id) {
+                    queue.retain(|id| id != &context.id);
+                    if queue.is_empty() {
+                        inner.queues.remove(&project_id);
+                    }
+                }
+            }
+
+            let info = inner.jobs.get_mut(&context.id).map(|entry| {
+                entry.info.finished_at = Some(Utc::now());
+                match &result {
+                    _ if context.is_cancelled() => entry.info.status = JobStatus::Cancelled,
+                    Ok(_) => entry.info.status = JobStatus::Completed,
+                    Err(e) => {
+                        entry.info.status = JobStatus::Failed;
+                        entry.info.error = Some(e.clone());
+                    }
+                }
+                entry.info.clone()
+            });
+
+            Self::prune(&mut inner);
+            info
+        };
+
+        self.turn.notify_all();
+        if let Some(info) = info {
+            Self::emit(app, &info);
+        }
+    }
+
+    fn prune(inner: &mut JobsInner) {
+        let mut finished: Vec<(DateTime<Utc>, String)> = inner
+            .jobs
+            .values()
+            .filter(|entry| entry.info.status.is_finished())
+            .map(|entry| (entry.info.finished_at.unwrap_or(entry.info.created_at), entry.info.id.clone()))
+            .collect();
+
+        if finished.len() > MAX_FINISHED_JOBS {
+            finished.sort();
+            for (_, id) in finished.iter().take(finished.len() - MAX_FINISHED_JOBS) {
+                inner.jobs.remove(id);
+            }
+        }
+    }
+
+    fn update<F: FnOnce(&mut JobInfo)>(&self, job_id: &str, f: F) -> Option<JobInfo> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.jobs.get_mut(job_id).map(|entry| {
+            f(&mut entry.info);
+            entry.info.clone()
+        })
+    }
+
+    pub fn list(&self, project_id: Option<&str>) -> Vec<JobInfo> {
+        let inner = self.inner.lock().unwrap();
+        let mut jobs: Vec<JobInfo> = inner
+            .jobs
+            .values()
+            .filter(|entry| project_id.map(|p| entry.info.project_id == p).unwrap_or(true))
+            .map(|entry| entry.info.clone())
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        jobs
+    }
+
+    /// Whether a job is queued or running for the project.
+    pub fn has_active(&self, project_id: &str) -> bool {
+        self.inner.lock().unwrap().queues.contains_key(project_id)
+    }
+
+    /// Cancels a queued or running job, killing any processes it started.
+    pub fn cancel(&self, job_id: &str) -> Result<JobInfo, String> {
+        let (context, info) = {
+            let inner = self.inner.lock().unwrap();
+            let entry = inner.jobs.get(job_id).ok_or_else(|| "Job not found".to_string())?;
+            if entry.info.status.is_finished() {
+                return Err("Job has already finished".to_string());
+            }
+            (entry.context.clone(), entry.info.clone())
+        };
+
+        context.cancel();
+        self.turn.notify_all();
+        Ok(info)
+    }
+
+    fn emit(app: &AppHandle, info: &JobInfo) {
+        let _ = app.emit("job-update", info);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::docker::DockerManager;
+use crate::engine::{Connection, EngineClient, LineSplitter, LogsQuery};
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+// Lines kept per stream so the UI can reattach without losing recent history
+const LOG_BUFFER_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogStreamOptions {
+    /// Services to follow; empty means every service of the project.
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// RFC 3339 timestamp, UNIX seconds or a relative duration such as "10m".
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Only lines matching this regex are kept.
+    pub include: Option<String>,
+    /// Lines matching this regex are dropped.
+    pub exclude: Option<String>,
+    /// Number of existing lines to replay per container before following.
+    pub tail: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub stream_id: String,
+    pub project_id: String,
+    pub service: String,
+    pub container: String,
+    pub stream: String,
+    pub timestamp: Option<String>,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogStreamInfo {
+    pub id: String,
+    pub project_id: String,
+    pub services: Vec<String>,
+    pub options: LogStreamOptions,
+    pub active: bool,
+    pub buffered_lines: usize,
+}
+
+struct LogStream {
+    project_id: String,
+    services: Vec<String>,
+    options: LogStreamOptions,
+    stopped: Arc<AtomicBool>,
+    // Followers still attached to a container
+    running: Arc<AtomicUsize>,
+    connections: Arc<Mutex<Vec<Connection>>>,
+    buffer: Arc<Mutex<VecDeque<LogLine>>>,
+}
+
+impl LogStream {
+    fn info(&self, id: &str) -> LogStreamInfo {
+        LogStreamInfo {
+            id: id.to_string(),
+            project_id: self.project_id.clone(),
+            services: self.services.clone(),
+            options: self.options.clone(),
+            active: !self.stopped.load(Ordering::SeqCst) && self.running.load(Ordering::SeqCst) > 0,
+            buffered_lines: self.buffer.lock().unwrap().len(),
+        }
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        for connection in self.connections.lock().unwrap().drain(..) {
+            let _ = connection.shutdown();
+        }
+    }
+}
+
+struct LineFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+}
+
+impl LineFilter {
+    fn new(options: &LogStreamOptions) -> Result<Self, String> {
+        let compile = |pattern: &Option<String>, label: &str| -> Result<Option<Regex>, String> {
+            match pattern.as_deref().map(str::trim) {
+                Some(p) if !p.is_empty() => Regex::new(p)
+                    .map(Some)
+                    .map_err(|e| format!("Invalid {} filter: {}", label, e)),
+                _ => Ok(None),
+            }
+        };
+
+        Ok(Self {
+            include: compile(&options.include, "include")?,
+            exclude: compile(&options.exclude, "exclude")?,
+        })
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        self.include.as_ref().map(|re| re.is_match(line)).unwrap_or(true)
+            && !self.exclude.as_ref().map(|re| re.is_match(line)).unwrap_or(false)
+    }
+}
+
+/// Follows container logs for projects and keeps a bounded history per stream.
+#[derive(Default)]
+pub struct LogStreamManager {
+    streams: Mutex<HashMap<String, LogStream>>,
+}
+
+impl LogStreamManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts following logs and emits each line as a `container-log` event.
+    pub fn start(&self, app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, options: LogStreamOptions) -> Result<String, String> {
+        let filter = Arc::new(LineFilter::new(&options)?);
+        let since = options.since.as_deref().map(parse_time_bound).transpose()?;
+        let until = options.until.as_deref().map(parse_time_bound).transpose()?;
+
+        let client = EngineClient::from_env()?;
+        let containers: Vec<_> = DockerManager::find_project_containers(&client, project_path, project_name)
+            .map_err(|e| format!("Failed to list containers: {}", e))?
+            .into_iter()
+            .filter(|c| {
+                options.services.is_empty()
+                    || options.services.iter().any(|s| Some(s.as_str()) == c.service() || c.name() == format!("{}_{}", project_name, s))
+            })
+            .collect();
+
+        if containers.is_empty() {
+            return Err("No matching containers found (is the project running?)".to_string());
+        }
+
+        let stream_id = Uuid::new_v4().to_string();
+        let stream = LogStream {
+            project_id: project_id.to_string(),
+            services: containers
+                .iter()
+                .map(|c| c.service().map(|s| s.to_string()).unwrap_or_else(|| c.name()))
+                .collect(),
+            options: options.clone(),
+            stopped: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicUsize::new(containers.len())),
+            connections: Arc::new(Mutex::new(Vec::new())),
+            buffer: Arc::new(Mutex::new(VecDeque::new())),
+        };
+
+        let query = LogsQuery {
+            follow: until.is_none(),
+            timestamps: true,
+            tail: options.tail.or(if since.is_some() { None } else { Some(100) }),
+            since,
+            until,
+        };
+
+        let mut last_error = None;
+        for container in containers {
+            let service = container
+                .service()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| container.name().trim_start_matches(&format!("{}_", project_name)).to_string());
+            let (connection, mut reader) = match client.logs_stream(&container.id, &query) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    stream.running.fetch_sub(1, Ordering::SeqCst);
+                    println!("[Logs] Failed to follow {}: {}", container.name(), e);
+                    last_error = Some(e.to_string());
+                    continue;
+                }
+            };
+            stream.connections.lock().unwrap().push(connection);
+
+            let app = app.clone();
+            let filter = filter.clone();
+            let stopped = stream.stopped.clone();
+            let running = stream.running.clone();
+            let buffer = stream.buffer.clone();
+            let template = LogLine {
+                stream_id: stream_id.clone(),
+                project_id: project_id.to_string(),
+                service,
+                container: container.name(),
+                stream: String::new(),
+                timestamp: None,
+                line: String::new(),
+            };
+
+            std::thread::spawn(move || {
+                let mut splitter = LineSplitter::default();
+                let publish = |kind: crate::engine::StreamKind, raw: String| {
+                    let (timestamp, text) = split_timestamp(&raw);
+                    if !filter.matches(text) {
+                        return;
+                    }
+
+                    let line = LogLine {
+                        stream: kind.as_str().to_string(),
+                        timestamp: timestamp.map(|t| t.to_string()),
+                        line: text.to_string(),
+                        ..template.clone()
+                    };
+
+                    {
+                        let mut buffer = buffer.lock().unwrap();
+                        if buffer.len() >= LOG_BUFFER_LINES {
+                            buffer.pop_front();
+                        }
+                        buffer.push_back(line.clone());
+                    }
+                    let _ = app.emit("container-log", line);
+                };
+
+                while !stopped.load(Ordering::SeqCst) {
+                    match reader.next_frame() {
+                        Ok(Some((kind, data))) => {
+                            for (kind, raw) in splitter.push(kind, &data) {
+                                publish(kind, raw);
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+                for (kind, raw) in splitter.finish() {
+                    publish(kind, raw);
+                }
+
+                running.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+
+        if stream.connections.lock().unwrap().is_empty() {
+            let e = last_error.unwrap_or_default();
+            return Err(format!("Failed to follow logs of any container: {}", e));
+        }
+
+        self.streams.lock().unwrap().insert(stream_id.clone(), stream);
+        Ok(stream_id)
+    }
+
+    pub fn stop(&self, stream_id: &str) -> Result<(), String> {
+        let stream = self
+            .streams
+            .lock()
+            .unwrap()
+            .remove(stream_id)
+            .ok_or_else(|| "Log stream not found".to_string())?;
+        stream.stop();
+        Ok(())
+    }
+
+    pub fn list(&self, project_id: Option<&str>) -> Vec<LogStreamInfo> {
+        self.streams
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, stream)| project_id.map(|p| stream.project_id == p).unwrap_or(true))
+            .map(|(id, stream)| stream.info(id))
+            .collect()
+    }
+
+    /// Returns the buffered lines of a stream, optionally only the last `limit`.
+    pub fn buffer(&self, stream_id: &str, limit: Option<usize>) -> Result<Vec<LogLine>, String> {
+        let streams = self.streams.lock().unwrap();
+        let stream = streams.get(stream_id).ok_or_else(|| "Log stream not found".to_string())?;
+        let buffer = stream.buffer.lock().unwrap();
+        let skip = limit.map(|l| buffer.len().saturating_sub(l)).unwrap_or(0);
+        Ok(buffer.iter().skip(skip).cloned().collect())
+    }
+}
+
+/// Splits the RFC 3339 timestamp Docker prefixes to each line when
+/// `timestamps=1` is requested.
+fn split_timestamp(raw: &str) -> (Option<&str>, &str) {
+    match raw.split_once(' ') {
+        Some((ts, rest)) if DateTime::parse_from_rfc3339(ts).is_ok() => (Some(ts), rest),
+        _ => (None, raw),
+    }
+}
+
+/// Parses a since/until bound into UNIX seconds. Accepts RFC 3339
+/// timestamps, plain UNIX seconds and relative durations like "30s", "10m",
+/// "2h" or "1d" (meaning that long ago).
+fn parse_time_bound(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.timestamp());
+    }
+
+    let (amount, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len()));
+    let amount: i64 = amount.parse().map_err(|_| format!("Invalid time: {}", value))?;
+    let duration = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(format!("Invalid time: {}", value)),
+    };
+
+    Ok((Utc::now() - duration).timestamp())
+}
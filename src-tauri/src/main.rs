@@ -1,15 +1,37 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod archive;
+mod catalog;
 mod commands;
 mod custom_template;
+mod detect;
 mod docker;
+mod engine;
+mod git;
+mod hosts;
+mod jobs;
+mod logs;
+mod metrics;
+mod ports;
+mod preview;
 mod project;
+mod proxy;
+mod regenerate;
+mod registry;
+mod relocate;
+mod schema;
 mod template;
+mod terminal;
+mod tinker;
+mod tls;
 mod state;
+mod watcher;
 
 use state::AppState;
 use std::sync::Mutex;
+use tauri::Manager;
+use watcher::ProjectWatcher;
 
 fn main() {
     tauri::Builder::default()
@@ -19,6 +41,11 @@ fn main() {
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_notification::init())
         .manage(Mutex::new(AppState::new()))
+        .setup(|app| {
+            let jobs = app.state::<Mutex<AppState>>().lock().unwrap().jobs.clone();
+            ProjectWatcher::spawn(app.handle().clone(), jobs);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Project commands
             commands::get_projects,
@@ -29,6 +56,11 @@ fn main() {
             commands::update_project_env,
             commands::open_project_folder,
             commands::open_project_in_editor,
+            // Project registry commands
+            commands::rescan_projects,
+            commands::register_project,
+            commands::unregister_project,
+            commands::get_load_diagnostics,
             // Docker commands
             commands::start_project,
             commands::stop_project,
@@ -38,12 +70,47 @@ fn main() {
             commands::stop_project_streaming,
             commands::rebuild_project_streaming,
             commands::install_laravel_streaming,
+            commands::list_jobs,
+            commands::cancel_job,
             commands::get_project_status,
             commands::get_container_logs,
+            commands::stream_container_logs,
+            commands::stop_container_logs,
+            commands::list_log_streams,
+            commands::get_log_stream_buffer,
             commands::get_services_status,
+            commands::get_project_stats,
+            commands::suggest_ports,
+            commands::check_port_conflicts,
+            commands::start_metrics_sampling,
+            commands::stop_metrics_sampling,
+            commands::get_metrics_sampler,
+            commands::get_latest_metrics,
+            commands::get_metrics_history,
+            // Proxy commands
+            commands::get_proxy_status,
+            commands::enable_proxy,
+            commands::disable_proxy,
+            commands::refresh_proxy,
+            // Hosts file commands
+            commands::preview_hosts_file,
+            commands::apply_hosts_file,
+            commands::remove_hosts_entries,
+            commands::set_hosts_management,
+            // Certificate commands
+            commands::get_certificate_authority,
+            commands::create_certificate_authority,
+            commands::export_certificate_authority,
+            commands::issue_project_certificate,
             // Template commands
             commands::get_templates,
             commands::get_template,
+            commands::get_template_roots,
+            commands::list_template_packs,
+            commands::install_template_pack,
+            commands::remove_template_pack,
+            commands::preview_project_from_template,
+            commands::preview_project_regeneration,
             // Artisan commands
             commands::run_artisan_command,
             commands::run_make_command,
@@ -77,6 +144,17 @@ fn main() {
             // Project cloning commands
             commands::clone_project,
             commands::import_project,
+            // Project rename & move commands
+            commands::rename_project,
+            commands::move_project,
+            // Project config commands
+            commands::update_project_config,
+            // Project archive commands
+            commands::export_project,
+            commands::read_project_archive,
+            commands::import_project_archive,
+            // Git import commands
+            commands::import_project_from_git,
             // Database backup commands
             commands::backup_database,
             commands::restore_database,
@@ -86,16 +164,29 @@ fn main() {
             // Terminal / Exec commands
             commands::exec_container_command,
             commands::run_tinker_command,
+            commands::start_tinker_session,
+            commands::eval_tinker,
+            commands::reset_tinker_session,
+            commands::close_tinker_session,
+            commands::get_tinker_session,
             commands::run_composer_command,
             commands::run_npm_command,
+            commands::open_terminal_session,
+            commands::write_terminal,
+            commands::resize_terminal,
+            commands::close_terminal_session,
+            commands::list_terminal_sessions,
             // Custom template commands
             commands::create_project_from_custom_template,
+            commands::preview_project_from_custom_template,
             // Settings commands
             commands::get_settings,
             commands::save_settings,
             // System commands
             commands::check_docker_installed,
             commands::get_docker_version,
+            commands::get_container_runtime,
+            commands::refresh_container_runtime,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,233 @@
+use crate::docker::DockerManager;
+use crate::project::ProjectManager;
+use crate::state::{ContainerStats, ProjectStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// Samples kept per container for the rolling history
+const HISTORY_SAMPLES: usize = 60;
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const MIN_INTERVAL_SECS: u64 = 2;
+
+/// Resource usage of every running container of a project at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectMetrics {
+    pub project_id: String,
+    pub project_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub cpu_percent: f64,
+    pub memory_usage: u64,
+    pub network_rx: u64,
+    pub network_tx: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+    pub containers: Vec<ContainerStats>,
+}
+
+impl ProjectMetrics {
+    fn aggregate(project_id: &str, project_name: &str, containers: Vec<ContainerStats>) -> Self {
+        Self {
+            project_id: project_id.to_string(),
+            project_name: project_name.to_string(),
+            timestamp: Utc::now(),
+            cpu_percent: containers.iter().map(|c| c.cpu_percent).sum(),
+            memory_usage: containers.iter().map(|c| c.memory_usage).sum(),
+            network_rx: containers.iter().map(|c| c.network_rx).sum(),
+            network_tx: containers.iter().map(|c| c.network_tx).sum(),
+            block_read: containers.iter().map(|c| c.block_read).sum(),
+            block_write: containers.iter().map(|c| c.block_write).sum(),
+            containers,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSamplerInfo {
+    pub running: bool,
+    /// Watched project ids; empty means every running project.
+    pub project_ids: Vec<String>,
+    pub interval_secs: u64,
+}
+
+#[derive(Default)]
+struct SamplerState {
+    // Bumped on every start/stop so an old sampling thread knows to exit
+    generation: u64,
+    running: bool,
+    project_ids: Vec<String>,
+    interval_secs: u64,
+    latest: HashMap<String, ProjectMetrics>,
+    // Project id -> container name -> samples, oldest first
+    history: HashMap<String, HashMap<String, VecDeque<ContainerStats>>>,
+}
+
+/// Periodically samples container stats for projects, emits them as
+/// `project-metrics` events and keeps a short history per container.
+#[derive(Default)]
+pub struct MetricsSampler {
+    state: Arc<Mutex<SamplerState>>,
+}
+
+impl MetricsSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts sampling, or updates the watched projects and interval if
+    /// the sampler is already running.
+    pub fn start(&self, app: &AppHandle, project_ids: Vec<String>, interval_secs: Option<u64>) -> MetricsSamplerInfo {
+        let generation = {
+            let mut state = self.state.lock().unwrap();
+            state.project_ids = project_ids;
+            state.interval_secs = interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS).max(MIN_INTERVAL_SECS);
+
+            if state.running {
+                return Self::info_of(&state);
+            }
+
+            state.running = true;
+            state.generation += 1;
+            state.generation
+        };
+
+        let app = app.clone();
+        let state = self.state.clone();
+        std::thread::spawn(move || Self::sample_loop(app, state, generation));
+
+        self.info()
+    }
+
+    pub fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.running = false;
+        state.generation += 1;
+    }
+
+    pub fn info(&self) -> MetricsSamplerInfo {
+        Self::info_of(&self.state.lock().unwrap())
+    }
+
+    fn info_of(state: &SamplerState) -> MetricsSamplerInfo {
+        MetricsSamplerInfo {
+            running: state.running,
+            project_ids: state.project_ids.clone(),
+            interval_secs: state.interval_secs,
+        }
+    }
+
+    /// Latest aggregate per project, heaviest memory user first.
+    pub fn latest(&self) -> Vec<ProjectMetrics> {
+        let mut metrics: Vec<ProjectMetrics> = self.state.lock().unwrap().latest.values().cloned().collect();
+        metrics.sort_by_key(|m| std::cmp::Reverse(m.memory_usage));
+        metrics
+    }
+
+    /// Rolling samples per container of a project, oldest first.
+    pub fn history(&self, project_id: &str) -> HashMap<String, Vec<ContainerStats>> {
+        self.state
+            .lock()
+            .unwrap()
+            .history
+            .get(project_id)
+            .map(|containers| {
+                containers
+                    .iter()
+                    .map(|(name, samples)| (name.clone(), samples.iter().cloned().collect()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sample_loop(app: AppHandle, state: Arc<Mutex<SamplerState>>, generation: u64) {
+        println!("[Metrics] Sampler started");
+
+        loop {
+            let started = Instant::now();
+            let (project_ids, interval) = {
+                let state = state.lock().unwrap();
+                if state.generation != generation {
+                    break;
+                }
+                (state.project_ids.clone(), Duration::from_secs(state.interval_secs))
+            };
+
+            let projects = match ProjectManager::load_all_projects() {
+                Ok(projects) => projects,
+                Err(e) => {
+                    println!("[Metrics] Failed to load projects: {}", e);
+                    HashMap::new()
+                }
+            };
+
+            let watched: Vec<_> = projects
+                .values()
+                .filter(|project| {
+                    if project_ids.is_empty() {
+                        project.status == ProjectStatus::Running
+                    } else {
+                        project_ids.contains(&project.id)
+                    }
+                })
+                .collect();
+
+            // Forget projects that are no longer sampled so `latest` never shows stale numbers
+            {
+                let mut state = state.lock().unwrap();
+                state.latest.retain(|id, _| watched.iter().any(|p| &p.id == id));
+                state.history.retain(|id, _| watched.iter().any(|p| &p.id == id));
+            }
+
+            for project in watched {
+                let containers = match DockerManager::get_project_stats(&project.path, &project.name) {
+                    Ok(containers) => containers,
+                    Err(e) => {
+                        println!("[Metrics] Failed to sample {}: {}", project.name, e);
+                        continue;
+                    }
+                };
+                let metrics = ProjectMetrics::aggregate(&project.id, &project.name, containers);
+
+                {
+                    let mut state = state.lock().unwrap();
+                    if state.generation != generation {
+                        return;
+                    }
+                    Self::record(&mut state, &metrics);
+                }
+                let _ = app.emit("project-metrics", &metrics);
+            }
+
+            // Sleep in short steps so a stop takes effect quickly
+            while started.elapsed() < interval {
+                std::thread::sleep(Duration::from_millis(250));
+                if state.lock().unwrap().generation != generation {
+                    println!("[Metrics] Sampler stopped");
+                    return;
+                }
+            }
+        }
+
+        println!("[Metrics] Sampler stopped");
+    }
+
+    fn record(state: &mut SamplerState, metrics: &ProjectMetrics) {
+        let history = state.history.entry(metrics.project_id.clone()).or_default();
+
+        // Drop history of containers that are gone
+        history.retain(|name, _| metrics.containers.iter().any(|c| &c.name == name));
+
+        for sample in &metrics.containers {
+            let samples = history.entry(sample.name.clone()).or_default();
+            if samples.len() >= HISTORY_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(sample.clone());
+        }
+
+        state.latest.insert(metrics.project_id.clone(), metrics.clone());
+    }
+}
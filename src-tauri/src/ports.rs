@@ -0,0 +1,270 @@
+use crate::docker::DockerManager;
+use crate::engine::EngineClient;
+use crate::project::ProjectManager;
+use crate::state::{PortConfig, Project, ProjectConfig, ProjectStatus, ServiceConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::TcpListener;
+
+// How far above the requested port to look before giving up
+const MAX_PORT_SEARCH: u16 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortConflict {
+    pub service: String,
+    pub port: u16,
+    /// Name of the other project claiming the port, if it is one of ours.
+    pub project: Option<String>,
+}
+
+impl PortConflict {
+    pub fn describe(&self) -> String {
+        match &self.project {
+            Some(project) => format!("port {} ({}) is used by project '{}'", self.port, self.service, project),
+            None => format!("port {} ({}) is already in use by another process", self.port, self.service),
+        }
+    }
+}
+
+/// Hands out host ports that neither another project nor another process
+/// is using, and checks a project's ports before it starts.
+pub struct PortAllocator;
+
+impl PortAllocator {
+    /// The host ports a project publishes, by service, given which services are enabled.
+    pub fn entries(ports: &PortConfig, services: &ServiceConfig) -> Vec<(&'static str, u16)> {
+        let mut entries = vec![("app", ports.app), ("vite", ports.vite)];
+        if services.nginx {
+            entries.push(("https", ports.https));
+        }
+        if services.mysql {
+            entries.push(("db", ports.db));
+        }
+        if services.redis {
+            entries.push(("redis", ports.redis));
+        }
+        if services.phpmyadmin {
+            entries.push(("phpmyadmin", ports.phpmyadmin));
+        }
+        if services.mailhog {
+            entries.push(("mailhog", ports.mailhog));
+        }
+        entries
+    }
+
+    /// Ports claimed in every other project's `.godmode.json`.
+    fn claimed_ports(exclude_project_id: Option<&str>) -> Vec<(u16, Project)> {
+        let projects = ProjectManager::load_all_projects().unwrap_or_default();
+        projects
+            .into_values()
+            .filter(|project| Some(project.id.as_str()) != exclude_project_id)
+            .flat_map(|project| {
+                Self::entries(&project.config.ports, &project.config.services)
+                    .into_iter()
+                    .map(|(_, port)| (port, project.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn is_host_port_free(port: u16) -> bool {
+        TcpListener::bind(("0.0.0.0", port)).is_ok() && TcpListener::bind(("127.0.0.1", port)).is_ok()
+    }
+
+    /// Returns a copy of `requested` where every port that collides with
+    /// another project, a port in use on the host or another port of the same
+    /// project is moved up to the next free one.
+    pub fn allocate(requested: &PortConfig, services: &ServiceConfig, exclude_project_id: Option<&str>) -> Result<PortConfig, String> {
+        let claimed: HashSet<u16> = Self::claimed_ports(exclude_project_id).into_iter().map(|(port, _)| port).collect();
+        Self::assign(requested, services, &claimed, &HashSet::new())
+    }
+
+    /// Like `allocate`, for new ports of an existing project, which may be
+    /// running. Ports it already uses are only moved when another project
+    /// claims them, and ports its own containers publish count as free.
+    pub fn reallocate(project: &Project, requested: &PortConfig, services: &ServiceConfig) -> Result<PortConfig, String> {
+        let claimed: HashSet<u16> = Self::claimed_ports(Some(&project.id)).into_iter().map(|(port, _)| port).collect();
+        Self::reallocate_with(&project.config, requested, services, &claimed, &Self::own_published_ports(project))
+    }
+
+    fn reallocate_with(
+        current: &ProjectConfig,
+        requested: &PortConfig,
+        services: &ServiceConfig,
+        claimed: &HashSet<u16>,
+        own_ports: &HashSet<u16>,
+    ) -> Result<PortConfig, String> {
+        let current: HashSet<u16> = Self::entries(&current.ports, &current.services).into_iter().map(|(_, port)| port).collect();
+        let in_use_by_project: HashSet<u16> = current.union(own_ports).copied().collect();
+        Self::assign(requested, services, claimed, &in_use_by_project)
+    }
+
+    /// Moves ports of `requested` that are `claimed`, used twice or busy on
+    /// the host, except for host ports in `in_use_by_project`.
+    fn assign(
+        requested: &PortConfig,
+        services: &ServiceConfig,
+        claimed: &HashSet<u16>,
+        in_use_by_project: &HashSet<u16>,
+    ) -> Result<PortConfig, String> {
+        let mut taken: HashSet<u16> = HashSet::new();
+        let mut allocated = requested.clone();
+
+        let fields: [(&str, &mut u16); 7] = [
+            ("app", &mut allocated.app),
+            ("https", &mut allocated.https),
+            ("vite", &mut allocated.vite),
+            ("db", &mut allocated.db),
+            ("redis", &mut allocated.redis),
+            ("phpmyadmin", &mut allocated.phpmyadmin),
+            ("mailhog", &mut allocated.mailhog),
+        ];
+        let enabled: Vec<&str> = Self::entries(requested, services).into_iter().map(|(name, _)| name).collect();
+
+        for (name, port) in fields {
+            if !enabled.contains(&name) {
+                continue;
+            }
+
+            let start = *port;
+            let mut candidate = start;
+            while taken.contains(&candidate)
+                || claimed.contains(&candidate)
+                || (!in_use_by_project.contains(&candidate) && !Self::is_host_port_free(candidate))
+            {
+                candidate = candidate
+                    .checked_add(1)
+                    .filter(|c| c - start <= MAX_PORT_SEARCH)
+                    .ok_or_else(|| format!("No free port found for {} near {}", name, start))?;
+            }
+
+            if candidate != start {
+                println!("[Ports] {} port {} is taken, using {}", name, start, candidate);
+            }
+            taken.insert(candidate);
+            *port = candidate;
+        }
+
+        Ok(allocated)
+    }
+
+    /// Ports of `project` that would fail to bind: claimed by another
+    /// project that is up, or in use on the host by something other than
+    /// this project's own containers.
+    pub fn find_conflicts(project: &Project) -> Vec<PortConflict> {
+        let claimed = Self::claimed_ports(Some(&project.id));
+        // Ports published by this project's containers are expected to be busy
+        let own_ports = Self::own_published_ports(project);
+
+        let mut conflicts = Vec::new();
+        for (service, port) in Self::entries(&project.config.ports, &project.config.services) {
+            let active_owner = claimed.iter().find(|(claimed_port, other)| {
+                *claimed_port == port
+                    && matches!(other.status, ProjectStatus::Running | ProjectStatus::Starting | ProjectStatus::Building | ProjectStatus::Error)
+            });
+
+            if let Some((_, other)) = active_owner {
+                conflicts.push(PortConflict {
+                    service: service.to_string(),
+                    port,
+                    project: Some(other.name.clone()),
+                });
+            } else if !own_ports.contains(&port) && !Self::is_host_port_free(port) {
+                // Name a stopped project that claims it too, if any, as the likely culprit
+                conflicts.push(PortConflict {
+                    service: service.to_string(),
+                    port,
+                    project: claimed.iter().find(|(p, _)| *p == port).map(|(_, other)| other.name.clone()),
+                });
+            }
+        }
+
+        conflicts
+    }
+
+    /// Host ports published by the project's running containers.
+    fn own_published_ports(project: &Project) -> HashSet<u16> {
+        EngineClient::from_env()
+            .ok()
+            .and_then(|client| DockerManager::find_project_containers(&client, &project.path, &project.name).ok())
+            .map(|containers| {
+                containers
+                    .iter()
+                    .filter(|c| c.state == "running")
+                    .flat_map(|c| c.ports.iter().filter_map(|p| p.public_port))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Fails with a message naming every conflicting port and project.
+    pub fn ensure_available(project: &Project) -> Result<(), String> {
+        let conflicts = Self::find_conflicts(project);
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let details: Vec<String> = conflicts.iter().map(|c| c.describe()).collect();
+        Err(format!("Cannot start '{}': {}", project.name, details.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project whose app, vite, db and redis ports are bound, as by its running containers.
+    fn running_project() -> (ProjectConfig, Vec<TcpListener>) {
+        let listeners: Vec<TcpListener> = (0..4).map(|_| TcpListener::bind(("127.0.0.1", 0)).unwrap()).collect();
+        let port = |i: usize| listeners[i].local_addr().unwrap().port();
+        let config = ProjectConfig {
+            services: ServiceConfig {
+                mysql: true,
+                redis: true,
+                phpmyadmin: false,
+                mailhog: false,
+                nginx: false,
+            },
+            ports: PortConfig {
+                app: port(0),
+                vite: port(1),
+                db: port(2),
+                redis: port(3),
+                ..PortConfig::default()
+            },
+            ..ProjectConfig::default()
+        };
+        (config, listeners)
+    }
+
+    fn published(listeners: &[TcpListener]) -> HashSet<u16> {
+        listeners.iter().map(|l| l.local_addr().unwrap().port()).collect()
+    }
+
+    #[test]
+    fn running_project_keeps_its_ports_when_other_settings_change() {
+        let (current, listeners) = running_project();
+        let updated = ProjectConfig {
+            install_bun: !current.install_bun,
+            ..current.clone()
+        };
+
+        let ports = PortAllocator::reallocate_with(&current, &updated.ports, &updated.services, &HashSet::new(), &published(&listeners)).unwrap();
+
+        assert_eq!(
+            PortAllocator::entries(&ports, &updated.services),
+            PortAllocator::entries(&current.ports, &current.services)
+        );
+    }
+
+    #[test]
+    fn running_project_moves_only_ports_claimed_by_another_project() {
+        let (current, listeners) = running_project();
+        let claimed: HashSet<u16> = [current.ports.db].into();
+
+        let ports = PortAllocator::reallocate_with(&current, &current.ports, &current.services, &claimed, &published(&listeners)).unwrap();
+
+        assert_ne!(ports.db, current.ports.db);
+        assert_eq!((ports.app, ports.vite, ports.redis), (current.ports.app, current.ports.vite, current.ports.redis));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::template::RenderedFile;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Unchanged lines shown around each change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChange {
+    /// The project has no such file yet.
+    Added,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewFile {
+    /// Relative to the project.
+    pub path: String,
+    pub content: String,
+    /// How the file compares to the project's, when previewing against one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<FileChange>,
+    /// Unified diff from the project's file, for modified files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// The files a template would produce, without writing anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePreview {
+    pub project_path: String,
+    /// Every folder the files live in, parents first.
+    pub directories: Vec<String>,
+    pub files: Vec<PreviewFile>,
+}
+
+/// Turns rendered template files into a preview, optionally compared to
+/// the files of an existing project.
+pub struct TemplatePreviewer;
+
+impl TemplatePreviewer {
+    /// A preview of `files` for a project at `project_path`. With
+    /// `compare_with`, each file is compared to the one at the same path
+    /// there and modified files carry a diff.
+    pub fn preview(project_path: &Path, files: Vec<RenderedFile>, compare_with: Option<&Path>) -> TemplatePreview {
+        // Every project gets these, even when no file lands in them
+        let mut directories: BTreeSet<String> = ["src".to_string(), "docker".to_string()].into();
+        for file in &files {
+            let mut parent = Path::new(&file.path).parent();
+            while let Some(dir) = parent.filter(|d| !d.as_os_str().is_empty()) {
+                directories.insert(dir.to_string_lossy().replace('\\', "/"));
+                parent = dir.parent();
+            }
+        }
+
+        let mut files: Vec<PreviewFile> = files
+            .into_iter()
+            .map(|file| {
+                let (change, diff) = match compare_with {
+                    Some(existing) => Self::compare(&existing.join(&file.path), &file),
+                    None => (None, None),
+                };
+                PreviewFile {
+                    path: file.path,
+                    content: file.content,
+                    change,
+                    diff,
+                }
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        TemplatePreview {
+            project_path: project_path.to_string_lossy().to_string(),
+            directories: directories.into_iter().collect(),
+            files,
+        }
+    }
+
+    fn compare(existing: &Path, file: &RenderedFile) -> (Option<FileChange>, Option<String>) {
+        match fs::read_to_string(existing) {
+            Err(_) => (Some(FileChange::Added), None),
+            Ok(current) if current == file.content => (Some(FileChange::Unchanged), None),
+            Ok(current) => (Some(FileChange::Modified), Some(Self::unified_diff(&file.path, &current, &file.content))),
+        }
+    }
+
+    /// A unified diff (`diff -u` style) turning `old` into `new`.
+    pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+
+        // (marker, index into old, index into new) per line of the diff
+        let mut ops = Vec::new();
+        let mut j = 0;
+        for (i, matched) in match_lines(&old, &new).into_iter().enumerate() {
+            match matched {
+                Some(target) => {
+                    ops.extend((j..target).map(|j| ('+', i, j)));
+                    ops.push((' ', i, target));
+                    j = target + 1;
+                }
+                None => ops.push(('-', i, j)),
+            }
+        }
+        ops.extend((j..new.len()).map(|j| ('+', old.len(), j)));
+
+        let changes: Vec<usize> = ops.iter().enumerate().filter(|(_, op)| op.0 != ' ').map(|(index, _)| index).collect();
+        let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+        let mut k = 0;
+        while k < changes.len() {
+            // Changes closer than twice the context share a hunk
+            let mut last = k;
+            while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * DIFF_CONTEXT {
+                last += 1;
+            }
+            let start = changes[k].saturating_sub(DIFF_CONTEXT);
+            let end = (changes[last] + DIFF_CONTEXT + 1).min(ops.len());
+            let hunk = &ops[start..end];
+
+            let old_count = hunk.iter().filter(|op| op.0 != '+').count();
+            let new_count = hunk.iter().filter(|op| op.0 != '-').count();
+            let old_start = hunk[0].1 + usize::from(old_count > 0);
+            let new_start = hunk[0].2 + usize::from(new_count > 0);
+            out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+            for &(marker, i, j) in hunk {
+                let line = if marker == '+' { new[j] } else { old[i] };
+                out.push(marker);
+                out.push_str(line);
+                out.push('\n');
+            }
+            k = last + 1;
+        }
+        out
+    }
+}
+
+/// For each line of `old`, the line of `new` it is kept as, if any, from
+/// the longest common subsequence of the two.
+pub fn match_lines(old: &[&str], new: &[&str]) -> Vec<Option<usize>> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut matches = vec![None; old.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
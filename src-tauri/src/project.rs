@@ -1,31 +1,99 @@
-use crate::state::{Project, ProjectConfig, ProjectStatus, Settings};
+use crate::detect::{DetectionReport, FrameworkDetector};
+use crate::docker::DockerManager;
+use crate::ports::PortAllocator;
+use crate::proxy::ProxyManager;
+use crate::regenerate::ProjectRegenerator;
+use crate::registry::{ProjectRegistry, PROJECT_FILE};
+use crate::schema::{read_settings_file, PROJECT_SCHEMA_VERSION, SETTINGS_SCHEMA_VERSION};
+use crate::state::{PortConfig, Project, ProjectStatus, Settings};
+use crate::template::create_project_from_template;
+use crate::tls::{CertificateManager, PROJECT_CERTS_DIR};
+use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use chrono::Utc;
 
+/// Generated files that embed the project name, regenerated when a project is cloned or renamed.
+const NAME_DERIVED_FILES: &[&str] = &["docker-compose.yml", "Makefile", "docker/init.sql"];
+
 pub struct ProjectManager;
 
 impl ProjectManager {
+    /// Directory for the app's own files (settings, proxy config, ...).
+    pub fn app_data_dir() -> PathBuf {
+        dirs::home_dir().unwrap_or_default().join(".laravel-godmode")
+    }
+
     fn get_settings_path() -> String {
-        let home = dirs::home_dir().unwrap_or_default();
-        home.join(".laravel-godmode")
+        Self::app_data_dir()
             .join("settings.json")
             .to_string_lossy()
             .to_string()
     }
 
+    /// The saved settings, or the defaults when there are none or they
+    /// cannot be loaded. A file that cannot be loaded shows up in the load
+    /// diagnostics and stays as it is until the user saves their settings.
+    pub fn load_settings() -> Settings {
+        let settings_path = PathBuf::from(Self::get_settings_path());
+        if !settings_path.exists() {
+            return Settings::default();
+        }
+
+        read_settings_file(&settings_path).unwrap_or_default()
+    }
+
+    /// Saves settings the app changed itself. Fails when the file on disk
+    /// cannot be loaded, since `settings` are then based on the defaults.
+    pub fn save_settings(settings: &Settings) -> Result<(), String> {
+        let settings_path = PathBuf::from(Self::get_settings_path());
+        if settings_path.exists() && read_settings_file(&settings_path).is_err() {
+            return Err(format!(
+                "{} could not be loaded and was left as is; fix it or save your settings to replace it",
+                settings_path.display()
+            ));
+        }
+        Self::write_settings(settings)
+    }
+
+    /// Saves settings the user edited, replacing a file that cannot be
+    /// loaded. That one is kept as `settings.json.invalid.bak`.
+    pub fn replace_settings(settings: &Settings) -> Result<(), String> {
+        let settings_path = PathBuf::from(Self::get_settings_path());
+        if settings_path.exists() && read_settings_file(&settings_path).is_err() {
+            fs::copy(&settings_path, settings_path.with_extension("json.invalid.bak"))
+                .map_err(|e| format!("Failed to back up unreadable settings: {}", e))?;
+        }
+        Self::write_settings(settings)
+    }
+
+    fn write_settings(settings: &Settings) -> Result<(), String> {
+        let settings_path = Self::get_settings_path();
+        if let Some(parent) = Path::new(&settings_path).parent() {
+            fs::create_dir_all(parent).ok();
+        }
+
+        // Settings coming back from the UI may not carry the version
+        let settings = Settings {
+            schema_version: SETTINGS_SCHEMA_VERSION,
+            ..settings.clone()
+        };
+        let content = serde_json::to_string_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(&settings_path, content)
+            .map_err(|e| format!("Failed to save settings: {}", e))
+    }
+
     pub fn get_projects_dir() -> String {
         // Try to read from settings first
         let settings_path = Self::get_settings_path();
         if Path::new(&settings_path).exists() {
-            if let Ok(content) = fs::read_to_string(&settings_path) {
-                if let Ok(settings) = serde_json::from_str::<Settings>(&content) {
-                    if !settings.projects_path.is_empty() {
-                        println!("[ProjectManager] Using projects path from settings: {}", settings.projects_path);
-                        return settings.projects_path;
-                    }
+            if let Ok(settings) = read_settings_file(Path::new(&settings_path)) {
+                if !settings.projects_path.is_empty() {
+                    println!("[ProjectManager] Using projects path from settings: {}", settings.projects_path);
+                    return settings.projects_path;
                 }
             }
         }
@@ -49,41 +117,21 @@ impl ProjectManager {
     }
 
     pub fn load_all_projects() -> Result<HashMap<String, Project>, String> {
-        let projects_dir = Self::ensure_projects_dir()?;
-        let mut projects = HashMap::new();
-
-        let entries = fs::read_dir(&projects_dir)
-            .map_err(|e| format!("Failed to read projects directory: {}", e))?;
-
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let config_path = entry.path().join(".godmode.json");
-                if config_path.exists() {
-                    if let Ok(content) = fs::read_to_string(&config_path) {
-                        if let Ok(project) = serde_json::from_str::<Project>(&content) {
-                            projects.insert(project.id.clone(), project);
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(projects)
+        ProjectRegistry::global().all()
     }
 
     pub fn get_project(project_id: &str) -> Result<Project, String> {
-        let projects = Self::load_all_projects()?;
-        projects.get(project_id)
-            .cloned()
-            .ok_or_else(|| "Project not found".to_string())
+        ProjectRegistry::global().get(project_id)
     }
 
     pub fn save_project(project: &Project) -> Result<(), String> {
-        let config_path = Path::new(&project.path).join(".godmode.json");
+        let config_path = Path::new(&project.path).join(PROJECT_FILE);
         let content = serde_json::to_string_pretty(project)
             .map_err(|e| format!("Failed to serialize project: {}", e))?;
         fs::write(&config_path, content)
-            .map_err(|e| format!("Failed to save project: {}", e))
+            .map_err(|e| format!("Failed to save project: {}", e))?;
+        ProjectRegistry::global().put(project);
+        Ok(())
     }
 
     pub fn delete_project(project_id: &str, delete_files: bool) -> Result<(), String> {
@@ -91,10 +139,9 @@ impl ProjectManager {
 
         if delete_files {
             // Stop containers first
-            let _ = std::process::Command::new("docker-compose")
-                .args(["down", "-v"])
-                .current_dir(&project.path)
-                .output();
+            if let Ok(mut compose) = crate::docker::DockerManager::compose_command(&project.path) {
+                let _ = compose.args(["down", "-v"]).output();
+            }
 
             // Delete project directory
             fs::remove_dir_all(&project.path)
@@ -106,14 +153,34 @@ impl ProjectManager {
                 .map_err(|e| format!("Failed to remove project config: {}", e))?;
         }
 
+        ProjectRegistry::global().remove(project_id);
         Ok(())
     }
 
     pub fn update_project_status(project_id: &str, status: ProjectStatus) -> Result<(), String> {
         let mut project = Self::get_project(project_id)?;
+        let routes_changed = matches!(status, ProjectStatus::Running | ProjectStatus::Stopped);
         project.status = status;
+        project.status_message = None;
         project.updated_at = Utc::now();
-        Self::save_project(&project)
+        Self::save_project(&project)?;
+
+        if routes_changed {
+            ProxyManager::refresh_in_background();
+        }
+        Ok(())
+    }
+
+    /// Puts the project in the `Error` status with a reason the UI can show.
+    pub fn mark_project_error(project_id: &str, message: &str) -> Result<(), String> {
+        let mut project = Self::get_project(project_id)?;
+        project.status = ProjectStatus::Error;
+        project.status_message = Some(message.to_string());
+        project.updated_at = Utc::now();
+        Self::save_project(&project)?;
+
+        ProxyManager::refresh_in_background();
+        Ok(())
     }
 
     pub fn update_env_file(project_path: &str, env_content: &str) -> Result<(), String> {
@@ -128,8 +195,16 @@ impl ProjectManager {
             .map_err(|e| format!("Failed to read .env file: {}", e))
     }
 
-    pub fn clone_project(source_project_id: &str, new_name: &str) -> Result<Project, String> {
+    /// Copies a project under a new name. The compose file is regenerated
+    /// so containers, networks and volumes get names of their own, ports are
+    /// reallocated and `.env` follows. With `copy_volumes` the source's named
+    /// volumes are copied too and the clone keeps using the source's database
+    /// name, which is the one inside the copied data.
+    pub fn clone_project(source_project_id: &str, new_name: &str, templates_path: &str, copy_volumes: bool) -> Result<Project, String> {
         let source_project = Self::get_project(source_project_id)?;
+        if copy_volumes && source_project.status == ProjectStatus::Running {
+            return Err(format!("Stop '{}' before cloning it with its data", source_project.name));
+        }
         let projects_dir = Self::ensure_projects_dir()?;
 
         // Create new project directory
@@ -137,27 +212,58 @@ impl ProjectManager {
         if new_project_path.exists() {
             return Err(format!("Project '{}' already exists", new_name));
         }
+        // Compose would take over volumes left under this name, stale data and all
+        let new_path_str = new_project_path.to_string_lossy().to_string();
+        if copy_volumes && !DockerManager::project_volumes(&new_path_str)?.is_empty() {
+            return Err(format!("Docker volumes for '{}' already exist", DockerManager::compose_project_name(&new_path_str)));
+        }
 
         // Copy all files from source project
         Self::copy_dir_recursive(&source_project.path, new_project_path.to_str().unwrap())?;
+        // The source's certificate names the source's domain
+        let _ = fs::remove_dir_all(new_project_path.join(PROJECT_CERTS_DIR));
+
+        // The source still claims its ports, so the clone gets its own
+        let mut config = source_project.config.clone();
+        config.ports = PortAllocator::allocate(&config.ports, &config.services, None)?;
+        // Copied data is in the source's database, a fresh one is named after the clone
+        config.database_name = Some(source_project.database_name().to_string()).filter(|db| copy_volumes && db != new_name);
 
         // Create new project with new ID
         let new_project = Project {
+            schema_version: PROJECT_SCHEMA_VERSION,
             id: Uuid::new_v4().to_string(),
             name: new_name.to_string(),
             path: new_project_path.to_string_lossy().to_string(),
             template: source_project.template.clone(),
-            config: source_project.config.clone(),
+            config,
             status: ProjectStatus::Stopped,
+            status_message: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
-        // Update docker-compose and other files with new project name
-        Self::update_cloned_project_files(&new_project)?;
-
-        // Save the new project config
-        Self::save_project(&new_project)?;
+        let result = Self::retarget_project_files(&new_project, &source_project, templates_path)
+            .and_then(|_| CertificateManager::ensure_project_certificate(&new_project))
+            .and_then(|_| {
+                if copy_volumes {
+                    DockerManager::copy_volumes(&source_project.path, &new_project.path).map(|_| ())
+                } else {
+                    Ok(())
+                }
+            })
+            .and_then(|_| Self::save_project(&new_project));
+        if let Err(e) = result {
+            if copy_volumes {
+                // Checked to be free before copying, so whatever is there now came from this clone
+                let copied = DockerManager::project_volumes(&new_project.path).unwrap_or_default();
+                if let Err(e) = DockerManager::remove_volumes(&copied) {
+                    println!("[ProjectManager] Failed to remove volumes of the failed clone: {}", e);
+                }
+            }
+            let _ = fs::remove_dir_all(&new_project_path);
+            return Err(e);
+        }
 
         Ok(new_project)
     }
@@ -191,54 +297,193 @@ impl ProjectManager {
         Ok(())
     }
 
-    fn update_cloned_project_files(project: &Project) -> Result<(), String> {
-        // Update docker-compose.yml with new container names
+    /// Points the files of a project copied or renamed from `source` at its
+    /// own name, ports and database.
+    pub fn retarget_project_files(project: &Project, source: &Project, templates_path: &str) -> Result<(), String> {
+        let project_path = Path::new(&project.path);
+
+        let regenerated = ProjectRegenerator::retarget(project, source, templates_path, NAME_DERIVED_FILES)?;
+        if regenerated.is_empty() {
+            // Custom templates have no stubs to render from, so rename in place
+            Self::rename_references(project_path, &source.name, &project.name, &["docker-compose.yml", "Makefile"])?;
+            Self::rewrite_ports(project, &source.config.ports)?;
+            if project.database_name() != project.name {
+                Self::restore_compose_database(project)?;
+            }
+        } else {
+            Self::rewrite_env_ports(project, &source.config.ports)?;
+        }
+
+        let (old_domain, new_domain) = (ProxyManager::project_domain(&source.name), ProxyManager::project_domain(&project.name));
+        for file in ["docker/nginx.conf", "docker/Caddyfile"] {
+            let path = project_path.join(file);
+            if let Ok(content) = fs::read_to_string(&path) {
+                fs::write(&path, content.replace(&old_domain, &new_domain))
+                    .map_err(|e| format!("Failed to update {}: {}", file, e))?;
+            }
+        }
+
+        let (old_database, new_database) = (source.database_name(), project.database_name());
+        let values = [
+            ("APP_NAME", source.name.as_str(), project.name.as_str()),
+            ("DB_DATABASE", old_database, new_database),
+            ("DB_NAME", old_database, new_database),
+        ];
+        for file in [".env", "src/.env"] {
+            let env_path = project_path.join(file);
+            if !env_path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(&env_path)
+                .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+
+            let updated: Vec<String> = content
+                .lines()
+                .map(|line| {
+                    for (key, old, new) in &values {
+                        if line == format!("{}={}", key, old) {
+                            return format!("{}={}", key, new);
+                        }
+                    }
+                    // Connection URLs end in the database name, e.g. postgresql://...@db:5432/shop
+                    match line.strip_prefix("DATABASE_URL=").and_then(|url| url.strip_suffix(&format!("/{}", old_database))) {
+                        Some(base) => format!("DATABASE_URL={}/{}", base, new_database),
+                        None => line.to_string(),
+                    }
+                })
+                .collect();
+
+            let mut updated = updated.join("\n");
+            if content.ends_with('\n') {
+                updated.push('\n');
+            }
+            fs::write(&env_path, updated)
+                .map_err(|e| format!("Failed to update {}: {}", file, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Puts the database name back in a compose file whose references to the
+    /// project's name were all replaced, the database's included.
+    fn restore_compose_database(project: &Project) -> Result<(), String> {
+        let compose_path = Path::new(&project.path).join("docker-compose.yml");
+        let content = fs::read_to_string(&compose_path).map_err(|e| format!("Failed to read docker-compose.yml: {}", e))?;
+
+        let renamed = format!("\"{}\"", project.name);
+        let updated: Vec<String> = content
+            .lines()
+            .map(|line| {
+                let is_database = ["MYSQL_DATABASE:", "MARIADB_DATABASE:", "POSTGRES_DB:"]
+                    .iter()
+                    .any(|key| line.trim() == format!("{} {}", key, renamed));
+                if is_database {
+                    line.replace(&renamed, &format!("\"{}\"", project.database_name()))
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+
+        let mut updated = updated.join("\n");
+        if content.ends_with('\n') {
+            updated.push('\n');
+        }
+        fs::write(&compose_path, updated).map_err(|e| format!("Failed to update docker-compose.yml: {}", e))
+    }
+
+    /// Replaces whole-word occurrences of a project's old name in `files`,
+    /// which derive container, network and database names from it.
+    pub fn rename_references(project_path: &Path, old_name: &str, new_name: &str, files: &[&str]) -> Result<(), String> {
+        let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(old_name)))
+            .map_err(|e| format!("Failed to rename project references: {}", e))?;
+
+        for file in files {
+            let path = project_path.join(file);
+            if !path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+            let updated = pattern.replace_all(&content, new_name);
+            fs::write(&path, updated.as_ref()).map_err(|e| format!("Failed to update {}: {}", file, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces published host ports in the compose file and the app URL in
+    /// `.env` after a project's ports were reassigned.
+    pub fn rewrite_ports(project: &Project, old_ports: &PortConfig) -> Result<(), String> {
+        let new_ports = &project.config.ports;
+        let changed: Vec<(u16, u16)> = [
+            (old_ports.app, new_ports.app),
+            (old_ports.vite, new_ports.vite),
+            (old_ports.db, new_ports.db),
+            (old_ports.redis, new_ports.redis),
+            (old_ports.phpmyadmin, new_ports.phpmyadmin),
+            (old_ports.mailhog, new_ports.mailhog),
+            (old_ports.https, new_ports.https),
+        ]
+        .into_iter()
+        .filter(|(old, new)| old != new)
+        .collect();
+
+        if changed.is_empty() {
+            return Ok(());
+        }
+
         let compose_path = Path::new(&project.path).join("docker-compose.yml");
         if compose_path.exists() {
             let content = fs::read_to_string(&compose_path)
                 .map_err(|e| format!("Failed to read docker-compose.yml: {}", e))?;
 
-            // Replace container_name references with new project name
-            // This is a simple approach - for more complex setups, might need YAML parsing
-            let updated = content.replace(
-                &format!("container_name: "),
-                &format!("# Original container names replaced\n      container_name: {}_", project.name)
-            );
+            // Only touch the host side of "HOST:CONTAINER" mappings
+            let mapping = Regex::new(r#"(-\s*["']?)(\d+)(:\d+)"#).unwrap();
+            let updated = mapping.replace_all(&content, |caps: &regex::Captures| {
+                let port: u16 = caps[2].parse().unwrap_or(0);
+                let port = changed.iter().find(|(old, _)| *old == port).map(|(_, new)| *new).unwrap_or(port);
+                format!("{}{}{}", &caps[1], port, &caps[3])
+            });
 
-            fs::write(&compose_path, updated)
+            fs::write(&compose_path, updated.as_ref())
                 .map_err(|e| format!("Failed to update docker-compose.yml: {}", e))?;
         }
 
-        // Update .env file
-        let env_path = Path::new(&project.path).join(".env");
-        if env_path.exists() {
-            let content = fs::read_to_string(&env_path)
-                .map_err(|e| format!("Failed to read .env: {}", e))?;
+        Self::rewrite_env_ports(project, old_ports)
+    }
 
-            // Update APP_NAME
-            let mut updated = content.clone();
-            for line in content.lines() {
-                if line.starts_with("APP_NAME=") {
-                    updated = updated.replace(line, &format!("APP_NAME={}", project.name));
-                }
-            }
+    /// Points `localhost:<app port>` URLs in `.env` and `src/.env` at the new app port.
+    fn rewrite_env_ports(project: &Project, old_ports: &PortConfig) -> Result<(), String> {
+        let new_ports = &project.config.ports;
+        if old_ports.app == new_ports.app {
+            return Ok(());
+        }
 
-            fs::write(&env_path, updated)
-                .map_err(|e| format!("Failed to update .env: {}", e))?;
+        for file in [".env", "src/.env"] {
+            let env_path = Path::new(&project.path).join(file);
+            if !env_path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(&env_path)
+                .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+            let content = content.replace(&format!("localhost:{}", old_ports.app), &format!("localhost:{}", new_ports.app));
+            fs::write(&env_path, content)
+                .map_err(|e| format!("Failed to update {}: {}", file, e))?;
         }
 
         Ok(())
     }
 
-    pub fn import_project(source_path: &str, name: &str) -> Result<Project, String> {
+    /// Copies an existing codebase into a new project's `src` directory and
+    /// scaffolds the template detected from it around the code.
+    pub fn import_project(source_path: &str, name: &str, templates_path: &str) -> Result<(Project, DetectionReport), String> {
         let source = Path::new(source_path);
-
-        // Verify it's a Laravel project
-        let artisan_path = source.join("artisan");
-        let composer_path = source.join("composer.json");
-
-        if !artisan_path.exists() && !composer_path.exists() {
-            return Err("This doesn't appear to be a Laravel project (no artisan or composer.json found)".to_string());
+        if !source.is_dir() {
+            return Err(format!("Source folder not found: {}", source_path));
+        }
+        // Fail before copying anything when the code is not supported
+        if FrameworkDetector::detect_template(source).is_none() {
+            return Err("No supported project found (expected artisan, composer.json or package.json)".to_string());
         }
 
         let projects_dir = Self::ensure_projects_dir()?;
@@ -248,48 +493,34 @@ impl ProjectManager {
             return Err(format!("Project '{}' already exists", name));
         }
 
-        // Copy the project
-        Self::copy_dir_recursive(source_path, new_project_path.to_str().unwrap())?;
-
-        // Create default project config
-        let config = ProjectConfig {
-            php_version: Some("8.3".to_string()),
-            node_version: "20".to_string(),
-            install_bun: false,
-            install_pnpm: false,
-            install_yarn: false,
-            install_laravel: false,
-            ports: crate::state::PortConfig {
-                app: 8080,
-                vite: 5173,
-                db: 3306,
-                phpmyadmin: 8081,
-                mailhog: 8025,
-                redis: 6379,
-            },
-            services: crate::state::ServiceConfig {
-                mysql: true,
-                redis: true,
-                mailhog: true,
-                phpmyadmin: true,
-                nginx: true,
-            },
-        };
+        let result = Self::copy_dir_recursive(source_path, new_project_path.join("src").to_str().unwrap())
+            .and_then(|_| Self::scaffold_imported(name, templates_path));
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&new_project_path);
+        }
+        result
+    }
 
-        let project = Project {
-            id: Uuid::new_v4().to_string(),
-            name: name.to_string(),
-            path: new_project_path.to_string_lossy().to_string(),
-            template: "laravel".to_string(),
-            config,
-            status: ProjectStatus::Stopped,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        };
+    /// Generates the docker scaffolding for a project whose code is already
+    /// in `<projects>/<name>/src`, using the template and configuration
+    /// detected from that code, and saves the project.
+    pub fn scaffold_imported(name: &str, templates_path: &str) -> Result<(Project, DetectionReport), String> {
+        let projects_dir = Self::ensure_projects_dir()?;
+        let src = Path::new(&projects_dir).join(name).join("src");
 
-        // Save project config
-        Self::save_project(&project)?;
+        let (mut config, report) = FrameworkDetector::detect(&src, templates_path)?;
+        println!("[GodMode] Detected {} project: {}", report.template, report.evidence.join("; "));
+        config.ports = PortAllocator::allocate(&config.ports, &config.services, None)?;
+
+        let project = create_project_from_template(name, &report.template, &projects_dir, templates_path, config)?;
 
-        Ok(project)
+        // Laravel reads src/.env, which is usually not part of the code
+        if report.template == "laravel" && !src.join(".env").exists() {
+            fs::copy(Path::new(&project.path).join(".env"), src.join(".env"))
+                .map_err(|e| format!("Failed to create src/.env: {}", e))?;
+        }
+
+        Self::save_project(&project)?;
+        Ok((project, report))
     }
 }
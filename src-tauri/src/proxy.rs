@@ -0,0 +1,353 @@
+use crate::docker::DockerManager;
+use crate::engine::{EngineClient, EngineError};
+use crate::project::ProjectManager;
+use crate::state::ProjectStatus;
+use crate::tls::CertificateManager;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const PROXY_NETWORK: &str = "godmode-proxy";
+const PROXY_CONTAINER: &str = "godmode-proxy";
+const PROXY_IMAGE: &str = "nginx:alpine";
+const DOMAIN_SUFFIX: &str = ".test";
+
+// Serialises refreshes triggered from different threads
+static REFRESH_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyRoute {
+    pub project_id: String,
+    pub project_name: String,
+    pub service: String,
+    pub host: String,
+    pub container: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub http_port: u16,
+    pub https_port: u16,
+    pub routes: Vec<ProxyRoute>,
+}
+
+/// Runs a single nginx container on a shared network that routes
+/// `<project>.test` (and `pma.`, `mail.` and `vite.` subdomains) to the
+/// containers of running projects, over HTTP and over HTTPS with
+/// certificates from the local CA.
+pub struct ProxyManager;
+
+impl ProxyManager {
+    /// The `.test` host name of a project.
+    pub fn project_domain(project_name: &str) -> String {
+        let slug: String = project_name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("{}{}", slug.trim_matches('-'), DOMAIN_SUFFIX)
+    }
+
+    /// Every host name the proxy can serve for a project.
+    pub fn project_hosts(project_name: &str) -> Vec<String> {
+        let domain = Self::project_domain(project_name);
+        vec![
+            domain.clone(),
+            format!("vite.{}", domain),
+            format!("pma.{}", domain),
+            format!("mail.{}", domain),
+        ]
+    }
+
+    fn config_dir() -> PathBuf {
+        ProjectManager::app_data_dir().join("proxy").join("conf.d")
+    }
+
+    fn certs_dir() -> PathBuf {
+        ProjectManager::app_data_dir().join("proxy").join("certs")
+    }
+
+    pub fn status() -> Result<ProxyStatus, String> {
+        let settings = ProjectManager::load_settings();
+        let client = EngineClient::from_env()?;
+        let running = Self::is_running(&client);
+
+        Ok(ProxyStatus {
+            enabled: settings.proxy.enabled,
+            running,
+            http_port: settings.proxy.http_port,
+            https_port: settings.proxy.https_port,
+            routes: if settings.proxy.enabled { Self::collect_routes(&client)? } else { Vec::new() },
+        })
+    }
+
+    fn is_running(client: &EngineClient) -> bool {
+        client
+            .inspect_container(PROXY_CONTAINER)
+            .map(|info| info["State"]["Running"].as_bool().unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    pub fn enable(http_port: Option<u16>, https_port: Option<u16>) -> Result<ProxyStatus, String> {
+        let mut settings = ProjectManager::load_settings();
+        settings.proxy.enabled = true;
+        if let Some(port) = http_port {
+            settings.proxy.http_port = port;
+        }
+        if let Some(port) = https_port {
+            settings.proxy.https_port = port;
+        }
+        ProjectManager::save_settings(&settings)?;
+
+        // A changed port means the container must be recreated
+        let client = EngineClient::from_env()?;
+        let _ = client.remove_container(PROXY_CONTAINER, true);
+
+        Self::refresh()
+    }
+
+    pub fn disable() -> Result<ProxyStatus, String> {
+        let mut settings = ProjectManager::load_settings();
+        settings.proxy.enabled = false;
+        ProjectManager::save_settings(&settings)?;
+
+        let client = EngineClient::from_env()?;
+        match client.remove_container(PROXY_CONTAINER, true) {
+            Ok(()) | Err(EngineError::NotFound(_)) => {}
+            Err(e) => return Err(format!("Failed to remove proxy container: {}", e)),
+        }
+
+        Self::status()
+    }
+
+    /// Refreshes routes on a background thread, for callers that should
+    /// not wait on Docker (project create/start/stop).
+    pub fn refresh_in_background() {
+        if !ProjectManager::load_settings().proxy.enabled {
+            return;
+        }
+
+        std::thread::spawn(|| {
+            if let Err(e) = Self::refresh() {
+                println!("[Proxy] Failed to refresh routes: {}", e);
+            }
+        });
+    }
+
+    /// Regenerates the routes from the current projects and makes sure the
+    /// proxy container is running with them. Does nothing while disabled.
+    pub fn refresh() -> Result<ProxyStatus, String> {
+        let _guard = REFRESH_LOCK.lock().unwrap();
+
+        let settings = ProjectManager::load_settings();
+        if !settings.proxy.enabled {
+            return Self::status();
+        }
+
+        let client = EngineClient::from_env()?;
+        client
+            .ensure_network(PROXY_NETWORK)
+            .map_err(|e| format!("Failed to create proxy network: {}", e))?;
+
+        let routes = Self::collect_routes(&client)?;
+        for container in routes.iter().map(|r| r.container.as_str()) {
+            client
+                .connect_network(PROXY_NETWORK, container)
+                .map_err(|e| format!("Failed to attach {} to the proxy network: {}", container, e))?;
+        }
+
+        Self::ensure_certificates(&routes)?;
+
+        let config_dir = Self::config_dir();
+        fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create proxy config directory: {}", e))?;
+        fs::write(config_dir.join("godmode.conf"), Self::render_config(&routes))
+            .map_err(|e| format!("Failed to write proxy config: {}", e))?;
+
+        let reloaded = Self::is_running(&client)
+            && client
+                .exec(PROXY_CONTAINER, &["nginx", "-s", "reload"], &Default::default())
+                .map(|output| output.exit_code == 0)
+                .unwrap_or(false);
+        if !reloaded {
+            // Not running, or created before its current mounts and ports existed
+            Self::start_container(&client, settings.proxy.http_port, settings.proxy.https_port)?;
+        }
+
+        println!("[Proxy] Routing {} host(s)", routes.len());
+        Ok(ProxyStatus {
+            enabled: true,
+            running: true,
+            http_port: settings.proxy.http_port,
+            https_port: settings.proxy.https_port,
+            routes,
+        })
+    }
+
+    fn start_container(client: &EngineClient, http_port: u16, https_port: u16) -> Result<(), String> {
+        let _ = client.remove_container(PROXY_CONTAINER, true);
+
+        if !client.image_exists(PROXY_IMAGE).unwrap_or(false) {
+            println!("[Proxy] Pulling {}", PROXY_IMAGE);
+            client
+                .pull_image(PROXY_IMAGE)
+                .map_err(|e| format!("Failed to pull {}: {}", PROXY_IMAGE, e))?;
+        }
+
+        let config = json!({
+            "Image": PROXY_IMAGE,
+            "Labels": { "dev.godmode.proxy": "true" },
+            "ExposedPorts": { "80/tcp": {}, "443/tcp": {} },
+            "HostConfig": {
+                "PortBindings": {
+                    "80/tcp": [{ "HostPort": http_port.to_string() }],
+                    "443/tcp": [{ "HostPort": https_port.to_string() }],
+                },
+                "Binds": [
+                    format!("{}:/etc/nginx/conf.d:ro", Self::config_dir().to_string_lossy()),
+                    format!("{}:/etc/nginx/certs:ro", Self::certs_dir().to_string_lossy()),
+                ],
+                "RestartPolicy": { "Name": "unless-stopped" },
+                "NetworkMode": PROXY_NETWORK,
+            },
+        });
+
+        let id = client
+            .create_container(PROXY_CONTAINER, &config)
+            .map_err(|e| format!("Failed to create proxy container: {}", e))?;
+        client
+            .start_container(&id)
+            .map_err(|e| format!("Failed to start proxy container (are ports {} and {} free?): {}", http_port, https_port, e))
+    }
+
+    /// Issues a certificate for every routed project domain that lacks one.
+    /// Each covers the domain and its subdomains.
+    fn ensure_certificates(routes: &[ProxyRoute]) -> Result<(), String> {
+        let certs_dir = Self::certs_dir();
+        fs::create_dir_all(&certs_dir).map_err(|e| format!("Failed to create proxy certificate directory: {}", e))?;
+
+        let mut names: Vec<&str> = routes.iter().map(|r| r.project_name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let domain = Self::project_domain(name);
+            let cert_path = certs_dir.join(format!("{}.pem", domain));
+            let key_path = certs_dir.join(format!("{}-key.pem", domain));
+            if !cert_path.exists() || !key_path.exists() {
+                CertificateManager::issue(&[domain.clone(), format!("*.{}", domain)], &cert_path, &key_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds, for every running project, the container publishing each of
+    /// its configured ports and the port it listens on inside the network.
+    fn collect_routes(client: &EngineClient) -> Result<Vec<ProxyRoute>, String> {
+        let projects = ProjectManager::load_all_projects()?;
+        let mut routes = Vec::new();
+
+        for project in projects.values().filter(|p| p.status == ProjectStatus::Running) {
+            let containers = match DockerManager::find_project_containers(client, &project.path, &project.name) {
+                Ok(containers) => containers,
+                Err(e) => {
+                    println!("[Proxy] Failed to list containers for {}: {}", project.name, e);
+                    continue;
+                }
+            };
+
+            let domain = Self::project_domain(&project.name);
+            let ports = &project.config.ports;
+            let targets = [
+                ("app", domain.clone(), ports.app),
+                ("vite", format!("vite.{}", domain), ports.vite),
+                ("phpmyadmin", format!("pma.{}", domain), ports.phpmyadmin),
+                ("mailhog", format!("mail.{}", domain), ports.mailhog),
+            ];
+
+            for (service, host, host_port) in targets {
+                let target = containers.iter().filter(|c| c.state == "running").find_map(|c| {
+                    c.ports
+                        .iter()
+                        .find(|p| p.public_port == Some(host_port))
+                        .map(|p| (c.name(), p.private_port))
+                });
+
+                if let Some((container, port)) = target {
+                    routes.push(ProxyRoute {
+                        project_id: project.id.clone(),
+                        project_name: project.name.clone(),
+                        service: service.to_string(),
+                        host,
+                        container,
+                        port,
+                    });
+                }
+            }
+        }
+
+        routes.sort_by(|a, b| a.host.cmp(&b.host));
+        Ok(routes)
+    }
+
+    fn render_config(routes: &[ProxyRoute]) -> String {
+        let mut config = String::from(
+            r#"# Generated by Laravel GodMode - changes will be overwritten
+
+map $http_upgrade $connection_upgrade {
+    default upgrade;
+    '' close;
+}
+
+server {
+    listen 80 default_server;
+    server_name _;
+    return 404 "No running GodMode project is served on this host\n";
+}
+"#,
+        );
+
+        for route in routes {
+            // Resolving at request time keeps nginx up when a container is gone
+            config.push_str(&format!(
+                r#"
+# {project} ({service})
+server {{
+    listen 80;
+    listen 443 ssl;
+    server_name {host};
+    client_max_body_size 100m;
+
+    ssl_certificate /etc/nginx/certs/{domain}.pem;
+    ssl_certificate_key /etc/nginx/certs/{domain}-key.pem;
+
+    location / {{
+        resolver 127.0.0.11 valid=10s;
+        set $upstream http://{container}:{port};
+        proxy_pass $upstream;
+        proxy_http_version 1.1;
+        proxy_set_header Host $host;
+        proxy_set_header X-Real-IP $remote_addr;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+        proxy_set_header X-Forwarded-Proto $scheme;
+        proxy_set_header Upgrade $http_upgrade;
+        proxy_set_header Connection $connection_upgrade;
+    }}
+}}
+"#,
+                project = route.project_name,
+                service = route.service,
+                host = route.host,
+                domain = Self::project_domain(&route.project_name),
+                container = route.container,
+                port = route.port,
+            ));
+        }
+
+        config
+    }
+}
@@ -0,0 +1,483 @@
+use crate::catalog::TemplateCatalog;
+use crate::ports::PortAllocator;
+use crate::preview::match_lines;
+use crate::project::ProjectManager;
+use crate::state::{Project, ProjectConfig};
+use crate::template::{write_rendered_file, RenderedFile, TemplateEngine};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Copies of the files as last generated, relative to the project. They are
+/// the common ancestor when merging a new rendering with local edits.
+pub const GENERATED_DIR: &str = ".godmode/generated";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegenerateOutcome {
+    /// The file already matched, or only had local edits the new rendering does not touch.
+    Unchanged,
+    /// The file had no local edits and was replaced.
+    Updated,
+    /// Local edits and template changes were combined.
+    Merged,
+    /// Local edits and template changes overlap; the file has conflict markers.
+    Conflicted,
+    /// The file did not exist and was written.
+    Created,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegeneratedFile {
+    pub path: String,
+    pub outcome: RegenerateOutcome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigUpdate {
+    pub project: Project,
+    pub files: Vec<RegeneratedFile>,
+    /// Files left with conflict markers to resolve by hand.
+    pub conflicts: Vec<String>,
+    pub rebuilt: bool,
+}
+
+/// Applies configuration changes to existing projects by rendering their
+/// template again and merging the result into the files on disk.
+pub struct ProjectRegenerator;
+
+impl ProjectRegenerator {
+    /// Saves `config` for a project and brings its generated files in line
+    /// with it. Files the user edited are three-way merged: the last
+    /// generated copy is the ancestor, the local file and the new rendering
+    /// the two sides. Code under `src/` belongs to the app and is left alone.
+    pub fn update_config(project_id: &str, mut config: ProjectConfig, templates_path: &str) -> Result<ConfigUpdate, String> {
+        let project = ProjectManager::get_project(project_id)?;
+        if project.template.starts_with("custom:") {
+            return Err(format!("{} was created from a custom template, which cannot be rendered again", project.name));
+        }
+        config.ports = PortAllocator::reallocate(&project, &config.ports, &config.services)?;
+
+        let updated = Project {
+            config,
+            updated_at: Utc::now(),
+            ..project.clone()
+        };
+        let engine = TemplateEngine::for_project(templates_path, &project)?;
+        let (files, conflicts) = Self::regenerate(engine, &project, &updated, None)?;
+        ProjectManager::save_project(&updated)?;
+        println!("[Regenerate] Updated {} ({} conflict(s))", updated.name, conflicts.len());
+
+        Ok(ConfigUpdate {
+            project: updated,
+            files,
+            conflicts,
+            rebuilt: false,
+        })
+    }
+
+    /// Brings `outputs` (e.g. "docker-compose.yml") of a project copied or
+    /// renamed from `source` in line with its own name and configuration,
+    /// merging local edits like `update_config`. Returns the outputs
+    /// regenerated, none for templates that cannot be rendered again, and
+    /// fails when local edits conflict with the changes.
+    pub fn retarget(project: &Project, source: &Project, templates_path: &str, outputs: &[&str]) -> Result<Vec<String>, String> {
+        if !TemplateCatalog::template_dir(templates_path, &project.template).join("config.json").exists() {
+            return Ok(Vec::new());
+        }
+
+        let engine = TemplateEngine::for_project(templates_path, project)?;
+        let (files, conflicts) = Self::regenerate(engine, source, project, Some(outputs))?;
+        if !conflicts.is_empty() {
+            return Err(format!(
+                "Local edits to {} conflict with the changes for '{}', undo them and try again",
+                conflicts.join(", "),
+                project.name
+            ));
+        }
+        Ok(files.into_iter().map(|file| file.path).collect())
+    }
+
+    /// Renders the template of `project` as it was (`before`) and as it is
+    /// now with `engine`, and merges the difference into its files: the
+    /// `outputs` given, or all but the app's code under `src/`. Returns what
+    /// happened to each file and the ones left conflicted.
+    fn regenerate(
+        mut engine: TemplateEngine,
+        before: &Project,
+        project: &Project,
+        outputs: Option<&[&str]>,
+    ) -> Result<(Vec<RegeneratedFile>, Vec<String>), String> {
+        // One engine for both, so renders only differ where the projects do
+        let previous = engine.render_project(&before.name, &before.config)?;
+        let rendered: Vec<RenderedFile> = engine
+            .render_project(&project.name, &project.config)?
+            .into_iter()
+            .filter(|file| match outputs {
+                Some(outputs) => outputs.contains(&file.path.as_str()),
+                None => !file.path.starts_with("src/"),
+            })
+            .collect();
+        // The copies of a project without saved secrets hold values this
+        // engine does not know, while its old rendering has the ones both
+        // renders share. Merging from that keeps the values in the files.
+        let knows_secrets = engine.knows_project_secrets();
+
+        let project_path = Path::new(&project.path);
+        let mut files = Vec::new();
+        let mut conflicts = Vec::new();
+        for file in &rendered {
+            // Projects from before the copies were kept fall back to what the old config renders to
+            let previous = previous.iter().find(|p| p.path == file.path).map(|p| p.content.clone());
+            let base = if knows_secrets {
+                read_generated(project_path, &file.path).or(previous)
+            } else {
+                previous
+            };
+            let outcome = Self::apply(project_path, file, base.as_deref())?;
+
+            if outcome == RegenerateOutcome::Conflicted {
+                conflicts.push(file.path.clone());
+            }
+            files.push(RegeneratedFile {
+                path: file.path.clone(),
+                outcome,
+            });
+        }
+        record_generated(project_path, &rendered);
+        if knows_secrets {
+            engine.save_secrets(project_path);
+        }
+        Ok((files, conflicts))
+    }
+
+    fn apply(project_path: &Path, file: &RenderedFile, base: Option<&str>) -> Result<RegenerateOutcome, String> {
+        let Ok(local) = fs::read_to_string(project_path.join(&file.path)) else {
+            write_rendered_file(project_path, file)?;
+            return Ok(RegenerateOutcome::Created);
+        };
+
+        let base = base.unwrap_or_default();
+        if local == file.content || base == file.content {
+            return Ok(RegenerateOutcome::Unchanged);
+        }
+        if local == base {
+            write_rendered_file(project_path, file)?;
+            return Ok(RegenerateOutcome::Updated);
+        }
+
+        let (content, conflicts) = merge3(base, &local, &file.content);
+        write_rendered_file(
+            project_path,
+            &RenderedFile {
+                content,
+                ..file.clone()
+            },
+        )?;
+        Ok(if conflicts > 0 {
+            RegenerateOutcome::Conflicted
+        } else {
+            RegenerateOutcome::Merged
+        })
+    }
+}
+
+/// Keeps copies of freshly generated `files` as the ancestors for later merges.
+pub fn record_generated<'a>(project_path: &Path, files: impl IntoIterator<Item = &'a RenderedFile>) {
+    for file in files {
+        let copy = project_path.join(GENERATED_DIR).join(&file.path);
+        if let Some(parent) = copy.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        if let Err(e) = fs::write(&copy, &file.content) {
+            println!("[Regenerate] Failed to keep generated copy of {}: {}", file.path, e);
+        }
+    }
+}
+
+fn read_generated(project_path: &Path, path: &str) -> Option<String> {
+    fs::read_to_string(project_path.join(GENERATED_DIR).join(path)).ok()
+}
+
+/// Three-way merges `ours` and `theirs`, two edits of `base`, line by line.
+/// Where both changed the same lines differently, both versions are kept
+/// between conflict markers. Returns the merged text and the conflict count.
+fn merge3(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+    let base: Vec<&str> = base.lines().collect();
+    let ours: Vec<&str> = ours.lines().collect();
+    let theirs: Vec<&str> = theirs.lines().collect();
+    let in_ours = match_lines(&base, &ours);
+    let in_theirs = match_lines(&base, &theirs);
+
+    let mut merged: Vec<&str> = Vec::new();
+    let mut conflicts = 0;
+    let (mut i, mut o, mut t) = (0, 0, 0);
+    loop {
+        // Lines neither side touched
+        while i < base.len() && in_ours[i] == Some(o) && in_theirs[i] == Some(t) {
+            merged.push(base[i]);
+            i += 1;
+            o += 1;
+            t += 1;
+        }
+        if i == base.len() && o == ours.len() && t == theirs.len() {
+            break;
+        }
+
+        // The next base line both sides kept ends the changed chunk
+        let next = (i..base.len()).find(|&k| in_ours[k].is_some() && in_theirs[k].is_some());
+        let (end, o_end, t_end) = match next {
+            Some(k) => (k, in_ours[k].unwrap(), in_theirs[k].unwrap()),
+            None => (base.len(), ours.len(), theirs.len()),
+        };
+        let (base_chunk, ours_chunk, theirs_chunk) = (&base[i..end], &ours[o..o_end], &theirs[t..t_end]);
+
+        if ours_chunk == base_chunk || ours_chunk == theirs_chunk {
+            merged.extend_from_slice(theirs_chunk);
+        } else if theirs_chunk == base_chunk {
+            merged.extend_from_slice(ours_chunk);
+        } else {
+            conflicts += 1;
+            merged.push("<<<<<<< local");
+            merged.extend_from_slice(ours_chunk);
+            merged.push("=======");
+            merged.extend_from_slice(theirs_chunk);
+            merged.push(">>>>>>> template");
+        }
+        (i, o, t) = (end, o_end, t_end);
+    }
+
+    let content = merged.iter().map(|line| format!("{}\n", line)).collect();
+    (content, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{TemplateRoot, TemplateSource};
+    use crate::schema::PROJECT_SCHEMA_VERSION;
+    use crate::state::ProjectStatus;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn templates_path() -> String {
+        format!("{}/../templates", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    /// An engine for the bundled Node.js template that reads no settings and probes no backend.
+    fn engine() -> TemplateEngine {
+        let bundled = TemplateRoot {
+            path: templates_path(),
+            source: TemplateSource::Bundled,
+            pack: None,
+        };
+        TemplateEngine::with_context(&[bundled], &templates_path(), "nodejs", "docker compose").unwrap()
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("godmode-regenerate-{}", Uuid::new_v4()))
+    }
+
+    /// A Node.js project generated into a temporary folder, as `create_project` writes it.
+    fn generated_project() -> Project {
+        let path = temp_dir();
+        let config = ProjectConfig::default();
+
+        let mut engine = engine();
+        let files = engine.render_project("shop", &config).unwrap();
+        for file in &files {
+            write_rendered_file(&path, file).unwrap();
+        }
+        record_generated(&path, &files);
+        engine.save_secrets(&path);
+
+        let now = Utc::now();
+        Project {
+            schema_version: PROJECT_SCHEMA_VERSION,
+            id: Uuid::new_v4().to_string(),
+            name: "shop".to_string(),
+            template: "nodejs".to_string(),
+            path: path.to_string_lossy().to_string(),
+            created_at: now,
+            updated_at: now,
+            config,
+            status: ProjectStatus::Stopped,
+            status_message: None,
+        }
+    }
+
+    fn session_secret(project_path: &Path) -> String {
+        let env = fs::read_to_string(project_path.join(".env")).unwrap();
+        env.lines().find(|line| line.starts_with("SESSION_SECRET=")).unwrap().to_string()
+    }
+
+    /// Turns Redis on or off, as `update_config` does. Returns the updated project.
+    fn toggle_redis(project: &Project) -> (Project, Vec<RegeneratedFile>) {
+        let mut updated = project.clone();
+        updated.config.services.redis = !updated.config.services.redis;
+        let engine = engine().with_saved_secrets(Path::new(&project.path));
+        let (files, conflicts) = ProjectRegenerator::regenerate(engine, project, &updated, None).unwrap();
+        assert!(conflicts.is_empty());
+        (updated, files)
+    }
+
+    #[test]
+    fn updating_the_config_keeps_session_secret() {
+        let project = generated_project();
+        let project_path = PathBuf::from(&project.path);
+        let before = session_secret(&project_path);
+
+        let (_, files) = toggle_redis(&project);
+
+        let env = files.iter().find(|file| file.path == ".env").unwrap();
+        assert_eq!(env.outcome, RegenerateOutcome::Updated);
+        assert_eq!(session_secret(&project_path), before);
+        fs::remove_dir_all(&project_path).ok();
+    }
+
+    #[test]
+    fn updating_the_config_keeps_session_secret_of_projects_without_saved_secrets() {
+        let project = generated_project();
+        let project_path = PathBuf::from(&project.path);
+        fs::remove_file(project_path.join(".godmode/secrets.json")).unwrap();
+        let before = session_secret(&project_path);
+
+        let (updated, files) = toggle_redis(&project);
+        assert_eq!(session_secret(&project_path), before);
+        let env = fs::read_to_string(project_path.join(".env")).unwrap();
+        assert_eq!(env.contains("REDIS_HOST="), updated.config.services.redis);
+        assert!(files.iter().all(|file| file.outcome != RegenerateOutcome::Conflicted));
+
+        // New values are never saved over the ones in the files
+        toggle_redis(&updated);
+        assert_eq!(session_secret(&project_path), before);
+        assert!(!project_path.join(".godmode/secrets.json").exists());
+        fs::remove_dir_all(&project_path).ok();
+    }
+
+    #[test]
+    fn merge_combines_edits_to_different_lines() {
+        let (merged, conflicts) = merge3("a\nb\nc\nd\n", "a\nlocal\nc\nd\n", "a\nb\nc\ntemplate\n");
+        assert_eq!(merged, "a\nlocal\nc\ntemplate\n");
+        assert_eq!(conflicts, 0);
+    }
+
+    #[test]
+    fn merge_keeps_both_sides_of_overlapping_edits_between_markers() {
+        let (merged, conflicts) = merge3("a\nb\nc\n", "a\nlocal\nc\n", "a\ntemplate\nc\n");
+        assert_eq!(merged, "a\n<<<<<<< local\nlocal\n=======\ntemplate\n>>>>>>> template\nc\n");
+        assert_eq!(conflicts, 1);
+    }
+
+    #[test]
+    fn merge_keeps_lines_added_at_the_end() {
+        let (merged, conflicts) = merge3("a\nb\n", "a\nb\nlocal\n", "template\nb\n");
+        assert_eq!(merged, "template\nb\nlocal\n");
+        assert_eq!(conflicts, 0);
+
+        let (merged, conflicts) = merge3("a\n", "a\nlocal\n", "a\ntemplate\n");
+        assert_eq!(merged, "a\n<<<<<<< local\nlocal\n=======\ntemplate\n>>>>>>> template\n");
+        assert_eq!(conflicts, 1);
+    }
+
+    #[test]
+    fn merge_without_a_base_conflicts_over_the_whole_file() {
+        let (merged, conflicts) = merge3("", "local\n", "template\n");
+        assert_eq!(merged, "<<<<<<< local\nlocal\n=======\ntemplate\n>>>>>>> template\n");
+        assert_eq!(conflicts, 1);
+    }
+
+    /// Applies a rendering of `content` to `local` (no file when `None`) with `base`.
+    /// Returns the outcome and the file afterwards.
+    fn apply(local: Option<&str>, base: Option<&str>, content: &str) -> (RegenerateOutcome, String) {
+        let project_path = temp_dir();
+        fs::create_dir_all(&project_path).unwrap();
+        if let Some(local) = local {
+            fs::write(project_path.join("Makefile"), local).unwrap();
+        }
+        let file = RenderedFile {
+            path: "Makefile".to_string(),
+            content: content.to_string(),
+            mode: None,
+        };
+
+        let outcome = ProjectRegenerator::apply(&project_path, &file, base).unwrap();
+        let written = fs::read_to_string(project_path.join("Makefile")).unwrap();
+        fs::remove_dir_all(&project_path).ok();
+        (outcome, written)
+    }
+
+    #[test]
+    fn apply_writes_missing_files() {
+        assert_eq!(apply(None, Some("a\n"), "b\n"), (RegenerateOutcome::Created, "b\n".to_string()));
+    }
+
+    #[test]
+    fn apply_leaves_files_the_rendering_does_not_change() {
+        assert_eq!(apply(Some("b\n"), Some("a\n"), "b\n"), (RegenerateOutcome::Unchanged, "b\n".to_string()));
+        // Local edits stay when the template output is the same as before
+        assert_eq!(apply(Some("local\n"), Some("a\n"), "a\n"), (RegenerateOutcome::Unchanged, "local\n".to_string()));
+    }
+
+    #[test]
+    fn apply_replaces_files_without_local_edits() {
+        assert_eq!(apply(Some("a\n"), Some("a\n"), "b\n"), (RegenerateOutcome::Updated, "b\n".to_string()));
+    }
+
+    #[test]
+    fn apply_merges_local_edits() {
+        assert_eq!(
+            apply(Some("local\nb\nc\n"), Some("a\nb\nc\n"), "a\nb\ntemplate\n"),
+            (RegenerateOutcome::Merged, "local\nb\ntemplate\n".to_string())
+        );
+    }
+
+    #[test]
+    fn apply_marks_conflicting_edits() {
+        let (outcome, written) = apply(Some("local\n"), Some("a\n"), "template\n");
+        assert_eq!(outcome, RegenerateOutcome::Conflicted);
+        assert_eq!(written, "<<<<<<< local\nlocal\n=======\ntemplate\n>>>>>>> template\n");
+
+        // Without a base every difference is a conflict
+        let (outcome, _) = apply(Some("a\nlocal\n"), None, "a\ntemplate\n");
+        assert_eq!(outcome, RegenerateOutcome::Conflicted);
+    }
+
+    /// `project` renamed to "store", as `ProjectRelocator::rename` does, with
+    /// `edit` applied to its docker-compose.yml first.
+    fn rename(project: &Project, edit: (&str, &str)) -> (String, Vec<String>) {
+        let compose_path = Path::new(&project.path).join("docker-compose.yml");
+        let compose = fs::read_to_string(&compose_path).unwrap();
+        assert!(compose.contains(edit.0));
+        fs::write(&compose_path, compose.replace(edit.0, edit.1)).unwrap();
+
+        let mut renamed = project.clone();
+        renamed.name = "store".to_string();
+        renamed.config.database_name = Some(project.name.clone());
+        let engine = engine().with_saved_secrets(Path::new(&project.path));
+        let (_, conflicts) = ProjectRegenerator::regenerate(engine, project, &renamed, Some(&["docker-compose.yml", "Makefile"])).unwrap();
+
+        let compose = fs::read_to_string(&compose_path).unwrap();
+        fs::remove_dir_all(&project.path).ok();
+        (compose, conflicts)
+    }
+
+    #[test]
+    fn renaming_keeps_local_edits() {
+        let (compose, conflicts) = rename(&generated_project(), ("working_dir: /app", "working_dir: /srv/app"));
+
+        assert!(conflicts.is_empty());
+        assert!(compose.contains("working_dir: /srv/app"));
+        assert!(compose.contains("container_name: store_app"));
+        assert!(compose.contains("MYSQL_DATABASE: shop"));
+        assert!(!compose.contains("shop_"));
+    }
+
+    #[test]
+    fn renaming_reports_local_edits_to_renamed_lines() {
+        let (_, conflicts) = rename(&generated_project(), ("container_name: shop_app", "container_name: web"));
+
+        assert_eq!(conflicts, vec!["docker-compose.yml".to_string()]);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::project::ProjectManager;
+use crate::schema::{read_project_file, PROJECT_SCHEMA_VERSION};
+use crate::state::Project;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+/// The file in a project directory that describes the project.
+pub const PROJECT_FILE: &str = ".godmode.json";
+
+static REGISTRY: OnceLock<Arc<ProjectRegistry>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegistryEntry {
+    path: String,
+    /// Modification time (ms since the epoch) and size of the project file
+    /// when `project` was read from it.
+    modified_ms: i64,
+    size: u64,
+    project: Project,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RegistryFile {
+    projects: HashMap<String, RegistryEntry>,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    loaded: bool,
+    entries: HashMap<String, RegistryEntry>,
+    watcher: Option<RecommendedWatcher>,
+    watched: HashSet<PathBuf>,
+}
+
+/// Index of every known project by id, with the contents of each project's
+/// `.godmode.json` cached in `registry.json`. Projects can live anywhere;
+/// the projects directory is scanned for new ones. A filesystem watcher
+/// marks cached projects stale when their file changes outside the app.
+pub struct ProjectRegistry {
+    state: Mutex<RegistryState>,
+    // Filled by the watcher thread, which must never wait on `state`:
+    // adding a watch blocks until that thread has handled it
+    changed: Mutex<HashSet<PathBuf>>,
+    rescan_needed: AtomicBool,
+    projects_dir: Mutex<Option<PathBuf>>,
+}
+
+impl ProjectRegistry {
+    /// The registry shared by commands and background threads.
+    pub fn global() -> Arc<ProjectRegistry> {
+        REGISTRY
+            .get_or_init(|| {
+                Arc::new(ProjectRegistry {
+                    state: Mutex::new(RegistryState::default()),
+                    changed: Mutex::new(HashSet::new()),
+                    rescan_needed: AtomicBool::new(false),
+                    projects_dir: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+
+    fn registry_path() -> PathBuf {
+        ProjectManager::app_data_dir().join("registry.json")
+    }
+
+    pub fn all(&self) -> Result<HashMap<String, Project>, String> {
+        let mut state = self.state.lock().unwrap();
+        self.sync(&mut state)?;
+        Ok(state.entries.iter().map(|(id, entry)| (id.clone(), entry.project.clone())).collect())
+    }
+
+    pub fn get(&self, project_id: &str) -> Result<Project, String> {
+        let mut state = self.state.lock().unwrap();
+        self.sync(&mut state)?;
+        state
+            .entries
+            .get(project_id)
+            .map(|entry| entry.project.clone())
+            .ok_or_else(|| "Project not found".to_string())
+    }
+
+    /// Records a project that was just written to its project file.
+    pub fn put(&self, project: &Project) {
+        let mut state = self.state.lock().unwrap();
+        if let Err(e) = self.sync(&mut state) {
+            println!("[Registry] {}", e);
+        }
+
+        let path = Path::new(&project.path);
+        let (modified_ms, size) = file_stamp(&path.join(PROJECT_FILE)).unwrap_or_default();
+        state.entries.insert(
+            project.id.clone(),
+            RegistryEntry {
+                path: project.path.clone(),
+                modified_ms,
+                size,
+                project: project.clone(),
+            },
+        );
+        self.watch(&mut state, path);
+        Self::persist(&state);
+    }
+
+    /// Forgets a project without touching its files.
+    pub fn remove(&self, project_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(entry) = state.entries.remove(project_id) {
+            let path = PathBuf::from(&entry.path);
+            if let Some(watcher) = state.watcher.as_mut() {
+                let _ = watcher.unwatch(&path);
+            }
+            state.watched.remove(&path);
+            Self::persist(&state);
+        }
+    }
+
+    /// Adds the project in `path`, which may be outside the projects directory.
+    pub fn register(&self, path: &str) -> Result<Project, String> {
+        let path = fs::canonicalize(path).map_err(|e| format!("Folder not found: {}: {}", path, e))?;
+        let entry = read_entry(&path)?.ok_or_else(|| format!("No {} found in {}", PROJECT_FILE, path.display()))?;
+
+        let mut state = self.state.lock().unwrap();
+        self.sync(&mut state)?;
+        if let Some(existing) = state.entries.get(&entry.project.id) {
+            if Path::new(&existing.path) != path && Path::new(&existing.path).join(PROJECT_FILE).exists() {
+                return Err(format!("Project '{}' is already registered at {}", existing.project.name, existing.path));
+            }
+        }
+
+        let project = entry.project.clone();
+        state.entries.insert(project.id.clone(), entry);
+        self.watch(&mut state, &path);
+        Self::persist(&state);
+
+        println!("[Registry] Registered {} at {}", project.name, path.display());
+        Ok(project)
+    }
+
+    /// Re-reads every registered project file, drops projects whose file is
+    /// gone and picks up new projects in the projects directory.
+    pub fn rescan(&self) -> Result<Vec<Project>, String> {
+        let mut state = self.state.lock().unwrap();
+        if !state.loaded {
+            self.load(&mut state)?;
+        }
+
+        let ids: Vec<String> = state.entries.keys().cloned().collect();
+        for id in ids {
+            let path = PathBuf::from(&state.entries[&id].path);
+            match read_entry(&path) {
+                Ok(Some(entry)) => {
+                    state.entries.insert(id, entry);
+                }
+                Ok(None) => {
+                    state.entries.remove(&id);
+                }
+                Err(e) => println!("[Registry] Skipping {}: {}", path.display(), e),
+            }
+        }
+        self.scan_projects_dir(&mut state)?;
+        Self::persist(&state);
+
+        println!("[Registry] Rescanned {} project(s)", state.entries.len());
+        Ok(state.entries.values().map(|entry| entry.project.clone()).collect())
+    }
+
+    /// Loads the index on first use, then applies whatever the watcher saw.
+    fn sync(&self, state: &mut RegistryState) -> Result<(), String> {
+        if !state.loaded {
+            return self.load(state);
+        }
+
+        let changed: Vec<PathBuf> = self.changed.lock().unwrap().drain().collect();
+        let rescan = self.rescan_needed.swap(false, Ordering::SeqCst);
+        if changed.is_empty() && !rescan {
+            return Ok(());
+        }
+
+        let mut dirty = false;
+        for path in changed {
+            let id = state.entries.iter().find(|(_, e)| Path::new(&e.path) == path).map(|(id, _)| id.clone());
+            if let Some(id) = id {
+                dirty |= Self::refresh_entry(state, &id);
+            }
+        }
+        if rescan {
+            dirty |= self.scan_projects_dir(state)?;
+        }
+        if dirty {
+            Self::persist(state);
+        }
+        Ok(())
+    }
+
+    fn load(&self, state: &mut RegistryState) -> Result<(), String> {
+        let file: RegistryFile = fs::read_to_string(Self::registry_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        state.entries = file.projects;
+        state.loaded = true;
+
+        // Cached entries are only re-read when their file changed since
+        let ids: Vec<String> = state.entries.keys().cloned().collect();
+        for id in &ids {
+            Self::refresh_entry(state, id);
+        }
+        self.scan_projects_dir(state)?;
+
+        let paths: Vec<PathBuf> = state.entries.values().map(|e| PathBuf::from(&e.path)).collect();
+        for path in paths {
+            self.watch(state, &path);
+        }
+        Self::persist(state);
+
+        println!("[Registry] Loaded {} project(s)", state.entries.len());
+        Ok(())
+    }
+
+    /// Re-reads an entry if its project file changed. Returns whether the entry changed.
+    fn refresh_entry(state: &mut RegistryState, id: &str) -> bool {
+        let entry = &state.entries[id];
+        let path = PathBuf::from(&entry.path);
+        let stamp = file_stamp(&path.join(PROJECT_FILE));
+        // Entries cached before a schema change still need migrating
+        if stamp == Some((entry.modified_ms, entry.size)) && entry.project.schema_version == PROJECT_SCHEMA_VERSION {
+            return false;
+        }
+
+        match read_entry(&path) {
+            // The file may now describe a different project, e.g. after a copy
+            Ok(Some(fresh)) if fresh.project.id == id => {
+                state.entries.insert(id.to_string(), fresh);
+            }
+            Ok(_) => {
+                println!("[Registry] {} no longer holds project {}", path.display(), id);
+                state.entries.remove(id);
+            }
+            Err(e) => println!("[Registry] Keeping cached {}: {}", path.display(), e),
+        }
+        true
+    }
+
+    /// Registers projects in the projects directory that are not known yet.
+    /// Returns whether any were added.
+    fn scan_projects_dir(&self, state: &mut RegistryState) -> Result<bool, String> {
+        let projects_dir = PathBuf::from(ProjectManager::ensure_projects_dir()?);
+        {
+            let mut watched_dir = self.projects_dir.lock().unwrap();
+            if watched_dir.as_ref() != Some(&projects_dir) {
+                *watched_dir = Some(projects_dir.clone());
+                drop(watched_dir);
+                self.watch(state, &projects_dir);
+            }
+        }
+
+        let known: HashSet<PathBuf> = state.entries.values().map(|e| PathBuf::from(&e.path)).collect();
+        let entries = fs::read_dir(&projects_dir).map_err(|e| format!("Failed to read projects directory: {}", e))?;
+
+        let mut added = false;
+        for dir in entries.flatten().map(|e| e.path()) {
+            if !dir.is_dir() || known.contains(&dir) {
+                continue;
+            }
+            match read_entry(&dir) {
+                Ok(Some(entry)) if !state.entries.contains_key(&entry.project.id) => {
+                    state.entries.insert(entry.project.id.clone(), entry);
+                    self.watch(state, &dir);
+                    added = true;
+                }
+                Ok(_) => {}
+                Err(e) => println!("[Registry] Skipping {}: {}", dir.display(), e),
+            }
+        }
+        Ok(added)
+    }
+
+    fn watch(&self, state: &mut RegistryState, path: &Path) {
+        if state.watched.contains(path) {
+            return;
+        }
+
+        if state.watcher.is_none() {
+            match notify::recommended_watcher(|event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    ProjectRegistry::global().handle_event(&event);
+                }
+            }) {
+                Ok(watcher) => state.watcher = Some(watcher),
+                Err(e) => {
+                    println!("[Registry] File watching unavailable: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(watcher) = state.watcher.as_mut() {
+            match watcher.watch(path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    state.watched.insert(path.to_path_buf());
+                }
+                Err(e) => println!("[Registry] Failed to watch {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    // Runs on the watcher thread
+    fn handle_event(&self, event: &notify::Event) {
+        let projects_dir = self.projects_dir.lock().unwrap().clone();
+        for path in &event.paths {
+            let parent = path.parent().map(Path::to_path_buf);
+            if path.file_name().map(|n| n == PROJECT_FILE).unwrap_or(false) {
+                if let Some(parent) = parent {
+                    self.changed.lock().unwrap().insert(parent);
+                }
+                continue;
+            }
+
+            // A project directory itself was moved or deleted
+            self.changed.lock().unwrap().insert(path.clone());
+            if parent.is_some() && parent == projects_dir {
+                self.rescan_needed.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn persist(state: &RegistryState) {
+        let file = RegistryFile {
+            projects: state.entries.clone(),
+        };
+        let result = serde_json::to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                let path = Self::registry_path();
+                let temp = path.with_extension("json.tmp");
+                fs::create_dir_all(ProjectManager::app_data_dir()).map_err(|e| e.to_string())?;
+                fs::write(&temp, content).map_err(|e| e.to_string())?;
+                fs::rename(&temp, &path).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            println!("[Registry] Failed to save registry: {}", e);
+        }
+    }
+}
+
+/// Reads the project file in `dir`. `None` when there is none.
+fn read_entry(dir: &Path) -> Result<Option<RegistryEntry>, String> {
+    let file = dir.join(PROJECT_FILE);
+    if !file.exists() {
+        return Ok(None);
+    }
+
+    let mut project = read_project_file(&file).map_err(|e| format!("Failed to load {}: {}", PROJECT_FILE, e))?;
+    // Taken after reading, since a migration rewrites the file
+    let (modified_ms, size) = file_stamp(&file).unwrap_or_default();
+    // Where the file is wins over where it says it is, for folders moved by hand
+    project.path = dir.to_string_lossy().to_string();
+
+    Ok(Some(RegistryEntry {
+        path: dir.to_string_lossy().to_string(),
+        modified_ms,
+        size,
+        project,
+    }))
+}
+
+fn file_stamp(path: &Path) -> Option<(i64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_millis() as i64, metadata.len()))
+}
@@ -0,0 +1,324 @@
+use crate::docker::DockerManager;
+use crate::project::ProjectManager;
+use crate::regenerate::GENERATED_DIR;
+use crate::registry::ProjectRegistry;
+use crate::state::{Project, ProjectStatus};
+use crate::tls::{CertificateManager, PROJECT_CERTS_DIR};
+use chrono::Utc;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Files a rename may rewrite, relative to the project, restored on rollback
+/// along with their generated copies.
+const RENAMED_FILES: &[&str] = &[
+    ".godmode.json",
+    "docker-compose.yml",
+    "Makefile",
+    ".env",
+    "src/.env",
+    "docker/init.sql",
+    "docker/nginx.conf",
+    "docker/Caddyfile",
+    "docker/certs/cert.pem",
+    "docker/certs/key.pem",
+];
+
+type UndoStep = Box<dyn FnOnce() -> Result<(), String>>;
+
+/// Steps already taken, undone newest first when a later step fails.
+struct Rollback {
+    steps: Vec<(String, UndoStep)>,
+}
+
+impl Rollback {
+    fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    fn push(&mut self, description: &str, undo: impl FnOnce() -> Result<(), String> + 'static) {
+        self.steps.push((description.to_string(), Box::new(undo)));
+    }
+
+    fn run(self) {
+        for (description, undo) in self.steps.into_iter().rev() {
+            println!("[Relocate] Rolling back: {}", description);
+            if let Err(e) = undo() {
+                println!("[Relocate] Rollback step '{}' failed: {}", description, e);
+            }
+        }
+    }
+}
+
+/// Renames and moves projects. The stack is stopped, the directory moved
+/// (or copied, to another filesystem), generated files rewritten and named volumes migrated to the new compose
+/// project name, then the stack is restarted if it was running. A failure
+/// at any step puts everything back the way it was.
+pub struct ProjectRelocator;
+
+impl ProjectRelocator {
+    /// Gives a project a new name. Its directory follows the name, so
+    /// containers, networks, volumes, `.test` domain and certificate change too.
+    pub fn rename(project_id: &str, new_name: &str, templates_path: &str) -> Result<Project, String> {
+        let project = ProjectManager::get_project(project_id)?;
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name.starts_with('.') {
+            return Err(format!("Invalid project name: '{}'", new_name));
+        }
+        if new_name == project.name {
+            return Ok(project);
+        }
+        if ProjectManager::load_all_projects()?.values().any(|p| p.name == new_name) {
+            return Err(format!("A project named '{}' already exists", new_name));
+        }
+
+        let parent = Path::new(&project.path).parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::relocate(project, new_name, parent.join(new_name), templates_path)
+    }
+
+    /// Moves a project's directory to `destination`, keeping its name.
+    pub fn move_to(project_id: &str, destination: &str, templates_path: &str) -> Result<Project, String> {
+        let project = ProjectManager::get_project(project_id)?;
+        let destination = PathBuf::from(destination);
+        if destination == Path::new(&project.path) {
+            return Ok(project);
+        }
+
+        if !destination.parent().map(Path::is_dir).unwrap_or(false) {
+            return Err(format!("Destination folder does not exist: {}", destination.display()));
+        }
+
+        let name = project.name.clone();
+        Self::relocate(project, &name, destination, templates_path)
+    }
+
+    fn relocate(project: Project, new_name: &str, new_path: PathBuf, templates_path: &str) -> Result<Project, String> {
+        if new_path.exists() {
+            return Err(format!("Directory already exists: {}", new_path.display()));
+        }
+
+        let mut rollback = Rollback::new();
+        match Self::apply(&project, new_name, &new_path, templates_path, &mut rollback) {
+            Ok(relocated) => {
+                // The data now lives in the new volumes
+                if DockerManager::compose_project_name(&project.path) != DockerManager::compose_project_name(&relocated.path) {
+                    let old_volumes = DockerManager::project_volumes(&project.path).unwrap_or_default();
+                    if let Err(e) = DockerManager::remove_volumes(&old_volumes) {
+                        println!("[Relocate] Failed to remove old volumes: {}", e);
+                    }
+                }
+                // Moves to another filesystem copy the directory, leaving the original until now
+                if Path::new(&project.path).exists() {
+                    if let Err(e) = fs::remove_dir_all(&project.path) {
+                        println!("[Relocate] Failed to remove {} after copying it: {}", project.path, e);
+                    }
+                }
+                println!("[Relocate] {} is now {} at {}", project.name, relocated.name, relocated.path);
+                Ok(relocated)
+            }
+            Err(e) => {
+                println!("[Relocate] Failed to relocate {}: {}", project.name, e);
+                rollback.run();
+                Err(e)
+            }
+        }
+    }
+
+    fn apply(project: &Project, new_name: &str, new_path: &Path, templates_path: &str, rollback: &mut Rollback) -> Result<Project, String> {
+        let was_running = project.status == ProjectStatus::Running;
+        let old_path = project.path.clone();
+        {
+            // Undone last, once the original files are back in place
+            let original = project.clone();
+            rollback.push("restore the registry entry", move || {
+                ProjectRegistry::global().put(&original);
+                Ok(())
+            });
+        }
+
+        // Containers are named after the project, so they have to go first
+        if let Err(e) = DockerManager::stop_project(&old_path) {
+            if was_running {
+                return Err(format!("Failed to stop {}: {}", project.name, e));
+            }
+        }
+        if was_running {
+            let path = old_path.clone();
+            rollback.push("restart the original stack", move || DockerManager::start_project(&path).map(|_| ()));
+        }
+
+        match fs::rename(&old_path, new_path) {
+            Ok(()) => {
+                let (from, to) = (new_path.to_path_buf(), PathBuf::from(&old_path));
+                rollback.push("move the directory back", move || {
+                    fs::rename(&from, &to).map_err(|e| format!("Failed to move {} back: {}", from.display(), e))
+                });
+            }
+            Err(e) if Self::crosses_devices(&e) => {
+                // The original stays until everything else worked, so undoing only removes the copy
+                let copy = new_path.to_path_buf();
+                rollback.push("remove the copied directory", move || {
+                    fs::remove_dir_all(&copy).map_err(|e| format!("Failed to remove {}: {}", copy.display(), e))
+                });
+                Self::copy_dir(Path::new(&old_path), new_path)?;
+            }
+            Err(e) => return Err(format!("Failed to move {} to {}: {}", old_path, new_path.display(), e)),
+        }
+
+        let snapshot = Self::snapshot(new_path);
+        {
+            let path = new_path.to_path_buf();
+            rollback.push("restore rewritten files", move || Self::restore(&path, snapshot));
+        }
+
+        let mut relocated = Project {
+            name: new_name.to_string(),
+            path: new_path.to_string_lossy().to_string(),
+            status: ProjectStatus::Stopped,
+            status_message: None,
+            updated_at: Utc::now(),
+            ..project.clone()
+        };
+        // Volumes keep the data under the old database name
+        relocated.config.database_name = Some(project.database_name().to_string()).filter(|db| db != new_name);
+
+        if relocated.name != project.name {
+            ProjectManager::retarget_project_files(&relocated, project, templates_path)?;
+            if new_path.join(PROJECT_CERTS_DIR).exists() {
+                CertificateManager::issue_for_project(&relocated.path, &relocated.name)?;
+            }
+        }
+        ProjectManager::save_project(&relocated)?;
+
+        if DockerManager::compose_project_name(&old_path) != DockerManager::compose_project_name(&relocated.path) {
+            // Leftovers under the new name would be taken over by compose, and removed on rollback
+            if !DockerManager::project_volumes(&relocated.path)?.is_empty() {
+                return Err(format!("Docker volumes for '{}' already exist", DockerManager::compose_project_name(&relocated.path)));
+            }
+            {
+                let path = relocated.path.clone();
+                rollback.push("remove the migrated volumes", move || DockerManager::remove_volumes(&DockerManager::project_volumes(&path)?));
+            }
+            DockerManager::copy_volumes(&old_path, &relocated.path)?;
+        }
+
+        if !was_running {
+            return Ok(relocated);
+        }
+
+        {
+            let path = relocated.path.clone();
+            rollback.push("stop the relocated stack", move || DockerManager::stop_project(&path).map(|_| ()));
+        }
+        DockerManager::start_project(&relocated.path).map_err(|e| format!("Failed to restart {}: {}", relocated.name, e))?;
+
+        let relocated = Project {
+            status: ProjectStatus::Running,
+            ..relocated
+        };
+        ProjectManager::save_project(&relocated)?;
+        Ok(relocated)
+    }
+
+    /// Whether `error` comes from renaming to another filesystem.
+    fn crosses_devices(error: &io::Error) -> bool {
+        // EXDEV, or ERROR_NOT_SAME_DEVICE on Windows
+        let code = if cfg!(windows) { 17 } else { 18 };
+        error.raw_os_error() == Some(code)
+    }
+
+    /// Copies the directory `source` to `target`, keeping symlinks as links.
+    fn copy_dir(source: &Path, target: &Path) -> Result<(), String> {
+        for entry in WalkDir::new(source) {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+            let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+            let destination = target.join(relative);
+            let result = if entry.file_type().is_dir() {
+                fs::create_dir_all(&destination)
+            } else if entry.file_type().is_symlink() {
+                Self::copy_link(entry.path(), &destination)
+            } else {
+                fs::copy(entry.path(), &destination).map(|_| ())
+            };
+            result.map_err(|e| format!("Failed to copy {}: {}", relative.display(), e))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn copy_link(link: &Path, destination: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(fs::read_link(link)?, destination)
+    }
+
+    #[cfg(not(unix))]
+    fn copy_link(link: &Path, destination: &Path) -> io::Result<()> {
+        if link.is_dir() {
+            fs::create_dir_all(destination)
+        } else {
+            fs::copy(link, destination).map(|_| ())
+        }
+    }
+
+    fn snapshot(project_path: &Path) -> Vec<(PathBuf, Option<Vec<u8>>)> {
+        // Regenerating a file also replaces its generated copy, the base of later merges
+        let generated = RENAMED_FILES.iter().map(|file| Path::new(GENERATED_DIR).join(file));
+        RENAMED_FILES
+            .iter()
+            .map(PathBuf::from)
+            .chain(generated)
+            .map(|file| {
+                let path = project_path.join(file);
+                let content = fs::read(&path).ok();
+                (path, content)
+            })
+            .collect()
+    }
+
+    fn restore(project_path: &Path, snapshot: Vec<(PathBuf, Option<Vec<u8>>)>) -> Result<(), String> {
+        for (path, content) in snapshot {
+            let result = match content {
+                Some(content) => fs::write(&path, content),
+                None if path.exists() => fs::remove_file(&path),
+                None => Ok(()),
+            };
+            result.map_err(|e| format!("Failed to restore {}: {}", path.strip_prefix(project_path).unwrap_or(&path).display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[cfg(unix)]
+    #[test]
+    fn copying_a_project_keeps_files_modes_and_links() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("godmode-relocate-{}", Uuid::new_v4()));
+        let (source, target) = (root.join("shop"), root.join("moved"));
+        fs::create_dir_all(source.join("src/node_modules/.bin")).unwrap();
+        fs::write(source.join("Makefile"), "up:\n").unwrap();
+        fs::write(source.join("docker-entrypoint.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(source.join("docker-entrypoint.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("../vite/bin/vite.js", source.join("src/node_modules/.bin/vite")).unwrap();
+
+        ProjectRelocator::copy_dir(&source, &target).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("Makefile")).unwrap(), "up:\n");
+        let mode = fs::metadata(target.join("docker-entrypoint.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(fs::read_link(target.join("src/node_modules/.bin/vite")).unwrap(), Path::new("../vite/bin/vite.js"));
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn only_cross_device_errors_fall_back_to_copying() {
+        let code = if cfg!(windows) { 17 } else { 18 };
+        assert!(ProjectRelocator::crosses_devices(&io::Error::from_raw_os_error(code)));
+        assert!(!ProjectRelocator::crosses_devices(&io::Error::from(io::ErrorKind::PermissionDenied)));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::state::{PortConfig, Settings};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A migration upgrades a document from one schema version to the next.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+// Index `n` upgrades version `n` to `n + 1`; files without a
+// `schema_version` predate versioning and are version 0
+const PROJECT_MIGRATIONS: &[Migration] = &[project_v0_to_v1];
+const SETTINGS_MIGRATIONS: &[Migration] = &[settings_v0_to_v1];
+
+pub const PROJECT_SCHEMA_VERSION: u32 = PROJECT_MIGRATIONS.len() as u32;
+pub const SETTINGS_SCHEMA_VERSION: u32 = SETTINGS_MIGRATIONS.len() as u32;
+
+static DIAGNOSTICS: Mutex<Vec<LoadDiagnostic>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticKind {
+    /// The file could not be loaded and was skipped.
+    Failed,
+    /// The file was upgraded to the current schema and rewritten.
+    Migrated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadDiagnostic {
+    pub path: String,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// Copy of the file as it was before it was rewritten.
+    pub backup_path: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Reads a project file, upgrading it to the current schema first.
+pub fn read_project_file(path: &Path) -> Result<crate::state::Project, String> {
+    read_document(path, PROJECT_MIGRATIONS)
+}
+
+/// Parses a project file from elsewhere, e.g. an archive, upgrading it to
+/// the current schema in memory. Nothing is written or recorded.
+pub fn parse_project(content: &str) -> Result<crate::state::Project, String> {
+    let mut value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    migrate_value(&mut value, PROJECT_MIGRATIONS).map(|(project, _)| project)
+}
+
+/// Reads the settings file, upgrading it to the current schema first.
+pub fn read_settings_file(path: &Path) -> Result<Settings, String> {
+    read_document(path, SETTINGS_MIGRATIONS)
+}
+
+/// What happened to each file loaded since startup: the ones that failed
+/// to load and why, and the ones that were migrated.
+pub fn diagnostics() -> Vec<LoadDiagnostic> {
+    DIAGNOSTICS.lock().unwrap().clone()
+}
+
+/// Parses `path` and runs the migrations from its version up to the
+/// current one. A migrated file is backed up as `<name>.v<N>.bak` and
+/// rewritten. Failures are recorded for the diagnostics report.
+fn read_document<T: DeserializeOwned>(path: &Path, migrations: &[Migration]) -> Result<T, String> {
+    let result = migrate_document(path, migrations);
+    let mut diagnostics = DIAGNOSTICS.lock().unwrap();
+    // Only the latest failure of a file is kept
+    diagnostics.retain(|d| !(d.path == path.to_string_lossy() && matches!(d.kind, DiagnosticKind::Failed)));
+
+    match result {
+        Ok((document, migration)) => {
+            if let Some(migration) = migration {
+                diagnostics.push(migration);
+            }
+            Ok(document)
+        }
+        Err(e) => {
+            println!("[Schema] Failed to load {}: {}", path.display(), e);
+            diagnostics.push(LoadDiagnostic {
+                path: path.to_string_lossy().to_string(),
+                kind: DiagnosticKind::Failed,
+                message: e.clone(),
+                backup_path: None,
+                at: Utc::now(),
+            });
+            Err(e)
+        }
+    }
+}
+
+fn migrate_document<T: DeserializeOwned>(path: &Path, migrations: &[Migration]) -> Result<(T, Option<LoadDiagnostic>), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut value: Value = serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let (parsed, version) = migrate_value(&mut value, migrations)?;
+    let current = migrations.len() as u32;
+    if version == current {
+        return Ok((parsed, None));
+    }
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let backup = path.with_file_name(format!("{}.v{}.bak", file_name, version));
+    if !backup.exists() {
+        fs::copy(path, &backup).map_err(|e| format!("Failed to back up before migrating: {}", e))?;
+    }
+    let migrated = serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to serialize migrated file: {}", e))?;
+    fs::write(path, migrated).map_err(|e| format!("Failed to write migrated file: {}", e))?;
+
+    println!("[Schema] Migrated {} from schema {} to {}", path.display(), version, current);
+    Ok((
+        parsed,
+        Some(LoadDiagnostic {
+            path: path.to_string_lossy().to_string(),
+            kind: DiagnosticKind::Migrated,
+            message: format!("Upgraded from schema {} to {}", version, current),
+            backup_path: Some(backup.to_string_lossy().to_string()),
+            at: Utc::now(),
+        }),
+    ))
+}
+
+/// Runs the migrations on `value` from its version up to the current one.
+/// Returns the parsed document and the version it had.
+fn migrate_value<T: DeserializeOwned>(value: &mut Value, migrations: &[Migration]) -> Result<(T, u32), String> {
+    let document = value.as_object_mut().ok_or("Expected a JSON object")?;
+
+    let current = migrations.len() as u32;
+    let version = document.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > current {
+        return Err(format!(
+            "Written by a newer version of Laravel GodMode (schema {}, this version reads up to {})",
+            version, current
+        ));
+    }
+
+    for (step, migration) in migrations.iter().enumerate().skip(version as usize) {
+        migration(document).map_err(|e| format!("Migration to schema {} failed: {}", step + 1, e))?;
+    }
+    document.insert("schema_version".to_string(), json!(current));
+
+    let parsed = serde_json::from_value(value.clone()).map_err(|e| format!("Invalid contents: {}", e))?;
+    Ok((parsed, version))
+}
+
+/// Adds every key of `defaults` missing from `target`, recursing into objects.
+fn fill_defaults(target: &mut Map<String, Value>, defaults: &Value) {
+    let Some(defaults) = defaults.as_object() else {
+        return;
+    };
+    for (key, default) in defaults {
+        match target.get_mut(key) {
+            None | Some(Value::Null) if !default.is_null() => {
+                target.insert(key.clone(), default.clone());
+            }
+            Some(Value::Object(nested)) => fill_defaults(nested, default),
+            _ => {}
+        }
+    }
+}
+
+fn object_mut<'a>(document: &'a mut Map<String, Value>, key: &str) -> Result<&'a mut Map<String, Value>, String> {
+    let entry = document.entry(key.to_string()).or_insert_with(|| json!({}));
+    if entry.is_null() {
+        *entry = json!({});
+    }
+    entry.as_object_mut().ok_or_else(|| format!("'{}' is not an object", key))
+}
+
+/// Projects from before versioning may lack services, ports and flags that
+/// were added later, or carry a status that no longer exists.
+fn project_v0_to_v1(document: &mut Map<String, Value>) -> Result<(), String> {
+    for key in ["id", "name", "path"] {
+        if !document.get(key).map(Value::is_string).unwrap_or(false) {
+            return Err(format!("Missing '{}'", key));
+        }
+    }
+
+    let now = json!(Utc::now());
+    fill_defaults(
+        document,
+        &json!({
+            // The only template before others were added
+            "template": "laravel",
+            "created_at": now,
+            "updated_at": now,
+        }),
+    );
+
+    let config = object_mut(document, "config")?;
+    fill_defaults(
+        config,
+        &json!({
+            "node_version": "20",
+            "install_bun": false,
+            "install_pnpm": false,
+            "install_yarn": false,
+            "install_laravel": false,
+            "services": { "mysql": false, "redis": false, "phpmyadmin": false, "mailhog": false, "nginx": false },
+            "ports": PortConfig::default(),
+        }),
+    );
+
+    let known = ["running", "stopped", "error", "building", "starting", "stopping"];
+    let status = document.get("status").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+    let status = if known.contains(&status.as_str()) { status } else { "stopped".to_string() };
+    document.insert("status".to_string(), json!(status));
+
+    Ok(())
+}
+
+/// Settings from before versioning may lack sections added later.
+fn settings_v0_to_v1(document: &mut Map<String, Value>) -> Result<(), String> {
+    let defaults = serde_json::to_value(Settings::default()).map_err(|e| e.to_string())?;
+    fill_defaults(document, &defaults);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_from_before_versioning_are_upgraded() {
+        let project = parse_project(r#"{ "id": "1", "name": "shop", "path": "/tmp/shop", "status": "Unknown" }"#).unwrap();
+
+        assert_eq!(project.schema_version, PROJECT_SCHEMA_VERSION);
+        assert_eq!(project.template, "laravel");
+        assert_eq!(project.status, crate::state::ProjectStatus::Stopped);
+    }
+
+    #[test]
+    fn projects_from_a_newer_version_are_rejected() {
+        let content = json!({ "schema_version": PROJECT_SCHEMA_VERSION + 1, "id": "1", "name": "shop", "path": "/tmp/shop" });
+
+        let error = parse_project(&content.to_string()).unwrap_err();
+        assert!(error.contains("newer version"), "{}", error);
+    }
+}
@@ -1,9 +1,19 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use crate::jobs::JobRegistry;
+use crate::logs::LogStreamManager;
+use crate::metrics::MetricsSampler;
+use crate::registry::ProjectRegistry;
+use crate::terminal::TerminalManager;
+use crate::tinker::TinkerManager;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
+    /// Version of the `.godmode.json` layout, see `schema.rs`.
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub template: String,
@@ -12,6 +22,16 @@ pub struct Project {
     pub updated_at: DateTime<Utc>,
     pub config: ProjectConfig,
     pub status: ProjectStatus,
+    /// Why the project is in its current status, e.g. the unhealthy service on `Error`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<String>,
+}
+
+impl Project {
+    /// The database holding the project's data.
+    pub fn database_name(&self) -> &str {
+        self.config.database_name.as_deref().unwrap_or(&self.name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +45,10 @@ pub struct ProjectConfig {
     pub install_laravel: bool,
     pub services: ServiceConfig,
     pub ports: PortConfig,
+    /// Database the project's data lives in, when it is not named after
+    /// the project, as after a rename or a clone with its data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +68,13 @@ pub struct PortConfig {
     pub redis: u16,
     pub phpmyadmin: u16,
     pub mailhog: u16,
+    /// Host port the project's web server serves HTTPS on.
+    #[serde(default = "default_https_port")]
+    pub https: u16,
+}
+
+fn default_https_port() -> u16 {
+    8443
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -66,6 +97,93 @@ pub struct ServiceStatus {
     pub health: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerStats {
+    pub name: String,
+    pub service: Option<String>,
+    pub container_id: String,
+    pub cpu_percent: f64,
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub memory_percent: f64,
+    pub network_rx: u64,
+    pub network_tx: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ContainerStats {
+    /// Builds a sample from a `GET /containers/{id}/stats` response, using
+    /// the same formulas as `docker stats`.
+    pub fn from_engine(container: &crate::engine::ContainerSummary, stats: &serde_json::Value) -> Self {
+        let cpu_total = stats["cpu_stats"]["cpu_usage"]["total_usage"].as_f64().unwrap_or(0.0);
+        let precpu_total = stats["precpu_stats"]["cpu_usage"]["total_usage"].as_f64().unwrap_or(0.0);
+        let system = stats["cpu_stats"]["system_cpu_usage"].as_f64().unwrap_or(0.0);
+        let presystem = stats["precpu_stats"]["system_cpu_usage"].as_f64().unwrap_or(0.0);
+        let online_cpus = stats["cpu_stats"]["online_cpus"]
+            .as_f64()
+            .or_else(|| stats["cpu_stats"]["cpu_usage"]["percpu_usage"].as_array().map(|a| a.len() as f64))
+            .unwrap_or(1.0);
+
+        let cpu_delta = cpu_total - precpu_total;
+        let system_delta = system - presystem;
+        let cpu_percent = if cpu_delta > 0.0 && system_delta > 0.0 {
+            cpu_delta / system_delta * online_cpus * 100.0
+        } else {
+            0.0
+        };
+
+        // Page cache is reported separately by cgroup v1 ("cache") and v2 ("inactive_file")
+        let memory = &stats["memory_stats"];
+        let cache = memory["stats"]["inactive_file"]
+            .as_u64()
+            .or_else(|| memory["stats"]["cache"].as_u64())
+            .unwrap_or(0);
+        let memory_usage = memory["usage"].as_u64().unwrap_or(0).saturating_sub(cache);
+        let memory_limit = memory["limit"].as_u64().unwrap_or(0);
+        let memory_percent = if memory_limit > 0 {
+            memory_usage as f64 / memory_limit as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        let (mut network_rx, mut network_tx) = (0, 0);
+        if let Some(networks) = stats["networks"].as_object() {
+            for net in networks.values() {
+                network_rx += net["rx_bytes"].as_u64().unwrap_or(0);
+                network_tx += net["tx_bytes"].as_u64().unwrap_or(0);
+            }
+        }
+
+        let (mut block_read, mut block_write) = (0, 0);
+        if let Some(entries) = stats["blkio_stats"]["io_service_bytes_recursive"].as_array() {
+            for entry in entries {
+                match entry["op"].as_str().map(|s| s.to_lowercase()).as_deref() {
+                    Some("read") => block_read += entry["value"].as_u64().unwrap_or(0),
+                    Some("write") => block_write += entry["value"].as_u64().unwrap_or(0),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            name: container.name(),
+            service: container.service().map(|s| s.to_string()),
+            container_id: container.id.chars().take(12).collect(),
+            cpu_percent,
+            memory_usage,
+            memory_limit,
+            memory_percent,
+            network_rx,
+            network_tx,
+            block_read,
+            block_write,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SupervisorProgram {
     pub name: String,
@@ -85,24 +203,130 @@ pub struct SupervisorStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    /// Version of the `settings.json` layout, see `schema.rs`.
+    #[serde(default)]
+    pub schema_version: u32,
     pub projects_path: String,
     pub auto_start_projects: bool,
     pub preferred_editor: String,
     pub default_php_version: String,
     pub default_node_version: String,
     pub theme: String,
+    #[serde(default)]
+    pub compose_backend: Option<ComposeBackend>,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    #[serde(default)]
+    pub hosts: HostsSettings,
+    /// Shared templates directory, e.g. on a network drive, searched after
+    /// the user's own templates and before packs and the bundled ones.
+    #[serde(default)]
+    pub team_templates_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostsSettings {
+    /// Keep the hosts file in step with the projects automatically.
+    pub managed: bool,
+    /// Hosts file to manage instead of the system one.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxySettings {
+    pub enabled: bool,
+    /// Host port the shared proxy listens on for HTTP.
+    pub http_port: u16,
+    /// Host port the shared proxy listens on for HTTPS.
+    #[serde(default = "default_proxy_https_port")]
+    pub https_port: u16,
+}
+
+fn default_proxy_https_port() -> u16 {
+    443
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_port: 80,
+            https_port: 443,
+        }
+    }
+}
+
+/// Which compose implementation to drive for stack-level operations.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ComposeBackend {
+    /// `docker compose` (Compose v2 CLI plugin)
+    DockerPlugin,
+    /// `docker-compose` (standalone binary, v1 or v2)
+    Standalone,
+    /// `podman compose`
+    Podman,
+}
+
+impl ComposeBackend {
+    /// Probe order used when detecting the available backend.
+    pub const ALL: [ComposeBackend; 3] = [ComposeBackend::DockerPlugin, ComposeBackend::Standalone, ComposeBackend::Podman];
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            ComposeBackend::DockerPlugin => "docker",
+            ComposeBackend::Standalone => "docker-compose",
+            ComposeBackend::Podman => "podman",
+        }
+    }
+
+    pub fn base_args(&self) -> &'static [&'static str] {
+        match self {
+            ComposeBackend::DockerPlugin | ComposeBackend::Podman => &["compose"],
+            ComposeBackend::Standalone => &[],
+        }
+    }
+
+    /// The shell command line, e.g. "docker compose".
+    pub fn command_line(&self) -> String {
+        let mut parts = vec![self.program()];
+        parts.extend_from_slice(self.base_args());
+        parts.join(" ")
+    }
+
+    /// The CLI used for engine-level commands (`docker` or `podman`).
+    pub fn engine_program(&self) -> &'static str {
+        match self {
+            ComposeBackend::Podman => "podman",
+            _ => "docker",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerRuntimeInfo {
+    pub engine: Option<String>,
+    pub engine_version: Option<String>,
+    pub compose_backend: Option<ComposeBackend>,
+    pub compose_command: Option<String>,
+    pub compose_version: Option<String>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         let home = dirs::home_dir().unwrap_or_default();
         Self {
+            schema_version: crate::schema::SETTINGS_SCHEMA_VERSION,
             projects_path: home.join("Documents").join("laravel-godmode").join("projects").to_string_lossy().to_string(),
             auto_start_projects: false,
             preferred_editor: "code".to_string(),
             default_php_version: "8.4".to_string(),
             default_node_version: "18".to_string(),
             theme: "dark".to_string(),
+            compose_backend: None,
+            proxy: ProxySettings::default(),
+            hosts: HostsSettings::default(),
+            team_templates_path: None,
         }
     }
 }
@@ -118,6 +342,7 @@ impl Default for ProjectConfig {
             install_laravel: true,
             services: ServiceConfig::default(),
             ports: PortConfig::default(),
+            database_name: None,
         }
     }
 }
@@ -143,6 +368,7 @@ impl Default for PortConfig {
             redis: 6379,
             phpmyadmin: 8080,
             mailhog: 8025,
+            https: 8443,
         }
     }
 }
@@ -151,6 +377,12 @@ pub struct AppState {
     pub projects: HashMap<String, Project>,
     pub settings: Settings,
     pub templates_path: String,
+    pub jobs: Arc<JobRegistry>,
+    pub registry: Arc<ProjectRegistry>,
+    pub log_streams: Arc<LogStreamManager>,
+    pub metrics: Arc<MetricsSampler>,
+    pub terminals: Arc<TerminalManager>,
+    pub tinker: Arc<TinkerManager>,
 }
 
 impl AppState {
@@ -165,6 +397,12 @@ impl AppState {
             projects: HashMap::new(),
             settings: Settings::default(),
             templates_path,
+            jobs: Arc::new(JobRegistry::new()),
+            registry: ProjectRegistry::global(),
+            log_streams: Arc::new(LogStreamManager::new()),
+            metrics: Arc::new(MetricsSampler::new()),
+            terminals: Arc::new(TerminalManager::new()),
+            tinker: Arc::new(TinkerManager::new()),
         }
     }
 }
@@ -1,29 +1,264 @@
+use crate::catalog::{TemplateCatalog, TemplateRoot};
+use crate::docker::DockerManager;
+use crate::project::ProjectManager;
+use crate::proxy::ProxyManager;
+use crate::regenerate::record_generated;
+use crate::schema::PROJECT_SCHEMA_VERSION;
 use crate::state::{Project, ProjectConfig, ProjectStatus, PortConfig, ServiceConfig};
-use handlebars::Handlebars;
+use crate::tls::CertificateManager;
+use handlebars::{handlebars_helper, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, Template, TemplateError};
+use serde::Deserialize;
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 use chrono::Utc;
 
+/// Values `random_secret` handed out for a project, relative to the project,
+/// so rendering it again keeps its session keys and passwords.
+const SECRETS_FILE: &str = ".godmode/secrets.json";
+
+/// Renders stubs with Handlebars. Besides the built-in helpers (`if`,
+/// `each`, `eq`, `and`, `or`, `not`, ...) stubs can use `random_secret`
+/// and `slugify`, and `{{> name}}` includes `name.hbs` from the template's
+/// `partials` folder or a shared `_partials` one in any template root.
 pub struct TemplateEngine {
     handlebars: Handlebars<'static>,
+    /// Where the template was found, resolved once for all renders.
+    template_dir: PathBuf,
+    template_type: String,
+    secrets: Arc<Mutex<Secrets>>,
+    /// What stubs call compose with, e.g. "docker compose".
+    compose_command: String,
+    /// Whether the secrets were loaded from a project's `SECRETS_FILE`.
+    saved_secrets: bool,
+}
+
+/// Values handed out by `random_secret`, by stub and call. Rendering a stub
+/// again with the same engine reuses them, so two renders only differ where
+/// their contexts do.
+#[derive(Default)]
+struct Secrets {
+    calls: HashMap<String, usize>,
+    values: HashMap<(String, usize), String>,
 }
 
 impl TemplateEngine {
-    pub fn new() -> Self {
+    pub fn new(templates_path: &str, template_type: &str) -> Result<Self, String> {
+        // Rendering never probes for a backend, so previews have no side effects
+        let compose_command = DockerManager::cached_compose_backend()
+            .map(|backend| backend.command_line())
+            .unwrap_or_else(|| "docker compose".to_string());
+        Self::with_context(&TemplateCatalog::roots(templates_path), templates_path, template_type, &compose_command)
+    }
+
+    /// An engine that only looks for templates and partials in `roots`, as
+    /// listed by `TemplateCatalog::roots`, and gives stubs `compose_command`.
+    pub fn with_context(roots: &[TemplateRoot], templates_path: &str, template_type: &str, compose_command: &str) -> Result<Self, String> {
+        let template_dir = TemplateCatalog::find_template(roots, templates_path, template_type);
+        let secrets = Arc::new(Mutex::new(Secrets::default()));
         let mut handlebars = Handlebars::new();
         handlebars.set_strict_mode(false);
-        Self { handlebars }
+        // Stubs are config files, not HTML
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.register_helper("random_secret", Box::new(RandomSecretHelper { secrets: Arc::clone(&secrets) }));
+        handlebars.register_helper("slugify", Box::new(slugify_helper));
+
+        // Later registrations win, so the template's own partials go last
+        for dir in TemplateCatalog::partial_dirs(roots, &template_dir) {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.extension().and_then(|e| e.to_str()) != Some("hbs") {
+                    continue;
+                }
+                let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read partial {}: {}", path.display(), e))?;
+                handlebars
+                    .register_partial(&name, content)
+                    .map_err(|e| Self::syntax_error(&format!("partial {}", path.display()), &e))?;
+            }
+        }
+
+        Ok(Self {
+            handlebars,
+            template_dir,
+            template_type: template_type.to_string(),
+            secrets,
+            compose_command: compose_command.to_string(),
+            saved_secrets: false,
+        })
+    }
+
+    /// An engine for rendering `project` again, which hands out the
+    /// secrets saved with it instead of new ones.
+    pub fn for_project(templates_path: &str, project: &Project) -> Result<Self, String> {
+        Ok(Self::new(templates_path, &project.template)?.with_saved_secrets(Path::new(&project.path)))
     }
 
-    pub fn render_template(&self, template_content: &str, data: &serde_json::Value) -> Result<String, String> {
-        self.handlebars
-            .render_template(template_content, data)
+    /// Hands out the secrets saved with the project at `project_path`, if any.
+    pub fn with_saved_secrets(mut self, project_path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(project_path.join(SECRETS_FILE)) else {
+            return self;
+        };
+        match serde_json::from_str::<HashMap<String, Vec<String>>>(&content) {
+            Ok(stored) => {
+                let mut secrets = self.secrets.lock().unwrap();
+                for (stub, values) in stored {
+                    for (call, value) in values.into_iter().enumerate() {
+                        secrets.values.insert((stub.clone(), call), value);
+                    }
+                }
+                drop(secrets);
+                self.saved_secrets = true;
+            }
+            // Treated like a project from before secrets were saved
+            Err(e) => println!("[Template] Ignoring invalid {}: {}", SECRETS_FILE, e),
+        }
+        self
+    }
+
+    /// Whether the secrets handed out so far are the project's own. Projects
+    /// from before secrets were saved only have theirs in their files, so
+    /// renders get new ones wherever a template uses `random_secret`.
+    pub fn knows_project_secrets(&self) -> bool {
+        self.saved_secrets || self.secrets.lock().unwrap().values.is_empty()
+    }
+
+    /// Keeps the secrets handed out so far, for later renders of the project at `project_path`.
+    pub fn save_secrets(&self, project_path: &Path) {
+        let secrets = self.secrets.lock().unwrap();
+        let mut keys: Vec<&(String, usize)> = secrets.values.keys().collect();
+        keys.sort();
+        let mut stored: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for key in keys {
+            stored.entry(key.0.as_str()).or_default().push(&secrets.values[key]);
+        }
+
+        let path = project_path.join(SECRETS_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        let result = serde_json::to_string_pretty(&stored)
             .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("[Template] Failed to save {}: {}", SECRETS_FILE, e);
+        }
+    }
+
+    /// Renders, in memory, every file the template produces for a project
+    /// called `name` with `config`.
+    pub fn render_project(&mut self, name: &str, config: &ProjectConfig) -> Result<Vec<RenderedFile>, String> {
+        let template_dir = self.template_dir.clone();
+        let stubs_dir = template_dir.join("stubs");
+        println!("[Template] Template dir: {:?}", template_dir);
+
+        if !template_dir.exists() {
+            println!("[Template] ERROR: Template directory does not exist!");
+            return Err(format!("Template '{}' not found at {:?}", self.template_type, template_dir));
+        }
+
+        let template_data = prepare_template_data(name, config, &self.compose_command)?;
+        let stub_mappings = stub_mappings(&read_template_config(&template_dir, &self.template_type)?, config)?;
+        println!("[Template] Processing {} stub files", stub_mappings.len());
+
+        let mut files = Vec::new();
+        for mapping in stub_mappings {
+            let stub_path = stubs_dir.join(&mapping.stub);
+            if !stub_path.exists() {
+                println!("[Template] WARN: Stub not found: {:?}", stub_path);
+                continue;
+            }
+
+            let stub_content = fs::read_to_string(&stub_path)
+                .map_err(|e| format!("Failed to read stub {}: {}", mapping.stub, e))?;
+            files.push(RenderedFile {
+                content: self.render_stub(&mapping.stub, &stub_content, &template_data)?,
+                path: mapping.output,
+                mode: mapping.mode,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Renders the content of `stub_name`. Errors name the stub and the line.
+    pub fn render_stub(&mut self, stub_name: &str, content: &str, data: &serde_json::Value) -> Result<String, String> {
+        let template = Template::compile_with_name(content, stub_name.to_string()).map_err(|e| Self::syntax_error(stub_name, &e))?;
+        self.handlebars.register_template(stub_name, template);
+        self.secrets.lock().unwrap().calls.remove(stub_name);
+
+        self.handlebars.render(stub_name, data).map_err(|e| {
+            let location = match (e.template_name.as_deref(), e.line_no) {
+                // The error is in a partial the stub includes
+                (Some(partial), Some(line)) if partial != stub_name => format!("{} (partial {}) line {}", stub_name, partial, line),
+                (_, Some(line)) => format!("{} line {}", stub_name, line),
+                _ => stub_name.to_string(),
+            };
+            format!("Failed to render {}: {}", location, e.reason())
+        })
+    }
+
+    fn syntax_error(name: &str, error: &TemplateError) -> String {
+        match error.pos() {
+            Some((line, _)) => format!("Invalid template syntax in {} line {}: {}", name, line, error.reason()),
+            None => format!("Invalid template syntax in {}: {}", name, error.reason()),
+        }
     }
 }
 
+/// `{{random_secret}}` or `{{random_secret 64}}`: random letters and digits, 32 by default.
+struct RandomSecretHelper {
+    secrets: Arc<Mutex<Secrets>>,
+}
+
+impl HelperDef for RandomSecretHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let length = helper.param(0).and_then(|p| p.value().as_u64()).unwrap_or(32) as usize;
+        let stub = rc.get_root_template_name().cloned().unwrap_or_default();
+
+        let mut secrets = self.secrets.lock().unwrap();
+        let call = secrets.calls.entry(stub.clone()).or_insert(0);
+        let key = (stub, *call);
+        *call += 1;
+        let secret = secrets.values.entry(key).or_insert_with(|| random_secret(length)).clone();
+
+        out.write(&secret)?;
+        Ok(())
+    }
+}
+
+fn random_secret(length: usize) -> String {
+    let mut secret = String::new();
+    while secret.len() < length {
+        secret.push_str(&Uuid::new_v4().simple().to_string());
+    }
+    secret.truncate(length);
+    secret
+}
+
+handlebars_helper!(slugify_helper: |value: str| slugify(value));
+
+/// Lowercase letters and digits joined by single dashes, e.g. "my-app" for "My App!".
+fn slugify(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 pub fn create_project_from_template(
     name: &str,
     template_type: &str,
@@ -33,6 +268,10 @@ pub fn create_project_from_template(
 ) -> Result<Project, String> {
     println!("[Template] Starting project creation: {}", name);
 
+    // Rendered up front, so a broken stub leaves nothing behind
+    let mut engine = TemplateEngine::new(templates_path, template_type)?;
+    let files = engine.render_project(name, &config)?;
+
     let project_id = Uuid::new_v4().to_string();
     let project_path = Path::new(base_path).join(name);
     println!("[Template] Project path: {:?}", project_path);
@@ -45,52 +284,23 @@ pub fn create_project_from_template(
     fs::create_dir_all(project_path.join("docker")).map_err(|e| format!("Failed to create docker directory: {}", e))?;
     println!("[Template] Created subdirectories");
 
-    // Load template config
-    let template_dir = Path::new(templates_path).join(template_type);
-    let stubs_dir = template_dir.join("stubs");
-    println!("[Template] Template dir: {:?}", template_dir);
-    println!("[Template] Stubs dir: {:?}", stubs_dir);
-
-    if !template_dir.exists() {
-        println!("[Template] ERROR: Template directory does not exist!");
-        return Err(format!("Template '{}' not found at {:?}", template_type, template_dir));
-    }
-
-    // Prepare template data
-    let template_data = prepare_template_data(name, &config);
-    let _engine = TemplateEngine::new();
-
-    // Process each stub file
-    let stub_mappings = get_stub_mappings(template_type);
-    println!("[Template] Processing {} stub files", stub_mappings.len());
-
-    for (stub_name, output_path) in stub_mappings {
-        let stub_path = stubs_dir.join(stub_name);
-        println!("[Template] Processing stub: {} -> {}", stub_name, output_path);
-
-        if stub_path.exists() {
-            let stub_content = fs::read_to_string(&stub_path)
-                .map_err(|e| format!("Failed to read stub {}: {}", stub_name, e))?;
-
-            // Replace handlebars-style placeholders with actual values
-            let rendered = render_stub(&stub_content, &template_data);
-
-            let output_file = project_path.join(output_path);
-            if let Some(parent) = output_file.parent() {
-                fs::create_dir_all(parent).ok();
-            }
-
-            fs::write(&output_file, rendered)
-                .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
-            println!("[Template] Written: {}", output_path);
-        } else {
-            println!("[Template] WARN: Stub not found: {:?}", stub_path);
+    for file in &files {
+        // Imported code keeps its own files, only the docker scaffolding is generated
+        if file.path.starts_with("src/") && project_path.join(&file.path).exists() {
+            println!("[Template] Keeping existing: {}", file.path);
+            continue;
         }
+
+        write_rendered_file(&project_path, file)?;
+        println!("[Template] Written: {}", file.path);
     }
+    record_generated(&project_path, files.iter().filter(|file| !file.path.starts_with("src/")));
+    engine.save_secrets(&project_path);
 
     let now = Utc::now();
 
-    Ok(Project {
+    let project = Project {
+        schema_version: PROJECT_SCHEMA_VERSION,
         id: project_id,
         name: name.to_string(),
         template: template_type.to_string(),
@@ -99,233 +309,235 @@ pub fn create_project_from_template(
         updated_at: now,
         config,
         status: ProjectStatus::Stopped,
-    })
-}
-
-fn prepare_template_data(name: &str, config: &ProjectConfig) -> serde_json::Value {
-    // Generate Redis service block
-    let redis_service = if config.services.redis {
-        format!(r#"
-  redis:
-    image: redis:7-alpine
-    container_name: {}_redis
-    ports:
-      - "{}:6379"
-    volumes:
-      - redis_data:/data
-    networks:
-      - {}_network
-    restart: unless-stopped
-    command: redis-server --appendonly yes
-"#, name, config.ports.redis, name)
-    } else {
-        String::new()
+        status_message: None,
     };
 
-    // Generate phpMyAdmin service block
-    let phpmyadmin_service = if config.services.phpmyadmin {
-        format!(r#"
-  phpmyadmin:
-    image: phpmyadmin:latest
-    container_name: {}_phpmyadmin
-    environment:
-      PMA_HOST: db
-      PMA_USER: root
-      PMA_PASSWORD: password
-      PMA_ARBITRARY: 1
-    ports:
-      - "{}:80"
-    depends_on:
-      db:
-        condition: service_healthy
-    networks:
-      - {}_network
-    restart: unless-stopped
-"#, name, config.ports.phpmyadmin, name)
-    } else {
-        String::new()
-    };
+    // The web server mounts the project's certificate, so it must exist before the first start
+    CertificateManager::ensure_project_certificate(&project)?;
 
-    // Generate Mailhog service block
-    let mailhog_service = if config.services.mailhog {
-        format!(r#"
-  mailhog:
-    image: mailhog/mailhog:latest
-    container_name: {}_mailhog
-    ports:
-      - "1025:1025"
-      - "{}:8025"
-    networks:
-      - {}_network
-    restart: unless-stopped
-"#, name, config.ports.mailhog, name)
-    } else {
-        String::new()
-    };
+    Ok(project)
+}
 
-    // Generate PostgreSQL service block (for JS frameworks)
-    let postgresql_service = if config.services.mysql {
-        format!(r#"
-  db:
-    image: postgres:16-alpine
-    container_name: {}_db
-    environment:
-      POSTGRES_DB: {}
-      POSTGRES_USER: postgres
-      POSTGRES_PASSWORD: password
-    ports:
-      - "{}:5432"
-    volumes:
-      - postgres_data:/var/lib/postgresql/data
-    networks:
-      - {}_network
-    restart: unless-stopped
-    healthcheck:
-      test: ["CMD-SHELL", "pg_isready -U postgres"]
-      interval: 5s
-      timeout: 5s
-      retries: 10
-"#, name, name, config.ports.db, name)
-    } else {
-        String::new()
-    };
+/// A file rendered from a template, not written yet.
+#[derive(Debug, Clone)]
+pub struct RenderedFile {
+    /// Relative to the project.
+    pub path: String,
+    pub content: String,
+    /// Permissions to give the file, e.g. "0755".
+    pub mode: Option<String>,
+}
 
-    // Generate Adminer service block (for JS frameworks)
-    let adminer_service = if config.services.phpmyadmin && config.services.mysql {
-        format!(r#"
-  adminer:
-    image: adminer:latest
-    container_name: {}_adminer
-    ports:
-      - "{}:8080"
-    depends_on:
-      - db
-    networks:
-      - {}_network
-    restart: unless-stopped
-"#, name, config.ports.phpmyadmin, name)
-    } else {
-        String::new()
-    };
+/// Renders, in memory, every file `template_type` produces for a project
+/// called `name` with `config`.
+pub fn render_template_files(name: &str, template_type: &str, templates_path: &str, config: &ProjectConfig) -> Result<Vec<RenderedFile>, String> {
+    TemplateEngine::new(templates_path, template_type)?.render_project(name, config)
+}
 
-    // Build volumes section
-    let mut volumes = vec![];
-    if config.services.mysql {
-        volumes.push("  postgres_data:");
+/// Writes `file` into the project at `project_path`, creating folders as needed.
+pub fn write_rendered_file(project_path: &Path, file: &RenderedFile) -> Result<(), String> {
+    let output_file = project_path.join(&file.path);
+    if let Some(parent) = output_file.parent() {
+        fs::create_dir_all(parent).ok();
     }
-    if config.services.redis {
-        volumes.push("  redis_data:");
+    fs::write(&output_file, &file.content).map_err(|e| format!("Failed to write {}: {}", file.path, e))?;
+
+    if let Some(mode) = &file.mode {
+        let mode = u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+            .map_err(|_| format!("Invalid mode '{}' for {}", mode, file.path))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&output_file, fs::Permissions::from_mode(mode))
+                .map_err(|e| format!("Failed to set permissions on {}: {}", file.path, e))?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
     }
-    let volumes_section = if volumes.is_empty() {
-        String::new()
-    } else {
-        format!("volumes:\n{}", volumes.join("\n"))
-    };
+    Ok(())
+}
 
-    // DB depends for JS frameworks
-    let db_depends = if config.services.mysql {
-        "db:\n        condition: service_healthy"
-    } else {
-        ""
+/// The configuration a project of `template_type` gets when nobody picks
+/// one: the default versions, required and default-on services and the
+/// default ports from the template's `config.json`.
+pub fn default_config(templates_path: &str, template_type: &str) -> Result<ProjectConfig, String> {
+    let template_config = load_template_config(templates_path, template_type)?;
+    let versions = &template_config["versions"];
+    let services = &template_config["services"];
+    let package_managers = &template_config["packageManagers"];
+
+    let enabled = |service: &str| {
+        let service = &services[service];
+        service["required"].as_bool().unwrap_or(false) || service["default"].as_bool().unwrap_or(false)
     };
+    let default_on = |manager: &str| package_managers[manager]["default"].as_bool().unwrap_or(false);
+
+    let mut ports = PortConfig::default();
+    let port_fields = [
+        ("app", &mut ports.app),
+        ("vite", &mut ports.vite),
+        ("phpmyadmin", &mut ports.phpmyadmin),
+        ("mailhog", &mut ports.mailhog),
+        ("redis", &mut ports.redis),
+        ("https", &mut ports.https),
+    ];
+    for (key, port) in port_fields {
+        if let Some(value) = template_config["ports"][key]["default"].as_u64() {
+            *port = value as u16;
+        }
+    }
+    if let Some(value) = services["mysql"]["defaultPort"].as_u64() {
+        ports.db = value as u16;
+    }
 
-    // Redis depends for JS frameworks  
-    let redis_depends = if config.services.redis {
-        "- redis"
-    } else {
-        ""
-    };
+    Ok(ProjectConfig {
+        php_version: versions["php"]["default"].as_str().map(String::from),
+        node_version: versions["node"]["default"].as_str().unwrap_or("20").to_string(),
+        install_bun: default_on("bun"),
+        install_pnpm: default_on("pnpm"),
+        install_yarn: default_on("yarn"),
+        install_laravel: false,
+        services: ServiceConfig {
+            mysql: enabled("mysql"),
+            redis: enabled("redis"),
+            phpmyadmin: enabled("phpmyadmin"),
+            mailhog: enabled("mailhog"),
+            nginx: enabled("nginx"),
+        },
+        ports,
+        database_name: None,
+    })
+}
 
-    json!({
+/// The template's `commands.setup` list, run once in the app container
+/// after the project first starts.
+pub fn setup_commands(templates_path: &str, template_type: &str) -> Result<Vec<String>, String> {
+    let template_config = load_template_config(templates_path, template_type)?;
+    Ok(template_config["commands"]["setup"]
+        .as_array()
+        .map(|commands| commands.iter().filter_map(|c| c.as_str().map(String::from)).collect())
+        .unwrap_or_default())
+}
+
+pub fn load_template_config(templates_path: &str, template_type: &str) -> Result<serde_json::Value, String> {
+    read_template_config(&TemplateCatalog::template_dir(templates_path, template_type), template_type)
+}
+
+fn read_template_config(template_dir: &Path, template_type: &str) -> Result<serde_json::Value, String> {
+    let config_path = template_dir.join("config.json");
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read template config for '{}': {}", template_type, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse template config for '{}': {}", template_type, e))
+}
+
+/// The context stubs are rendered with: the project's configuration as is
+/// (`services.redis`, `ports.app`, `php_version`, ...), its `name`,
+/// `domain` and `database_name`, and the upper-case values stubs have
+/// always used.
+fn prepare_template_data(name: &str, config: &ProjectConfig, compose_command: &str) -> Result<serde_json::Value, String> {
+    let mut data = serde_json::to_value(config).map_err(|e| format!("Failed to serialize project config: {}", e))?;
+    let values = json!({
+        "name": name,
+        "domain": ProxyManager::project_domain(name),
+        "database_name": config.database_name.as_deref().unwrap_or(name),
+        "compose_command": compose_command,
         "PROJECT_NAME": name,
+        "COMPOSE_COMMAND": compose_command,
         "PHP_VERSION": config.php_version.clone().unwrap_or("8.4".to_string()),
         "NODE_VERSION": config.node_version,
         "INSTALL_BUN": config.install_bun.to_string(),
         "INSTALL_PNPM": config.install_pnpm.to_string(),
+        "PROJECT_DOMAIN": ProxyManager::project_domain(name),
         "APP_PORT": config.ports.app,
+        "HTTPS_PORT": config.ports.https,
         "NGINX_PORT": 80,
         "VITE_PORT": config.ports.vite,
         "DB_PORT": config.ports.db,
         "REDIS_PORT": config.ports.redis,
         "PHPMYADMIN_PORT": config.ports.phpmyadmin,
         "MAILHOG_PORT": config.ports.mailhog,
-        "REDIS_DEPENDS": if config.services.redis { "\n      - redis" } else { "" },
-        "REDIS_SERVICE": redis_service,
-        "REDIS_VOLUME": if config.services.redis { "\n  redis_data:\n    driver: local" } else { "" },
-        "PHPMYADMIN_SERVICE": phpmyadmin_service,
-        "MAILHOG_SERVICE": mailhog_service,
-        "POSTGRESQL_SERVICE": postgresql_service,
-        "ADMINER_SERVICE": adminer_service,
-        "VOLUMES": volumes_section,
-        "DB_DEPENDS": db_depends,
-    })
+    });
+    if let (Some(data), Some(values)) = (data.as_object_mut(), values.as_object()) {
+        data.extend(values.clone());
+    }
+    Ok(data)
 }
 
-fn render_stub(content: &str, data: &serde_json::Value) -> String {
-    let mut result = content.to_string();
-
-    if let Some(obj) = data.as_object() {
-        for (key, value) in obj {
-            let placeholder = format!("{{{{{}}}}}", key);
-            let replacement = match value {
-                serde_json::Value::String(s) => s.clone(),
-                serde_json::Value::Number(n) => n.to_string(),
-                serde_json::Value::Bool(b) => b.to_string(),
-                _ => value.to_string(),
-            };
-            result = result.replace(&placeholder, &replacement);
-        }
-    }
+/// One file a template renders, from the `stubs` list in its `config.json`.
+#[derive(Debug, Clone, Deserialize)]
+struct StubMapping {
+    /// File in the template's `stubs` directory.
+    stub: String,
+    /// Where the rendered file goes, relative to the project.
+    output: String,
+    /// Project setting that must be on for the file to be written, e.g.
+    /// "services.redis". A leading `!` inverts it.
+    #[serde(default)]
+    when: Option<String>,
+    /// Permissions of the written file, e.g. "0755".
+    #[serde(default)]
+    mode: Option<String>,
+}
 
-    result
+/// A `stubs` entry: a bare stub name is rendered to the project root
+/// under its own name without `.stub`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StubEntry {
+    Name(String),
+    Mapping(StubMapping),
 }
 
-fn get_stub_mappings(template_type: &str) -> Vec<(&'static str, &'static str)> {
-    match template_type {
-        "laravel" => vec![
-            ("docker-compose.yml.stub", "docker-compose.yml"),
-            ("Dockerfile.stub", "Dockerfile"),
-            ("Makefile.stub", "Makefile"),
-            (".env.stub", ".env"),
-            ("php.ini.stub", "docker/php.ini"),
-            ("nginx.conf.stub", "docker/nginx.conf"),
-            ("mysql.cnf.stub", "docker/mysql.cnf"),
-            ("mysql-client.cnf.stub", "docker/mysql-client.cnf"),
-            ("supervisor.conf.stub", "docker/supervisor.conf"),
-            ("init.sql.stub", "docker/init.sql"),
-        ],
-        "nodejs" => vec![
-            ("docker-compose.yml.stub", "docker-compose.yml"),
-            ("Dockerfile.stub", "Dockerfile"),
-            ("Makefile.stub", "Makefile"),
-            (".env.stub", ".env"),
-            ("mysql.cnf.stub", "docker/mysql.cnf"),
-            ("package.json.stub", "src/package.json"),
-            ("index.js.stub", "src/index.js"),
-        ],
-        "astro" => vec![
-            ("docker-compose.yml.stub", "docker-compose.yml"),
-            ("Dockerfile.stub", "Dockerfile"),
-            ("Makefile.stub", "Makefile"),
-            (".env.stub", ".env"),
-            ("nginx.conf.stub", "docker/nginx.conf"),
-        ],
-        "nextjs" => vec![
-            ("docker-compose.yml.stub", "docker-compose.yml"),
-            ("Dockerfile.stub", "Dockerfile"),
-            ("Makefile.stub", "Makefile"),
-            (".env.stub", ".env"),
-            ("nginx.conf.stub", "docker/nginx.conf"),
-        ],
-        "nuxt" => vec![
-            ("docker-compose.yml.stub", "docker-compose.yml"),
-            ("Dockerfile.stub", "Dockerfile"),
-            ("Makefile.stub", "Makefile"),
-            (".env.stub", ".env"),
-            ("nginx.conf.stub", "docker/nginx.conf"),
-        ],
-        _ => vec![],
+/// The stubs `template_config` declares that apply to a project with `config`.
+fn stub_mappings(template_config: &serde_json::Value, config: &ProjectConfig) -> Result<Vec<StubMapping>, String> {
+    let entries: Vec<StubEntry> = match &template_config["stubs"] {
+        serde_json::Value::Null => Vec::new(),
+        stubs => serde_json::from_value(stubs.clone()).map_err(|e| format!("Invalid stubs list in template config: {}", e))?,
+    };
+    let project_config = serde_json::to_value(config).map_err(|e| format!("Failed to serialize project config: {}", e))?;
+
+    let mut mappings = Vec::new();
+    for entry in entries {
+        let mapping = match entry {
+            StubEntry::Name(stub) => StubMapping {
+                output: stub.strip_suffix(".stub").unwrap_or(&stub).to_string(),
+                stub,
+                when: None,
+                mode: None,
+            },
+            StubEntry::Mapping(mapping) => mapping,
+        };
+
+        // Templates only ever write inside the project
+        let inside = Path::new(&mapping.output).components().all(|c| matches!(c, Component::Normal(_)));
+        if mapping.output.is_empty() || !inside {
+            return Err(format!("Stub output must be a path inside the project: {}", mapping.output));
+        }
+
+        if mapping.when.as_deref().map(|when| condition_holds(when, &project_config)).unwrap_or(true) {
+            mappings.push(mapping);
+        }
     }
+    Ok(mappings)
+}
+
+/// Whether the setting at a dotted `path` into the project config is on:
+/// true, a non-empty string or a non-zero number.
+fn condition_holds(condition: &str, project_config: &serde_json::Value) -> bool {
+    let (negated, path) = match condition.trim().strip_prefix('!') {
+        Some(path) => (true, path.trim()),
+        None => (false, condition.trim()),
+    };
+    let value = path.split('.').fold(project_config, |value, key| &value[key]);
+    let set = match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Number(n) => n.as_f64() != Some(0.0),
+        _ => true,
+    };
+    set != negated
 }
 
 pub fn load_project_from_path(path: &str) -> Result<Project, String> {
@@ -343,9 +555,5 @@ pub fn load_project_from_path(path: &str) -> Result<Project, String> {
 }
 
 pub fn save_project(project: &Project) -> Result<(), String> {
-    let config_path = Path::new(&project.path).join(".godmode.json");
-    let content = serde_json::to_string_pretty(project)
-        .map_err(|e| format!("Failed to serialize project: {}", e))?;
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to save project config: {}", e))
+    ProjectManager::save_project(project)
 }
@@ -0,0 +1,254 @@
+use crate::docker::DockerManager;
+use crate::engine::{Connection, EngineClient, ExecOptions, PID_MARKER, PID_WRAPPER};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+// Used when no command is given: bash if the image has it, plain sh otherwise
+const DEFAULT_SHELL: &str = "if command -v bash >/dev/null 2>&1; then exec bash; else exec sh; fi";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TerminalOptions {
+    /// Compose service to open the session in; defaults to "app".
+    pub service: Option<String>,
+    /// Command to run instead of a login shell, e.g. ["mysql", "-uroot"].
+    #[serde(default)]
+    pub command: Vec<String>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    pub rows: Option<u16>,
+    pub cols: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalSessionInfo {
+    pub id: String,
+    pub project_id: String,
+    pub service: String,
+    pub container: String,
+    pub command: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalOutput {
+    pub session_id: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalExit {
+    pub session_id: String,
+    pub exit_code: Option<i64>,
+}
+
+struct TerminalSession {
+    info: TerminalSessionInfo,
+    container_id: String,
+    exec_id: String,
+    stdin: Mutex<Connection>,
+    // In-container PID of the session's process, once it has reported it
+    pid: Arc<Mutex<Option<String>>>,
+}
+
+impl TerminalSession {
+    fn kill(&self, client: &EngineClient) {
+        if let Some(pid) = self.pid.lock().unwrap().clone() {
+            let _ = client.kill_process_tree(&self.container_id, &pid);
+        }
+        let _ = self.stdin.lock().unwrap().shutdown();
+    }
+}
+
+/// Interactive TTY sessions into project containers, several per project.
+#[derive(Default)]
+pub struct TerminalManager {
+    sessions: Arc<Mutex<HashMap<String, TerminalSession>>>,
+}
+
+impl TerminalManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a session and streams its output as `terminal-output` events,
+    /// followed by a `terminal-exit` event when the process ends.
+    pub fn open(&self, app: &AppHandle, project_id: &str, project_path: &str, project_name: &str, options: TerminalOptions) -> Result<TerminalSessionInfo, String> {
+        let service = options.service.clone().unwrap_or_else(|| "app".to_string());
+        let client = EngineClient::from_env()?;
+        let container = DockerManager::find_service_container(&client, project_path, project_name, &service)?;
+
+        let command: Vec<String> = if options.command.is_empty() {
+            vec!["sh".to_string(), "-c".to_string(), DEFAULT_SHELL.to_string()]
+        } else {
+            options.command.clone()
+        };
+        let mut wrapped = vec!["sh", "-c", PID_WRAPPER, "sh"];
+        wrapped.extend(command.iter().map(|s| s.as_str()));
+
+        let (rows, cols) = (options.rows.unwrap_or(24), options.cols.unwrap_or(80));
+        let exec_options = ExecOptions {
+            working_dir: options.working_dir.clone(),
+            env: vec![
+                "TERM=xterm-256color".to_string(),
+                format!("LINES={}", rows),
+                format!("COLUMNS={}", cols),
+            ],
+            tty: true,
+            attach_stdin: true,
+            user: options.user.clone(),
+        };
+
+        let exec_id = client
+            .create_exec(&container.id, &wrapped, &exec_options)
+            .map_err(|e| format!("Failed to create session: {}", e))?;
+        let (stdin, mut reader) = client
+            .start_exec_attached(&exec_id, true)
+            .map_err(|e| format!("Failed to start session: {}", e))?;
+        let _ = client.resize_exec(&exec_id, rows, cols);
+
+        let session_id = Uuid::new_v4().to_string();
+        let info = TerminalSessionInfo {
+            id: session_id.clone(),
+            project_id: project_id.to_string(),
+            service,
+            container: container.name(),
+            command,
+            created_at: Utc::now(),
+        };
+        let pid = Arc::new(Mutex::new(None));
+
+        self.sessions.lock().unwrap().insert(
+            session_id.clone(),
+            TerminalSession {
+                info: info.clone(),
+                container_id: container.id.clone(),
+                exec_id: exec_id.clone(),
+                stdin: Mutex::new(stdin),
+                pid: pid.clone(),
+            },
+        );
+
+        let app = app.clone();
+        let sessions = self.sessions.clone();
+        std::thread::spawn(move || {
+            let mut header = Vec::new();
+            let mut pending = Vec::new();
+            let mut header_done = false;
+
+            while let Ok(Some((_, data))) = reader.next_frame() {
+                // The wrapper's PID line comes first; keep it out of the terminal
+                let data = if header_done {
+                    data
+                } else {
+                    header.extend_from_slice(&data);
+                    match header.iter().position(|b| *b == b'\n') {
+                        Some(end) => {
+                            header_done = true;
+                            let line = String::from_utf8_lossy(&header[..end]).to_string();
+                            match line.trim().strip_prefix(PID_MARKER) {
+                                Some(found) => {
+                                    *pid.lock().unwrap() = Some(found.trim().to_string());
+                                    header.split_off(end + 1)
+                                }
+                                None => std::mem::take(&mut header),
+                            }
+                        }
+                        None => continue,
+                    }
+                };
+
+                pending.extend_from_slice(&data);
+                let text = take_utf8(&mut pending);
+                if !text.is_empty() {
+                    let _ = app.emit("terminal-output", TerminalOutput { session_id: session_id.clone(), data: text });
+                }
+            }
+
+            let exit_code = EngineClient::from_env().ok().and_then(|client| client.exec_exit_code(&exec_id).ok());
+            sessions.lock().unwrap().remove(&session_id);
+            let _ = app.emit("terminal-exit", TerminalExit { session_id, exit_code });
+        });
+
+        Ok(info)
+    }
+
+    pub fn write(&self, session_id: &str, data: &str) -> Result<(), String> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(session_id).ok_or_else(|| "Terminal session not found".to_string())?;
+        let mut stdin = session.stdin.lock().unwrap();
+        stdin
+            .write_all(data.as_bytes())
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to terminal: {}", e))
+    }
+
+    pub fn resize(&self, session_id: &str, rows: u16, cols: u16) -> Result<(), String> {
+        let exec_id = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|session| session.exec_id.clone())
+            .ok_or_else(|| "Terminal session not found".to_string())?;
+
+        let client = EngineClient::from_env()?;
+        client
+            .resize_exec(&exec_id, rows, cols)
+            .map_err(|e| format!("Failed to resize terminal: {}", e))
+    }
+
+    /// Kills the session's process tree and closes the connection.
+    pub fn close(&self, session_id: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .ok_or_else(|| "Terminal session not found".to_string())?;
+
+        let client = EngineClient::from_env()?;
+        session.kill(&client);
+        Ok(())
+    }
+
+    pub fn list(&self, project_id: Option<&str>) -> Vec<TerminalSessionInfo> {
+        let mut sessions: Vec<TerminalSessionInfo> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|session| project_id.map(|p| session.info.project_id == p).unwrap_or(true))
+            .map(|session| session.info.clone())
+            .collect();
+        sessions.sort_by_key(|session| session.created_at);
+        sessions
+    }
+}
+
+/// Decodes as much of `buf` as forms complete UTF-8, leaving a trailing
+/// partial character for the next chunk.
+fn take_utf8(buf: &mut Vec<u8>) -> String {
+    match std::str::from_utf8(buf) {
+        Ok(text) => {
+            let text = text.to_string();
+            buf.clear();
+            text
+        }
+        Err(e) if e.error_len().is_none() => {
+            let rest = buf.split_off(e.valid_up_to());
+            let text = String::from_utf8_lossy(buf).to_string();
+            *buf = rest;
+            text
+        }
+        Err(_) => {
+            let text = String::from_utf8_lossy(buf).to_string();
+            buf.clear();
+            text
+        }
+    }
+}
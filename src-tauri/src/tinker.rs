@@ -0,0 +1,219 @@
+use crate::docker::DockerManager;
+use crate::engine::{Connection, EngineClient, ExecOptions, LineSplitter, PID_MARKER, PID_WRAPPER};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+pub const DEFAULT_EVAL_TIMEOUT_SECS: u64 = 30;
+
+// Echoed after every snippet so we know where its output ends
+const SENTINEL_PREFIX: &str = "__GODMODE_TINKER_DONE_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkerSessionInfo {
+    pub project_id: String,
+    pub container: String,
+    pub started_at: DateTime<Utc>,
+    pub evaluations: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TinkerResult {
+    pub output: String,
+    pub duration_ms: u64,
+}
+
+struct TinkerSession {
+    container_id: String,
+    container: String,
+    started_at: DateTime<Utc>,
+    evaluations: AtomicUsize,
+    stdin: Mutex<Connection>,
+    // Output lines from the reader thread; `None` once tinker has exited
+    output: Mutex<Receiver<Option<String>>>,
+    pid: Arc<Mutex<Option<String>>>,
+}
+
+impl TinkerSession {
+    fn info(&self, project_id: &str) -> TinkerSessionInfo {
+        TinkerSessionInfo {
+            project_id: project_id.to_string(),
+            container: self.container.clone(),
+            started_at: self.started_at,
+            evaluations: self.evaluations.load(Ordering::SeqCst),
+        }
+    }
+
+    fn kill(&self) {
+        if let Some(pid) = self.pid.lock().unwrap().clone() {
+            if let Ok(client) = EngineClient::from_env() {
+                let _ = client.kill_process_tree(&self.container_id, &pid);
+            }
+        }
+        let _ = self.stdin.lock().unwrap().shutdown();
+    }
+}
+
+/// One long-lived `php artisan tinker` process per project, so variables
+/// survive between evaluations.
+#[derive(Default)]
+pub struct TinkerManager {
+    sessions: Mutex<HashMap<String, Arc<TinkerSession>>>,
+}
+
+impl TinkerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the project's session, starting tinker if it is not running.
+    pub fn start(&self, project_id: &str, project_path: &str, project_name: &str) -> Result<TinkerSessionInfo, String> {
+        self.session(project_id, project_path, project_name).map(|session| session.info(project_id))
+    }
+
+    fn session(&self, project_id: &str, project_path: &str, project_name: &str) -> Result<Arc<TinkerSession>, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(session) = sessions.get(project_id) {
+            return Ok(session.clone());
+        }
+
+        let client = EngineClient::from_env()?;
+        let container = DockerManager::find_service_container(&client, project_path, project_name, "app")?;
+        let options = ExecOptions {
+            working_dir: Some("/var/www/html".to_string()),
+            attach_stdin: true,
+            ..Default::default()
+        };
+
+        let exec_id = client
+            .create_exec(&container.id, &["sh", "-c", PID_WRAPPER, "sh", "php", "artisan", "tinker"], &options)
+            .map_err(|e| format!("Failed to start tinker: {}", e))?;
+        let (stdin, mut reader) = client
+            .start_exec_attached(&exec_id, false)
+            .map_err(|e| format!("Failed to start tinker: {}", e))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let pid = Arc::new(Mutex::new(None));
+        let reader_pid = pid.clone();
+        std::thread::spawn(move || {
+            let mut splitter = LineSplitter::default();
+            let forward = |line: String| match line.strip_prefix(PID_MARKER) {
+                Some(found) => *reader_pid.lock().unwrap() = Some(found.trim().to_string()),
+                None => {
+                    let _ = sender.send(Some(line));
+                }
+            };
+
+            while let Ok(Some((kind, data))) = reader.next_frame() {
+                for (_, line) in splitter.push(kind, &data) {
+                    forward(line);
+                }
+            }
+            for (_, line) in splitter.finish() {
+                forward(line);
+            }
+            let _ = sender.send(None);
+        });
+
+        println!("[Tinker] Started session for project {} in {}", project_id, container.name());
+
+        let session = Arc::new(TinkerSession {
+            container_id: container.id.clone(),
+            container: container.name(),
+            started_at: Utc::now(),
+            evaluations: AtomicUsize::new(0),
+            stdin: Mutex::new(stdin),
+            output: Mutex::new(receiver),
+            pid,
+        });
+        sessions.insert(project_id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Evaluates a snippet in the project's session and returns its output.
+    pub fn eval(&self, project_id: &str, project_path: &str, project_name: &str, code: &str, timeout: Duration) -> Result<TinkerResult, String> {
+        let session = self.session(project_id, project_path, project_name)?;
+
+        // Holding the receiver serialises evaluations on the same session
+        let output = session.output.lock().unwrap();
+        let started = Instant::now();
+        let eval_id = Uuid::new_v4().simple().to_string();
+        let sentinel = format!("{}{}", SENTINEL_PREFIX, eval_id);
+
+        let input = format!("{}\necho \"\\n{}\\n\";\n", code.trim_end(), sentinel);
+        {
+            let mut stdin = session.stdin.lock().unwrap();
+            if let Err(e) = stdin.write_all(input.as_bytes()).and_then(|_| stdin.flush()) {
+                drop(stdin);
+                self.remove(project_id, &session);
+                return Err(format!("Failed to send code to tinker: {}", e));
+            }
+        }
+        session.evaluations.fetch_add(1, Ordering::SeqCst);
+
+        let mut lines: Vec<String> = Vec::new();
+        loop {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            match output.recv_timeout(remaining) {
+                Ok(Some(line)) if line.trim() == sentinel => break,
+                // Output of an earlier evaluation that timed out
+                Ok(Some(line)) if line.trim().starts_with(SENTINEL_PREFIX) => lines.clear(),
+                Ok(Some(line)) => lines.push(line),
+                Ok(None) | Err(RecvTimeoutError::Disconnected) => {
+                    drop(output);
+                    self.remove(project_id, &session);
+                    return Err(format!("Tinker exited unexpectedly:\n{}", lines.join("\n")));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!(
+                        "Evaluation timed out after {}s; reset the session if it is stuck",
+                        timeout.as_secs()
+                    ));
+                }
+            }
+        }
+
+        // The echo adds a blank line before the sentinel
+        if lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
+            lines.pop();
+        }
+
+        Ok(TinkerResult {
+            output: lines.join("\n"),
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Kills the running session and starts a fresh one.
+    pub fn reset(&self, project_id: &str, project_path: &str, project_name: &str) -> Result<TinkerSessionInfo, String> {
+        self.close(project_id);
+        self.start(project_id, project_path, project_name)
+    }
+
+    pub fn close(&self, project_id: &str) {
+        if let Some(session) = self.sessions.lock().unwrap().remove(project_id) {
+            session.kill();
+            println!("[Tinker] Closed session for project {}", project_id);
+        }
+    }
+
+    pub fn info(&self, project_id: &str) -> Option<TinkerSessionInfo> {
+        self.sessions.lock().unwrap().get(project_id).map(|session| session.info(project_id))
+    }
+
+    // Drops a dead session, unless it has already been replaced
+    fn remove(&self, project_id: &str, session: &Arc<TinkerSession>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.get(project_id).map(|s| Arc::ptr_eq(s, session)).unwrap_or(false) {
+            sessions.remove(project_id);
+        }
+        drop(sessions);
+        session.kill();
+    }
+}
@@ -0,0 +1,282 @@
+use crate::project::ProjectManager;
+use crate::proxy::ProxyManager;
+use crate::state::Project;
+use chrono::{DateTime, Datelike, Duration, Utc};
+use rcgen::{
+    date_time_ymd, BasicConstraints, Certificate, CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair, KeyUsagePurpose,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CA_COMMON_NAME: &str = "Laravel GodMode Local CA";
+const CA_ORGANIZATION: &str = "Laravel GodMode";
+const CA_VALIDITY_DAYS: i64 = 3650;
+// Browsers reject leaf certificates valid for more than 825 days
+const CERT_VALIDITY_DAYS: i64 = 825;
+
+/// Where generated compose files expect a project's certificate, relative to the project.
+pub const PROJECT_CERTS_DIR: &str = "docker/certs";
+
+// Keeps two threads from creating the CA at the same time
+static CA_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateAuthorityInfo {
+    pub common_name: String,
+    pub cert_path: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Command that adds the CA to the system trust store on this platform.
+    pub trust_command: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub hosts: Vec<String>,
+    pub cert_path: String,
+    pub key_path: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CaMetadata {
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+/// A local root CA, created once per machine, that signs certificates for
+/// project domains so they can be served over HTTPS without warnings once
+/// the CA is trusted.
+pub struct CertificateManager;
+
+impl CertificateManager {
+    fn ca_dir() -> PathBuf {
+        ProjectManager::app_data_dir().join("ca")
+    }
+
+    fn ca_cert_path() -> PathBuf {
+        Self::ca_dir().join("rootCA.pem")
+    }
+
+    fn ca_key_path() -> PathBuf {
+        Self::ca_dir().join("rootCA-key.pem")
+    }
+
+    fn ca_metadata_path() -> PathBuf {
+        Self::ca_dir().join("ca.json")
+    }
+
+    /// The CA's details, or `None` if it has not been created yet.
+    pub fn ca_info() -> Result<Option<CertificateAuthorityInfo>, String> {
+        let metadata_path = Self::ca_metadata_path();
+        if !metadata_path.exists() || !Self::ca_cert_path().exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&metadata_path).map_err(|e| format!("Failed to read CA metadata: {}", e))?;
+        let metadata: CaMetadata = serde_json::from_str(&content).map_err(|e| format!("Failed to parse CA metadata: {}", e))?;
+
+        Ok(Some(Self::info_from(&metadata)))
+    }
+
+    fn info_from(metadata: &CaMetadata) -> CertificateAuthorityInfo {
+        let cert_path = Self::ca_cert_path().to_string_lossy().to_string();
+        CertificateAuthorityInfo {
+            common_name: CA_COMMON_NAME.to_string(),
+            trust_command: Self::trust_command(&cert_path),
+            cert_path,
+            created_at: metadata.created_at,
+            expires_at: metadata.expires_at,
+        }
+    }
+
+    fn trust_command(cert_path: &str) -> String {
+        if cfg!(target_os = "macos") {
+            format!("sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain \"{}\"", cert_path)
+        } else if cfg!(target_os = "windows") {
+            format!("certutil -addstore -f ROOT \"{}\"", cert_path)
+        } else {
+            format!(
+                "sudo cp \"{}\" /usr/local/share/ca-certificates/laravel-godmode.crt && sudo update-ca-certificates",
+                cert_path
+            )
+        }
+    }
+
+    /// Creates the root CA if it does not exist yet.
+    pub fn ensure_ca() -> Result<CertificateAuthorityInfo, String> {
+        let _guard = CA_LOCK.lock().unwrap();
+
+        if let Some(info) = Self::ca_info()? {
+            if Self::ca_key_path().exists() {
+                return Ok(info);
+            }
+        }
+
+        let ca_dir = Self::ca_dir();
+        fs::create_dir_all(&ca_dir).map_err(|e| format!("Failed to create CA directory: {}", e))?;
+
+        let created_at = Utc::now();
+        let metadata = CaMetadata {
+            created_at,
+            expires_at: created_at + Duration::days(CA_VALIDITY_DAYS),
+        };
+
+        let key = KeyPair::generate().map_err(|e| format!("Failed to generate CA key: {}", e))?;
+        let cert = Self::ca_params(&metadata)
+            .self_signed(&key)
+            .map_err(|e| format!("Failed to create CA certificate: {}", e))?;
+
+        write_private(&Self::ca_key_path(), &key.serialize_pem())?;
+        fs::write(Self::ca_cert_path(), cert.pem()).map_err(|e| format!("Failed to write CA certificate: {}", e))?;
+        let metadata_json =
+            serde_json::to_string_pretty(&metadata).map_err(|e| format!("Failed to serialize CA metadata: {}", e))?;
+        fs::write(Self::ca_metadata_path(), metadata_json).map_err(|e| format!("Failed to write CA metadata: {}", e))?;
+
+        println!("[TLS] Created local root CA in {:?}", ca_dir);
+        Ok(Self::info_from(&metadata))
+    }
+
+    fn ca_params(metadata: &CaMetadata) -> CertificateParams {
+        let mut params = CertificateParams::default();
+        let mut name = DistinguishedName::new();
+        name.push(DnType::CommonName, CA_COMMON_NAME);
+        name.push(DnType::OrganizationName, CA_ORGANIZATION);
+        params.distinguished_name = name;
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign, KeyUsagePurpose::DigitalSignature];
+        set_validity(&mut params, metadata.created_at - Duration::days(1), metadata.expires_at);
+        params
+    }
+
+    /// Loads the CA as an issuer. The certificate is rebuilt from the same
+    /// name and key, which is all a signature needs to chain to the original.
+    fn load_ca() -> Result<(Certificate, KeyPair), String> {
+        Self::ensure_ca()?;
+
+        let content = fs::read_to_string(Self::ca_metadata_path()).map_err(|e| format!("Failed to read CA metadata: {}", e))?;
+        let metadata: CaMetadata = serde_json::from_str(&content).map_err(|e| format!("Failed to parse CA metadata: {}", e))?;
+        let key_pem = fs::read_to_string(Self::ca_key_path()).map_err(|e| format!("Failed to read CA key: {}", e))?;
+        let key = KeyPair::from_pem(&key_pem).map_err(|e| format!("Failed to parse CA key: {}", e))?;
+
+        let cert = Self::ca_params(&metadata)
+            .self_signed(&key)
+            .map_err(|e| format!("Failed to load CA certificate: {}", e))?;
+        Ok((cert, key))
+    }
+
+    /// Copies the CA certificate to `destination` so it can be imported
+    /// into a browser or another machine's trust store.
+    pub fn export_ca(destination: &str) -> Result<String, String> {
+        Self::ensure_ca()?;
+
+        let mut destination = PathBuf::from(destination);
+        if destination.is_dir() {
+            destination = destination.join("laravel-godmode-rootCA.pem");
+        }
+
+        fs::copy(Self::ca_cert_path(), &destination).map_err(|e| format!("Failed to export CA certificate: {}", e))?;
+        Ok(destination.to_string_lossy().to_string())
+    }
+
+    /// Issues a certificate for `hosts` (DNS names or IP addresses), signed by the local CA.
+    pub fn issue(hosts: &[String], cert_path: &Path, key_path: &Path) -> Result<CertificateInfo, String> {
+        let (ca_cert, ca_key) = Self::load_ca()?;
+
+        let mut params =
+            CertificateParams::new(hosts.to_vec()).map_err(|e| format!("Invalid certificate host name: {}", e))?;
+        let mut name = DistinguishedName::new();
+        name.push(DnType::CommonName, hosts.first().cloned().unwrap_or_default());
+        name.push(DnType::OrganizationName, CA_ORGANIZATION);
+        params.distinguished_name = name;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature, KeyUsagePurpose::KeyEncipherment];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        params.use_authority_key_identifier_extension = true;
+
+        let now = Utc::now();
+        let expires_at = now + Duration::days(CERT_VALIDITY_DAYS);
+        set_validity(&mut params, now - Duration::days(1), expires_at);
+
+        let key = KeyPair::generate().map_err(|e| format!("Failed to generate certificate key: {}", e))?;
+        let cert = params
+            .signed_by(&key, &ca_cert, &ca_key)
+            .map_err(|e| format!("Failed to sign certificate: {}", e))?;
+
+        if let Some(parent) = cert_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create certificate directory: {}", e))?;
+        }
+        fs::write(cert_path, cert.pem()).map_err(|e| format!("Failed to write certificate: {}", e))?;
+        write_private(key_path, &key.serialize_pem())?;
+
+        Ok(CertificateInfo {
+            hosts: hosts.to_vec(),
+            cert_path: cert_path.to_string_lossy().to_string(),
+            key_path: key_path.to_string_lossy().to_string(),
+            expires_at,
+        })
+    }
+
+    /// Host names a project's certificate covers: its `.test` domain, every
+    /// subdomain of it and plain localhost.
+    pub fn project_hosts(project_name: &str) -> Vec<String> {
+        let domain = ProxyManager::project_domain(project_name);
+        vec![
+            domain.clone(),
+            format!("*.{}", domain),
+            "localhost".to_string(),
+            "127.0.0.1".to_string(),
+        ]
+    }
+
+    /// Issues the project's certificate into `docker/certs`, replacing any existing one.
+    pub fn issue_for_project(project_path: &str, project_name: &str) -> Result<CertificateInfo, String> {
+        let certs_dir = Path::new(project_path).join(PROJECT_CERTS_DIR);
+        let info = Self::issue(
+            &Self::project_hosts(project_name),
+            &certs_dir.join("cert.pem"),
+            &certs_dir.join("key.pem"),
+        )?;
+
+        println!("[TLS] Issued certificate for {}", info.hosts.join(", "));
+        Ok(info)
+    }
+
+    /// Issues the project's certificate if its compose file mounts one that is missing.
+    pub fn ensure_project_certificate(project: &Project) -> Result<(), String> {
+        let project_path = Path::new(&project.path);
+        let compose = fs::read_to_string(project_path.join("docker-compose.yml")).unwrap_or_default();
+        if !compose.contains(&format!("./{}", PROJECT_CERTS_DIR)) {
+            return Ok(());
+        }
+
+        let certs_dir = project_path.join(PROJECT_CERTS_DIR);
+        if certs_dir.join("cert.pem").exists() && certs_dir.join("key.pem").exists() {
+            return Ok(());
+        }
+
+        Self::issue_for_project(&project.path, &project.name).map(|_| ())
+    }
+}
+
+// Validity is kept to whole days; the day before issuing covers clock skew
+fn set_validity(params: &mut CertificateParams, not_before: DateTime<Utc>, not_after: DateTime<Utc>) {
+    params.not_before = date_time_ymd(not_before.year(), not_before.month() as u8, not_before.day() as u8);
+    params.not_after = date_time_ymd(not_after.year(), not_after.month() as u8, not_after.day() as u8);
+}
+
+// Private keys are only readable by the current user
+fn write_private(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("Failed to write private key: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict private key permissions: {}", e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::docker::DockerManager;
+use crate::engine::{ContainerSummary, EngineClient, EngineEvent};
+use crate::jobs::JobRegistry;
+use crate::project::ProjectManager;
+use crate::state::{Project, ProjectStatus};
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// A Starting/Stopping/Building status older than this with no job behind it
+// is left over from a crash and may be corrected
+const STALE_TRANSITION_MINUTES: i64 = 15;
+
+// Container actions that can change what a project's status should be
+const TRACKED_ACTIONS: &[&str] = &["create", "start", "restart", "die", "stop", "kill", "oom", "pause", "unpause", "destroy", "health_status"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStatusChange {
+    pub project_id: String,
+    pub project_name: String,
+    pub previous: ProjectStatus,
+    pub status: ProjectStatus,
+    pub status_message: Option<String>,
+    /// What triggered the change, e.g. "blog_app die (exit 137)".
+    pub reason: String,
+}
+
+/// Follows Docker container events and keeps `Project.status` in sync with
+/// what is actually running, including changes made outside the app.
+pub struct ProjectWatcher;
+
+impl ProjectWatcher {
+    /// Starts the watcher thread. It reconnects whenever the daemon goes
+    /// away and resyncs every project after each (re)connect.
+    pub fn spawn(app: AppHandle, jobs: Arc<JobRegistry>) {
+        std::thread::spawn(move || loop {
+            match Self::watch(&app, &jobs) {
+                Ok(()) => println!("[Watcher] Event stream closed"),
+                Err(e) => println!("[Watcher] {}", e),
+            }
+            std::thread::sleep(RECONNECT_DELAY);
+        });
+    }
+
+    fn watch(app: &AppHandle, jobs: &JobRegistry) -> Result<(), String> {
+        let client = EngineClient::from_env()?;
+
+        // Subscribe before resyncing so nothing that happens in between is missed
+        let (_connection, events) = client
+            .events(&json!({ "type": ["container"] }))
+            .map_err(|e| format!("Failed to subscribe to Docker events: {}", e))?;
+        println!("[Watcher] Subscribed to Docker events");
+
+        for project in ProjectManager::load_all_projects()?.values() {
+            Self::sync_project(app, jobs, &client, project, "resync");
+        }
+
+        for event in events {
+            let event = event.map_err(|e| format!("Failed to read Docker event: {}", e))?;
+            if !TRACKED_ACTIONS.contains(&event.base_action()) {
+                continue;
+            }
+
+            let projects = match ProjectManager::load_all_projects() {
+                Ok(projects) => projects,
+                Err(e) => {
+                    println!("[Watcher] Failed to load projects: {}", e);
+                    continue;
+                }
+            };
+
+            if let Some(project) = projects.values().find(|p| Self::owns(p, &event)) {
+                Self::sync_project(app, jobs, &client, project, &Self::describe(&event));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Matches an event to a project by compose project label, falling back
+    /// to the `{name}_` container name prefix.
+    fn owns(project: &Project, event: &EngineEvent) -> bool {
+        if let Some(compose_project) = event.compose_project() {
+            return compose_project == DockerManager::compose_project_name(&project.path);
+        }
+
+        event
+            .container_name()
+            .map(|name| name.starts_with(&format!("{}_", project.name)))
+            .unwrap_or(false)
+    }
+
+    fn describe(event: &EngineEvent) -> String {
+        let name = event.container_name().unwrap_or(&event.actor.id);
+        match event.actor.attributes.get("exitCode") {
+            Some(code) => format!("{} {} (exit {})", name, event.action, code),
+            None => format!("{} {}", name, event.action),
+        }
+    }
+
+    fn sync_project(app: &AppHandle, jobs: &JobRegistry, client: &EngineClient, project: &Project, reason: &str) {
+        if Self::is_busy(jobs, project) {
+            return;
+        }
+
+        let containers = match DockerManager::find_project_containers(client, &project.path, &project.name) {
+            Ok(containers) => containers,
+            Err(e) => {
+                println!("[Watcher] Failed to list containers for {}: {}", project.name, e);
+                return;
+            }
+        };
+
+        let (status, message) = Self::derive_status(&containers);
+        if status == project.status && message == project.status_message {
+            return;
+        }
+
+        println!("[Watcher] {} is now {:?} ({})", project.name, status, reason);
+        let saved = match &message {
+            Some(message) => ProjectManager::mark_project_error(&project.id, message),
+            None => ProjectManager::update_project_status(&project.id, status.clone()),
+        };
+        if let Err(e) = saved {
+            println!("[Watcher] Failed to update {}: {}", project.name, e);
+            return;
+        }
+
+        let _ = app.emit(
+            "project-status-changed",
+            ProjectStatusChange {
+                project_id: project.id.clone(),
+                project_name: project.name.clone(),
+                previous: project.status.clone(),
+                status,
+                status_message: message,
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    /// The app's own start/stop/rebuild owns the status while it runs.
+    fn is_busy(jobs: &JobRegistry, project: &Project) -> bool {
+        matches!(project.status, ProjectStatus::Starting | ProjectStatus::Stopping | ProjectStatus::Building)
+            && (jobs.has_active(&project.id) || (Utc::now() - project.updated_at).num_minutes() < STALE_TRANSITION_MINUTES)
+    }
+
+    /// Running when every container is up, stopped when none are, and an
+    /// error naming the culprits when some are up while others crashed, keep
+    /// restarting or fail their healthcheck. Containers that exited cleanly
+    /// (e.g. one-off init services) are ignored.
+    fn derive_status(containers: &[ContainerSummary]) -> (ProjectStatus, Option<String>) {
+        let running = containers.iter().filter(|c| c.state == "running").count();
+        if running == 0 {
+            return (ProjectStatus::Stopped, None);
+        }
+
+        let exit_code = Regex::new(r"^Exited \((\d+)\)").unwrap();
+        let failed: Vec<String> = containers
+            .iter()
+            .filter(|c| match c.state.as_str() {
+                "running" => c.health().as_deref() == Some("unhealthy"),
+                "created" => false,
+                "exited" => exit_code
+                    .captures(&c.status)
+                    .map(|caps| &caps[1] != "0")
+                    .unwrap_or(true),
+                _ => true,
+            })
+            .map(|c| format!("{} ({})", c.service().map(|s| s.to_string()).unwrap_or_else(|| c.name()), c.status))
+            .collect();
+
+        if failed.is_empty() {
+            (ProjectStatus::Running, None)
+        } else {
+            (ProjectStatus::Error, Some(format!("Unhealthy services: {}", failed.join(", "))))
+        }
+    }
+}
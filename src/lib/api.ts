@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core'
-import type { Project, ProjectConfig, Template, Settings, ServiceStatus, SupervisorStatus, BackupInfo } from './types'
+import type { Project, ProjectConfig, ConfigUpdate, PortConfig, ServiceConfig, PortConflict, ProxyStatus, CertificateAuthorityInfo, CertificateInfo, HostsPreview, ImportResult, LoadDiagnostic, ArchiveManifest, ExportSummary, Template, TemplatePack, TemplatePreview, TemplateRoot, Settings, ContainerRuntimeInfo, ServiceStatus, ContainerStats, ProjectMetrics, MetricsSamplerInfo, JobInfo, LogLine, LogStreamInfo, LogStreamOptions, SupervisorStatus, BackupInfo, TerminalOptions, TerminalSessionInfo, TinkerSessionInfo, TinkerResult } from './types'
 
 // Project API
 export const api = {
@@ -56,8 +56,8 @@ export const api = {
   },
 
   // Streaming versions (with real-time output)
-  async startProjectStreaming(projectId: string): Promise<string> {
-    return await invoke('start_project_streaming', { projectId })
+  async startProjectStreaming(projectId: string, waitHealthy?: boolean, timeoutSecs?: number): Promise<string> {
+    return await invoke('start_project_streaming', { projectId, waitHealthy, timeoutSecs })
   },
 
   async stopProjectStreaming(projectId: string): Promise<string> {
@@ -72,6 +72,15 @@ export const api = {
     return await invoke('install_laravel_streaming', { projectId })
   },
 
+  // Jobs (long-running operations, emitted as 'job-update' events)
+  async listJobs(projectId?: string): Promise<JobInfo[]> {
+    return await invoke('list_jobs', { projectId })
+  },
+
+  async cancelJob(jobId: string): Promise<JobInfo> {
+    return await invoke('cancel_job', { jobId })
+  },
+
   async getProjectStatus(projectId: string): Promise<ServiceStatus[]> {
     return await invoke('get_project_status', { projectId })
   },
@@ -80,10 +89,110 @@ export const api = {
     return await invoke('get_container_logs', { projectId, service, lines })
   },
 
+  // Follow-mode logs (lines arrive as 'container-log' events)
+  async streamContainerLogs(projectId: string, options: LogStreamOptions = {}): Promise<string> {
+    return await invoke('stream_container_logs', { projectId, options })
+  },
+
+  async stopContainerLogs(streamId: string): Promise<void> {
+    return await invoke('stop_container_logs', { streamId })
+  },
+
+  async listLogStreams(projectId?: string): Promise<LogStreamInfo[]> {
+    return await invoke('list_log_streams', { projectId })
+  },
+
+  async getLogStreamBuffer(streamId: string, limit?: number): Promise<LogLine[]> {
+    return await invoke('get_log_stream_buffer', { streamId, limit })
+  },
+
   async getServicesStatus(projectId: string): Promise<ServiceStatus[]> {
     return await invoke('get_services_status', { projectId })
   },
 
+  async getProjectStats(projectId: string): Promise<ContainerStats[]> {
+    return await invoke('get_project_stats', { projectId })
+  },
+
+  async suggestPorts(ports?: PortConfig, services?: ServiceConfig, projectId?: string): Promise<PortConfig> {
+    return await invoke('suggest_ports', { ports, services, projectId })
+  },
+
+  async checkPortConflicts(projectId: string): Promise<PortConflict[]> {
+    return await invoke('check_port_conflicts', { projectId })
+  },
+
+  // Proxy
+  async getProxyStatus(): Promise<ProxyStatus> {
+    return await invoke('get_proxy_status')
+  },
+
+  async enableProxy(httpPort?: number, httpsPort?: number): Promise<ProxyStatus> {
+    return await invoke('enable_proxy', { httpPort, httpsPort })
+  },
+
+  async disableProxy(): Promise<ProxyStatus> {
+    return await invoke('disable_proxy')
+  },
+
+  async refreshProxy(): Promise<ProxyStatus> {
+    return await invoke('refresh_proxy')
+  },
+
+  // Hosts file
+  async previewHostsFile(path?: string): Promise<HostsPreview> {
+    return await invoke('preview_hosts_file', { path })
+  },
+
+  async applyHostsFile(path?: string): Promise<HostsPreview> {
+    return await invoke('apply_hosts_file', { path })
+  },
+
+  async removeHostsEntries(path?: string): Promise<HostsPreview> {
+    return await invoke('remove_hosts_entries', { path })
+  },
+
+  async setHostsManagement(managed: boolean, path?: string): Promise<HostsPreview> {
+    return await invoke('set_hosts_management', { managed, path })
+  },
+
+  // Certificates
+  async getCertificateAuthority(): Promise<CertificateAuthorityInfo | null> {
+    return await invoke('get_certificate_authority')
+  },
+
+  async createCertificateAuthority(): Promise<CertificateAuthorityInfo> {
+    return await invoke('create_certificate_authority')
+  },
+
+  async exportCertificateAuthority(destination: string): Promise<string> {
+    return await invoke('export_certificate_authority', { destination })
+  },
+
+  async issueProjectCertificate(projectId: string): Promise<CertificateInfo> {
+    return await invoke('issue_project_certificate', { projectId })
+  },
+
+  async startMetricsSampling(projectIds?: string[], intervalSecs?: number): Promise<MetricsSamplerInfo> {
+    return await invoke('start_metrics_sampling', { projectIds, intervalSecs })
+  },
+
+  async stopMetricsSampling(): Promise<void> {
+    return await invoke('stop_metrics_sampling')
+  },
+
+  async getMetricsSampler(): Promise<MetricsSamplerInfo> {
+    return await invoke('get_metrics_sampler')
+  },
+
+  async getLatestMetrics(): Promise<ProjectMetrics[]> {
+    return await invoke('get_latest_metrics')
+  },
+
+  async getMetricsHistory(projectId: string): Promise<Record<string, ContainerStats[]>> {
+    return await invoke('get_metrics_history', { projectId })
+  },
+
   // Templates
   async getTemplates(): Promise<Template[]> {
     return await invoke('get_templates')
@@ -93,6 +202,33 @@ export const api = {
     return await invoke('get_template', { templateType })
   },
 
+  async getTemplateRoots(): Promise<TemplateRoot[]> {
+    return await invoke('get_template_roots')
+  },
+
+  async listTemplatePacks(): Promise<TemplatePack[]> {
+    return await invoke('list_template_packs')
+  },
+
+  async installTemplatePack(source: string): Promise<TemplatePack> {
+    return await invoke('install_template_pack', { source })
+  },
+
+  async removeTemplatePack(name: string): Promise<void> {
+    return await invoke('remove_template_pack', { name })
+  },
+
+  async previewProjectFromTemplate(name: string, template: string, config: ProjectConfig, compareProjectId?: string): Promise<TemplatePreview> {
+    return await invoke('preview_project_from_template', {
+      request: { name, template, config },
+      compareProjectId
+    })
+  },
+
+  async previewProjectRegeneration(projectId: string): Promise<TemplatePreview> {
+    return await invoke('preview_project_regeneration', { projectId })
+  },
+
   // Artisan
   async runArtisanCommand(projectId: string, command: string): Promise<string> {
     return await invoke('run_artisan_command', { projectId, command })
@@ -160,15 +296,64 @@ export const api = {
     return await invoke('stop_scheduler', { projectId })
   },
 
+  // Project Registry
+  async rescanProjects(): Promise<Project[]> {
+    return await invoke('rescan_projects')
+  },
+
+  async registerProject(path: string): Promise<Project> {
+    return await invoke('register_project', { path })
+  },
+
+  async unregisterProject(projectId: string): Promise<void> {
+    return await invoke('unregister_project', { projectId })
+  },
+
+  async getLoadDiagnostics(): Promise<LoadDiagnostic[]> {
+    return await invoke('get_load_diagnostics')
+  },
+
   // Project Cloning
-  async cloneProject(projectId: string, newName: string): Promise<Project> {
-    return await invoke('clone_project', { projectId, newName })
+  async cloneProject(projectId: string, newName: string, copyVolumes?: boolean): Promise<Project> {
+    return await invoke('clone_project', { projectId, newName, copyVolumes })
   },
 
-  async importProject(sourcePath: string, name: string): Promise<Project> {
+  async importProject(sourcePath: string, name: string): Promise<ImportResult> {
     return await invoke('import_project', { sourcePath, name })
   },
 
+  // Project Rename & Move
+  async renameProject(projectId: string, newName: string): Promise<Project> {
+    return await invoke('rename_project', { projectId, newName })
+  },
+
+  async moveProject(projectId: string, destination: string): Promise<Project> {
+    return await invoke('move_project', { projectId, destination })
+  },
+
+  // Project Config
+  async updateProjectConfig(projectId: string, config: ProjectConfig, rebuild?: boolean): Promise<ConfigUpdate> {
+    return await invoke('update_project_config', { projectId, config, rebuild })
+  },
+
+  // Project Archives
+  async exportProject(projectId: string, destination: string, includeDatabase?: boolean): Promise<ExportSummary> {
+    return await invoke('export_project', { projectId, destination, includeDatabase })
+  },
+
+  async readProjectArchive(archivePath: string): Promise<ArchiveManifest> {
+    return await invoke('read_project_archive', { archivePath })
+  },
+
+  async importProjectArchive(archivePath: string, name?: string, start?: boolean): Promise<Project> {
+    return await invoke('import_project_archive', { archivePath, name, start })
+  },
+
+  // Git Import
+  async importProjectFromGit(url: string, name?: string, branch?: string, start?: boolean): Promise<ImportResult> {
+    return await invoke('import_project_from_git', { url, name, branch, start })
+  },
+
   // Cache
   async clearCache(projectId: string, cacheType: string = 'all'): Promise<string> {
     return await invoke('clear_cache', { projectId, cacheType })
@@ -221,6 +406,26 @@ export const api = {
     return await invoke('run_tinker_command', { projectId, code })
   },
 
+  async startTinkerSession(projectId: string): Promise<TinkerSessionInfo> {
+    return await invoke('start_tinker_session', { projectId })
+  },
+
+  async evalTinker(projectId: string, code: string, timeoutSecs?: number): Promise<TinkerResult> {
+    return await invoke('eval_tinker', { projectId, code, timeoutSecs })
+  },
+
+  async resetTinkerSession(projectId: string): Promise<TinkerSessionInfo> {
+    return await invoke('reset_tinker_session', { projectId })
+  },
+
+  async closeTinkerSession(projectId: string): Promise<void> {
+    return await invoke('close_tinker_session', { projectId })
+  },
+
+  async getTinkerSession(projectId: string): Promise<TinkerSessionInfo | null> {
+    return await invoke('get_tinker_session', { projectId })
+  },
+
   async runComposerCommand(projectId: string, command: string): Promise<string> {
     return await invoke('run_composer_command', { projectId, command })
   },
@@ -229,6 +434,26 @@ export const api = {
     return await invoke('run_npm_command', { projectId, command })
   },
 
+  async openTerminalSession(projectId: string, options?: TerminalOptions): Promise<TerminalSessionInfo> {
+    return await invoke('open_terminal_session', { projectId, options })
+  },
+
+  async writeTerminal(sessionId: string, data: string): Promise<void> {
+    return await invoke('write_terminal', { sessionId, data })
+  },
+
+  async resizeTerminal(sessionId: string, rows: number, cols: number): Promise<void> {
+    return await invoke('resize_terminal', { sessionId, rows, cols })
+  },
+
+  async closeTerminalSession(sessionId: string): Promise<void> {
+    return await invoke('close_terminal_session', { sessionId })
+  },
+
+  async listTerminalSessions(projectId?: string): Promise<TerminalSessionInfo[]> {
+    return await invoke('list_terminal_sessions', { projectId })
+  },
+
   // Supervisor
   async getSupervisorStatus(projectId: string): Promise<SupervisorStatus> {
     return await invoke('get_supervisor_status', { projectId })
@@ -260,10 +485,25 @@ export const api = {
     return await invoke('get_docker_version')
   },
 
+  async getContainerRuntime(): Promise<ContainerRuntimeInfo> {
+    return await invoke('get_container_runtime')
+  },
+
+  async refreshContainerRuntime(): Promise<ContainerRuntimeInfo> {
+    return await invoke('refresh_container_runtime')
+  },
+
   // Custom Templates
   async createProjectFromCustomTemplate(name: string, template: any): Promise<Project> {
     return await invoke('create_project_from_custom_template', {
       request: { name, template }
     })
+  },
+
+  async previewProjectFromCustomTemplate(name: string, template: any, compareProjectId?: string): Promise<TemplatePreview> {
+    return await invoke('preview_project_from_custom_template', {
+      request: { name, template },
+      compareProjectId
+    })
   }
 }
@@ -18,6 +18,12 @@ export const webserverBlocks: ServiceBlock[] = [
         type: 'number',
         default: 80
       },
+      {
+        key: 'https_port',
+        label: 'HTTPS Port',
+        type: 'number',
+        default: 8443
+      },
       {
         key: 'client_max_body_size',
         label: 'Max Body Size',
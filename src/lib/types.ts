@@ -1,4 +1,5 @@
 export interface Project {
+  schema_version?: number
   id: string
   name: string
   template: string
@@ -7,10 +8,29 @@ export interface Project {
   updated_at: string
   config: ProjectConfig
   status: ProjectStatus
+  status_message?: string
 }
 
 export type ProjectStatus = 'running' | 'stopped' | 'error' | 'building' | 'starting' | 'stopping'
 
+export interface ServiceReadiness {
+  project_id: string
+  service: string
+  container: string
+  state: string
+  health?: string
+  ready: boolean
+}
+
+export interface ProjectStatusChange {
+  project_id: string
+  project_name: string
+  previous: ProjectStatus
+  status: ProjectStatus
+  status_message?: string
+  reason: string
+}
+
 export interface ProjectConfig {
   php_version?: string
   node_version: string
@@ -20,6 +40,7 @@ export interface ProjectConfig {
   install_laravel: boolean
   services: ServiceConfig
   ports: PortConfig
+  database_name?: string | null
 }
 
 export interface ServiceConfig {
@@ -37,6 +58,7 @@ export interface PortConfig {
   redis: number
   phpmyadmin: number
   mailhog: number
+  https: number
 }
 
 export interface ServiceStatus {
@@ -47,6 +69,83 @@ export interface ServiceStatus {
   health?: string
 }
 
+export type JobKind = 'start' | 'stop' | 'rebuild' | 'install_laravel' | 'import'
+
+export type JobStatus = 'queued' | 'running' | 'completed' | 'failed' | 'cancelled'
+
+export interface JobInfo {
+  id: string
+  project_id: string
+  kind: JobKind
+  status: JobStatus
+  created_at: string
+  started_at?: string
+  finished_at?: string
+  error?: string
+}
+
+export interface LogStreamOptions {
+  services?: string[]
+  since?: string
+  until?: string
+  include?: string
+  exclude?: string
+  tail?: number
+}
+
+export interface LogLine {
+  stream_id: string
+  project_id: string
+  service: string
+  container: string
+  stream: 'stdout' | 'stderr'
+  timestamp?: string
+  line: string
+}
+
+export interface LogStreamInfo {
+  id: string
+  project_id: string
+  services: string[]
+  options: LogStreamOptions
+  active: boolean
+  buffered_lines: number
+}
+
+export interface ContainerStats {
+  name: string
+  service?: string
+  container_id: string
+  cpu_percent: number
+  memory_usage: number
+  memory_limit: number
+  memory_percent: number
+  network_rx: number
+  network_tx: number
+  block_read: number
+  block_write: number
+  timestamp: string
+}
+
+export interface ProjectMetrics {
+  project_id: string
+  project_name: string
+  timestamp: string
+  cpu_percent: number
+  memory_usage: number
+  network_rx: number
+  network_tx: number
+  block_read: number
+  block_write: number
+  containers: ContainerStats[]
+}
+
+export interface MetricsSamplerInfo {
+  running: boolean
+  project_ids: string[]
+  interval_secs: number
+}
+
 export interface SupervisorProgram {
   name: string
   status: string
@@ -68,15 +167,134 @@ export interface Template {
   icon: string
   template_type: string
   category: string
+  version?: string | null
+  source?: TemplateSource
+  pack?: string | null
+  path?: string
+  overrides?: TemplateSource[]
+}
+
+export type TemplateSource = 'user' | 'team' | 'pack' | 'bundled'
+
+export interface TemplateRoot {
+  path: string
+  source: TemplateSource
+  pack?: string
+}
+
+export interface TemplatePack {
+  name: string
+  version: string
+  description: string
+  path: string
+  templates: string[]
+  installed_at?: string | null
+}
+
+export interface PreviewFile {
+  path: string
+  content: string
+  change?: 'added' | 'modified' | 'unchanged'
+  diff?: string
+}
+
+export interface TemplatePreview {
+  project_path: string
+  directories: string[]
+  files: PreviewFile[]
+}
+
+export interface RegeneratedFile {
+  path: string
+  outcome: 'unchanged' | 'updated' | 'merged' | 'conflicted' | 'created'
+}
+
+export interface ConfigUpdate {
+  project: Project
+  files: RegeneratedFile[]
+  conflicts: string[]
+  rebuilt: boolean
 }
 
 export interface Settings {
+  schema_version?: number
   projects_path: string
   auto_start_projects: boolean
   preferred_editor: string
   default_php_version: string
   default_node_version: string
   theme: string
+  compose_backend?: ComposeBackend | null
+  proxy?: ProxySettings
+  hosts?: HostsSettings
+  team_templates_path?: string | null
+}
+
+export interface DetectionReport {
+  template: string
+  framework_version?: string
+  php_version?: string
+  node_version: string
+  services: ServiceConfig
+  features: string[]
+  evidence: string[]
+}
+
+export interface ImportResult {
+  project: Project
+  detection: DetectionReport
+}
+
+export interface LoadDiagnostic {
+  path: string
+  kind: 'failed' | 'migrated'
+  message: string
+  backup_path?: string
+  at: string
+}
+
+export interface ArchiveManifest {
+  format_version: number
+  project_name: string
+  template: string
+  exported_at: string
+  includes_database: boolean
+}
+
+export interface ExportSummary {
+  path: string
+  file_count: number
+  size_bytes: number
+  includes_database: boolean
+}
+
+export interface HostsSettings {
+  managed: boolean
+  path?: string
+}
+
+export interface HostsPreview {
+  path: string
+  changed: boolean
+  added: string[]
+  removed: string[]
+  diff: string
+}
+
+export interface ProxySettings {
+  enabled: boolean
+  http_port: number
+  https_port: number
+}
+
+export type ComposeBackend = 'docker-plugin' | 'standalone' | 'podman'
+
+export interface ContainerRuntimeInfo {
+  engine?: string
+  engine_version?: string
+  compose_backend?: ComposeBackend
+  compose_command?: string
+  compose_version?: string
 }
 
 export interface BackupInfo {
@@ -84,3 +302,81 @@ export interface BackupInfo {
   size: number
   created_at: string
 }
+
+export interface TerminalOptions {
+  service?: string
+  command?: string[]
+  working_dir?: string
+  user?: string
+  rows?: number
+  cols?: number
+}
+
+export interface TerminalSessionInfo {
+  id: string
+  project_id: string
+  service: string
+  container: string
+  command: string[]
+  created_at: string
+}
+
+export interface TerminalOutput {
+  session_id: string
+  data: string
+}
+
+export interface TerminalExit {
+  session_id: string
+  exit_code?: number
+}
+
+export interface TinkerSessionInfo {
+  project_id: string
+  container: string
+  started_at: string
+  evaluations: number
+}
+
+export interface TinkerResult {
+  output: string
+  duration_ms: number
+}
+
+export interface PortConflict {
+  service: string
+  port: number
+  project?: string
+}
+
+export interface ProxyRoute {
+  project_id: string
+  project_name: string
+  service: string
+  host: string
+  container: string
+  port: number
+}
+
+export interface ProxyStatus {
+  enabled: boolean
+  running: boolean
+  http_port: number
+  https_port: number
+  routes: ProxyRoute[]
+}
+
+export interface CertificateAuthorityInfo {
+  common_name: string
+  cert_path: string
+  created_at: string
+  expires_at: string
+  trust_command: string
+}
+
+export interface CertificateInfo {
+  hosts: string[]
+  cert_path: string
+  key_path: string
+  expires_at: string
+}